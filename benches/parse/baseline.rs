@@ -0,0 +1,216 @@
+//! The previous parser, as a baseline: walking a `Peekable<Chars>` one `char` at a time,
+//! tracking the line and column on every step, and collecting numbers into a `String`
+//! for `str::parse` (only its strict mode, on valid JSON).
+
+use rustic_json::{Arr, Num, Obj, Str, Value};
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Line and column of a failure (kept, as the previous parser built them eagerly).
+#[derive(Debug, Clone, Copy)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+type ParseResult<T> = Result<T, Position>;
+
+pub fn parse(str: &str) -> ParseResult<Value> {
+    let mut parser = Parser {
+        chars: str.chars().peekable(),
+        position: Position { line: 1, column: 1 },
+    };
+    parser.skip_ws();
+    let value = parser.parse_value()?;
+    parser.skip_ws();
+    match parser.chars.peek() {
+        Some(_) => Err(parser.position),
+        None => Ok(value),
+    }
+}
+
+struct Parser<'s> {
+    chars: Peekable<Chars<'s>>,
+    position: Position,
+}
+
+impl Parser<'_> {
+    fn peek_char(&mut self) -> ParseResult<char> {
+        self.chars.peek().copied().ok_or(self.position)
+    }
+
+    fn skip_char(&mut self) {
+        let next = self.chars.next().expect("should have just peeked");
+        if next == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+    }
+
+    fn expect_char(&mut self, expected: char) -> ParseResult<()> {
+        if self.peek_char()? != expected {
+            return Err(self.position);
+        }
+        self.skip_char();
+        Ok(())
+    }
+
+    fn skip_ws(&mut self) {
+        while let Ok(' ' | '\n' | '\r' | '\t') = self.peek_char() {
+            self.skip_char();
+        }
+    }
+
+    fn parse_value(&mut self) -> ParseResult<Value> {
+        match self.peek_char()? {
+            'n' => self.expect_str("null").and(Ok(Value::Null)),
+            't' => self.expect_str("true").and(Ok(Value::Boolean(true))),
+            'f' => self.expect_str("false").and(Ok(Value::Boolean(false))),
+            '-' | '0'..='9' => self.parse_number().map(Value::Number),
+            '"' => self.parse_string().map(Value::String),
+            '[' => self.parse_array().map(Value::Array),
+            '{' => self.parse_object().map(Value::Object),
+            _ => Err(self.position),
+        }
+    }
+
+    fn expect_str(&mut self, str: &str) -> ParseResult<()> {
+        str.chars().try_for_each(|c| self.expect_char(c))
+    }
+
+    fn parse_number(&mut self) -> ParseResult<Num> {
+        let num_error = self.position;
+        let mut buf = String::new();
+        if let Ok('-') = self.peek_char() {
+            buf.push('-');
+            self.skip_char();
+        }
+        match self.peek_char()? {
+            '0' => {
+                buf.push('0');
+                self.skip_char();
+            }
+            '1'..='9' => self.push_digits(&mut buf),
+            _ => return Err(self.position),
+        }
+        if let Ok('.') = self.peek_char() {
+            buf.push('.');
+            self.skip_char();
+            self.push_digits(&mut buf);
+        }
+        if let Ok(e @ ('E' | 'e')) = self.peek_char() {
+            buf.push(e);
+            self.skip_char();
+            if let Ok(sign @ ('+' | '-')) = self.peek_char() {
+                buf.push(sign);
+                self.skip_char();
+            }
+            self.push_digits(&mut buf);
+        }
+        let f: f64 = buf.parse().map_err(|_| self.position)?;
+        Num::new(f).ok_or(num_error)
+    }
+
+    fn push_digits(&mut self, buf: &mut String) {
+        while let Ok(digit @ '0'..='9') = self.peek_char() {
+            buf.push(digit);
+            self.skip_char();
+        }
+    }
+
+    fn parse_string(&mut self) -> ParseResult<Str> {
+        self.expect_char('"')?;
+        let mut buf = String::new();
+        loop {
+            match self.peek_char()? {
+                '"' => {
+                    self.skip_char();
+                    break;
+                }
+                '\\' => buf.push(self.parse_escape()?),
+                c if c >= '\x20' => {
+                    buf.push(c);
+                    self.skip_char();
+                }
+                _ => return Err(self.position),
+            }
+        }
+        Ok(Str::from(buf))
+    }
+
+    fn parse_escape(&mut self) -> ParseResult<char> {
+        let utf16_decode_error = self.position;
+        self.expect_char('\\')?;
+        let escaped = self.peek_char()?;
+        self.skip_char();
+        Ok(match escaped {
+            '"' | '\\' | '/' => escaped,
+            'b' => '\x08',
+            'f' => '\x0c',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let unit = self.parse_hex_4()?;
+                if let Some(Ok(decoded)) = char::decode_utf16([unit]).next() {
+                    return Ok(decoded);
+                }
+                self.expect_str("\\u")?;
+                let unit_2 = self.parse_hex_4()?;
+                match char::decode_utf16([unit, unit_2]).next() {
+                    Some(Ok(decoded)) => decoded,
+                    _ => return Err(utf16_decode_error),
+                }
+            }
+            _ => return Err(self.position),
+        })
+    }
+
+    fn parse_hex_4(&mut self) -> ParseResult<u16> {
+        let mut buf = 0;
+        for _ in 0..4 {
+            let hex = self.peek_char()?.to_digit(16).ok_or(self.position)?;
+            buf = buf << 4 | hex as u16;
+            self.skip_char();
+        }
+        Ok(buf)
+    }
+
+    fn parse_array(&mut self) -> ParseResult<Arr> {
+        self.expect_char('[')?;
+        self.skip_ws();
+        let mut buf = Vec::new();
+        while self.peek_char()? != ']' {
+            if !buf.is_empty() {
+                self.expect_char(',')?;
+                self.skip_ws();
+            }
+            buf.push(self.parse_value()?);
+            self.skip_ws();
+        }
+        self.skip_char();
+        Ok(Arr::from(buf))
+    }
+
+    fn parse_object(&mut self) -> ParseResult<Obj> {
+        self.expect_char('{')?;
+        self.skip_ws();
+        let mut buf = Vec::new();
+        while self.peek_char()? != '}' {
+            if !buf.is_empty() {
+                self.expect_char(',')?;
+                self.skip_ws();
+            }
+            let key = self.parse_string()?;
+            self.skip_ws();
+            self.expect_char(':')?;
+            self.skip_ws();
+            buf.push((key, self.parse_value()?));
+            self.skip_ws();
+        }
+        self.skip_char();
+        Ok(Obj::from_iter(buf))
+    }
+}
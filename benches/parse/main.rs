@@ -0,0 +1,142 @@
+//! Parsing throughput on a few large generated payloads.
+//!
+//! Run with `cargo bench --bench parse` (a plain `main`, as the built-in
+//! benchmark harness is unstable and we have no dependencies),
+//! comparing with the previous parser (see [`baseline`]).
+
+use rustic_json::{Arr, Num, Obj, Str, Value};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+mod baseline;
+
+/// Deterministic pseudo-random numbers (xorshift).
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+fn num(f: f64) -> Value {
+    Value::Number(Num::new(f).expect("finite number"))
+}
+
+/// Records with short keys, prose strings (some escaped or non-ASCII) and a few numbers.
+fn records(rng: &mut Rng, count: usize) -> Value {
+    const WORDS: [&str; 8] = [
+        "lorem",
+        "ipsum",
+        "dolor",
+        "sit",
+        "amet",
+        "café",
+        "\"quoted\"",
+        "line\nbreak",
+    ];
+    Value::Array(
+        (0..count)
+            .map(|i| {
+                let text = (0..rng.next(40) + 10)
+                    .map(|_| WORDS[rng.next(8) as usize])
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Value::Object(Obj::from([
+                    (Str::from("id"), num(i as f64)),
+                    (Str::from("name"), Value::from(format!("record #{}", i))),
+                    (Str::from("active"), Value::Boolean(rng.next(2) == 0)),
+                    (Str::from("score"), num(rng.next(1 << 20) as f64 / 64.0)),
+                    (Str::from("text"), Value::from(text)),
+                    (
+                        Str::from("tags"),
+                        Value::Array(Arr::from([Value::from("a"), Value::Null])),
+                    ),
+                ]))
+            })
+            .collect(),
+    )
+}
+
+/// Long plain strings (e.g. embedded documents).
+fn strings(rng: &mut Rng, count: usize) -> Value {
+    Value::Array(
+        (0..count)
+            .map(|_| {
+                let len = rng.next(4000) as usize + 100;
+                Value::from(
+                    (0..len)
+                        .map(|_| char::from(b'a' + rng.next(26) as u8))
+                        .collect::<String>(),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Integers (e.g. IDs and counters).
+fn integers(rng: &mut Rng, count: usize) -> Value {
+    Value::Array((0..count).map(|_| num(rng.next(1 << 40) as f64)).collect())
+}
+
+/// Mostly numbers (integers and floats).
+fn numbers(rng: &mut Rng, count: usize) -> Value {
+    Value::Array(
+        (0..count)
+            .map(|_| {
+                let f = match rng.next(3) {
+                    0 => rng.next(1 << 32) as f64,
+                    1 => rng.next(1 << 53) as f64 / 1e6 - 4e9,
+                    _ => (rng.next(1 << 20) as f64).powi(3) * 1e-30,
+                };
+                num(f)
+            })
+            .collect(),
+    )
+}
+
+/// Throughput in MiB/s of the fastest of the runs in about a second (the least disturbed).
+fn measure(text: &str, parse: impl Fn(&str) -> Value) -> f64 {
+    let expected: Value = text.parse().expect("valid JSON");
+    assert_eq!(parse(text), expected);
+    let mut elapsed = Duration::ZERO;
+    let mut fastest = Duration::MAX;
+    while elapsed < Duration::from_secs(1) {
+        let start = Instant::now();
+        black_box(parse(black_box(text)));
+        let duration = start.elapsed();
+        elapsed += duration;
+        fastest = fastest.min(duration);
+    }
+    text.len() as f64 / fastest.as_secs_f64() / 1048576.0
+}
+
+fn bench(name: &str, text: &str) {
+    let baseline = measure(text, |text| baseline::parse(text).expect("valid JSON"));
+    let current = measure(text, |text| text.parse().expect("valid JSON"));
+    println!(
+        "{:<24} {:>6.2} MiB: {:>7.1} MiB/s (baseline {:>6.1} MiB/s, x{:.2})",
+        name,
+        text.len() as f64 / 1048576.0,
+        current,
+        baseline,
+        current / baseline,
+    );
+}
+
+fn main() {
+    let mut rng = Rng(0x5eed);
+    let records = records(&mut rng, 20_000);
+    let strings = strings(&mut rng, 2_000);
+    let numbers = numbers(&mut rng, 200_000);
+    let integers = integers(&mut rng, 200_000);
+    bench("records (compact)", &records.to_string());
+    bench("records (pretty)", &format!("{:#}", records));
+    bench("strings", &strings.to_string());
+    bench("numbers (compact)", &numbers.to_string());
+    bench("numbers (pretty)", &format!("{:#}", numbers));
+    bench("integers", &integers.to_string());
+}
@@ -0,0 +1,369 @@
+//! Generation of the trait impls, as source code.
+
+use crate::parse::{Case, Data, Field, Fields, Input, Variant};
+use std::fmt::Write;
+
+const J: &str = "::rustic_json";
+const RESULT: &str = "::core::result::Result<Self, ::rustic_json::FromJsonError>";
+
+pub(crate) fn to_json(input: &Input) -> Result<String, String> {
+    let body = match &input.data {
+        Data::Struct(fields) => {
+            let (pattern, content) = fields_to_json(fields, "Self", input.attrs.rename_all);
+            format!("let {} = self; {}", pattern, content)
+        }
+        Data::Enum(variants) => {
+            let mut arms = String::new();
+            for variant in variants {
+                let name = variant_name(input, variant);
+                let ctor = format!("Self::{}", variant.name);
+                let (pattern, content) =
+                    fields_to_json(&variant.fields, &ctor, variant.attrs.rename_all);
+                let is_unit = matches!(variant.fields, Fields::Unit);
+                let value = match (&input.attrs.tag, &input.attrs.content) {
+                    _ if input.attrs.untagged => content,
+                    (None, _) if is_unit => format!("{J}::Value::String({J}::Str::from({:?}))", name),
+                    (None, _) => object([(name.as_str(), content)]),
+                    (Some(tag), _) if is_unit => object([(tag.as_str(), string(&name))]),
+                    (Some(tag), Some(content_key)) => {
+                        object([(tag.as_str(), string(&name)), (content_key.as_str(), content)])
+                    }
+                    (Some(tag), None) => match &variant.fields {
+                        Fields::Tuple(fields) if fields.len() != 1 => {
+                            return Err(format!(
+                                "internally tagged tuple variant `{}` is not supported",
+                                variant.name
+                            ))
+                        }
+                        // insert the tag into the (expected) object
+                        _ => format!(
+                            "match {} {{ {J}::Value::Object(mut obj) => {{ obj.insert({J}::Str::from({:?}), {}); {J}::Value::Object(obj) }} other => {} }}",
+                            content,
+                            tag,
+                            string(&name),
+                            not_an_object(&format!("cannot tag variant `{}::{}` internally", input.name, variant.name))
+                        ),
+                    },
+                };
+                write!(arms, "{} => {},", pattern, value).expect("infallible");
+            }
+            format!("match self {{ {} }}", arms)
+        }
+    };
+    Ok(format!(
+        "{} {{ fn to_json(&self) -> {J}::Value {{ {} }} }}",
+        impl_header(input, "ToJson"),
+        body
+    ))
+}
+
+pub(crate) fn from_json(input: &Input) -> Result<String, String> {
+    let body = match &input.data {
+        Data::Struct(fields) => {
+            format!(
+                "::core::result::Result::Ok({})",
+                fields_from_json(fields, "Self", "value", input.attrs.rename_all)
+            )
+        }
+        Data::Enum(variants) => enum_from_json(input, variants),
+    };
+    Ok(format!(
+        "{} {{ #[allow(unused_variables, clippy::redundant_closure_call)] fn from_json(value: &{J}::Value) -> {RESULT} {{ {} }} }}",
+        impl_header(input, "FromJson"),
+        body
+    ))
+}
+
+fn impl_header(input: &Input, trait_name: &str) -> String {
+    let generics = &input.generics;
+    let mut predicates = generics.where_predicates.clone();
+    for param in &generics.type_params {
+        if !predicates.is_empty() {
+            predicates.push_str(", ");
+        }
+        write!(predicates, "{}: {J}::{}", param, trait_name).expect("infallible");
+    }
+    format!(
+        "#[automatically_derived] impl<{}> {J}::{} for {}<{}> {} {}",
+        generics.impl_params.join(", "),
+        trait_name,
+        input.name,
+        generics.type_args.join(", "),
+        if predicates.is_empty() { "" } else { "where" },
+        predicates
+    )
+}
+
+fn string(value: &str) -> String {
+    format!("{J}::Value::String({J}::Str::from({:?}))", value)
+}
+
+fn object<'a>(members: impl IntoIterator<Item = (&'a str, String)>) -> String {
+    let members: Vec<_> = members
+        .into_iter()
+        .map(|(key, value)| format!("({J}::Str::from({:?}), {})", key, value))
+        .collect();
+    format!(
+        "{J}::Value::Object({J}::Obj::from([{}]))",
+        members.join(", ")
+    )
+}
+
+/// A panic for a value `other` that should have been an object (since it would be lost).
+fn not_an_object(context: &str) -> String {
+    format!(
+        "::core::panic!(\"{{}}: not an object but {{}}\", {:?}, other)",
+        context
+    )
+}
+
+fn variant_name(input: &Input, variant: &Variant) -> String {
+    match (&variant.attrs.rename, input.attrs.rename_all) {
+        (Some(rename), _) => rename.clone(),
+        (None, Some(case)) => case.apply_to_variant(&variant.name),
+        (None, None) => variant.name.clone(),
+    }
+}
+
+fn field_key(field: &Field, rename_all: Option<Case>) -> String {
+    match (&field.attrs.rename, rename_all) {
+        (Some(rename), _) => rename.clone(),
+        (None, Some(case)) => case.apply_to_field(field.name()),
+        (None, None) => String::from(field.name()),
+    }
+}
+
+/// Returns a pattern binding the fields (as `__0`, `__1`, ...) and an expression converting them.
+fn fields_to_json(fields: &Fields, ctor: &str, rename_all: Option<Case>) -> (String, String) {
+    match fields {
+        Fields::Unit => (String::from(ctor), format!("{J}::Value::Null")),
+        Fields::Tuple(fields) => {
+            let bindings: Vec<_> = (0..fields.len()).map(|i| format!("__{}", i)).collect();
+            let pattern = format!("{}({})", ctor, bindings.join(", "));
+            let content = match &bindings[..] {
+                [binding] => format!("{J}::ToJson::to_json({})", binding),
+                _ => {
+                    let elements: Vec<_> = bindings
+                        .iter()
+                        .map(|binding| format!("{J}::ToJson::to_json({})", binding))
+                        .collect();
+                    format!(
+                        "{J}::Value::Array({J}::Arr::from([{}]))",
+                        elements.join(", ")
+                    )
+                }
+            };
+            (pattern, content)
+        }
+        Fields::Named(fields) => {
+            let mut pattern = String::new();
+            let mut content = format!("let mut obj = {J}::Obj::new();");
+            for (i, field) in fields.iter().enumerate() {
+                write!(
+                    pattern,
+                    "{}: __{}, ",
+                    field.ident.as_deref().expect("named"),
+                    i
+                )
+                .expect("infallible");
+                if field.attrs.skip {
+                    continue;
+                }
+                if field.attrs.flatten {
+                    write!(
+                        content,
+                        "match {J}::ToJson::to_json(__{}) {{ {J}::Value::Object(inner) => obj.extend(inner), other => {} }}",
+                        i,
+                        not_an_object(&format!("cannot flatten field `{}`", field.name()))
+                    )
+                    .expect("infallible");
+                } else {
+                    write!(
+                        content,
+                        "obj.insert({J}::Str::from({:?}), {J}::ToJson::to_json(__{}));",
+                        field_key(field, rename_all),
+                        i
+                    )
+                    .expect("infallible");
+                }
+            }
+            let pattern = format!("{} {{ {} }}", ctor, pattern);
+            (
+                pattern,
+                format!("{{ {} {J}::Value::Object(obj) }}", content),
+            )
+        }
+    }
+}
+
+/// Returns an expression constructing the value from the `Value` named `var`,
+/// using `?` to propagate errors.
+fn fields_from_json(fields: &Fields, ctor: &str, var: &str, rename_all: Option<Case>) -> String {
+    match fields {
+        Fields::Unit => format!(
+            "match {var} {{ {J}::Value::Null => {ctor}, _ => return ::core::result::Result::Err({J}::FromJsonError::invalid_type(\"null\", {var})) }}"
+        ),
+        Fields::Tuple(fields) if fields.len() == 1 => {
+            format!("{}({J}::FromJson::from_json({})?)", ctor, var)
+        }
+        Fields::Tuple(fields) => {
+            let elements: Vec<_> = (0..fields.len())
+                .map(|i| format!("{J}::FromJson::from_json(&arr[{i}]).map_err(|error| error.within({i}_usize))?"))
+                .collect();
+            format!(
+                "match {var} {{
+                    {J}::Value::Array(arr) if arr.len() == {len} => {ctor}({elements}),
+                    {J}::Value::Array(arr) => return ::core::result::Result::Err({J}::FromJsonError::new(
+                        {J}::FromJsonErrorKind::InvalidLength {{ expected: {len}, found: arr.len() }}
+                    )),
+                    _ => return ::core::result::Result::Err({J}::FromJsonError::invalid_type(\"array\", {var})),
+                }}",
+                len = fields.len(),
+                elements = elements.join(", ")
+            )
+        }
+        Fields::Named(fields) => {
+            let mut members = String::new();
+            for field in fields {
+                let ident = field.ident.as_deref().expect("named");
+                let key = field_key(field, rename_all);
+                let missing = if field.attrs.default {
+                    String::from("::core::default::Default::default()")
+                } else {
+                    // e.g. `None` for an `Option`
+                    format!(
+                        "{J}::FromJson::from_json(&{J}::Value::Null).map_err(|_| {J}::FromJsonError::new(
+                            {J}::FromJsonErrorKind::MissingField({J}::Str::from({key:?}))
+                        ))?"
+                    )
+                };
+                let value = if field.attrs.skip {
+                    String::from("::core::default::Default::default()")
+                } else if field.attrs.flatten {
+                    format!("{J}::FromJson::from_json({var})?")
+                } else {
+                    format!(
+                        "match obj.get({key:?}) {{
+                            ::core::option::Option::Some(member) => {J}::FromJson::from_json(member).map_err(|error| error.within({key:?}))?,
+                            ::core::option::Option::None => {missing},
+                        }}"
+                    )
+                };
+                write!(members, "{}: {},", ident, value).expect("infallible");
+            }
+            format!(
+                "{{
+                    let {J}::Value::Object(obj) = {var} else {{
+                        return ::core::result::Result::Err({J}::FromJsonError::invalid_type(\"object\", {var}));
+                    }};
+                    {ctor} {{ {members} }}
+                }}"
+            )
+        }
+    }
+}
+
+/// Wraps the construction of a variant in a closure, so errors can be adjusted.
+fn variant_from_json(variant: &Variant, var: &str) -> String {
+    let ctor = format!("Self::{}", variant.name);
+    let construct = fields_from_json(&variant.fields, &ctor, var, variant.attrs.rename_all);
+    format!(
+        "(|| -> {RESULT} {{ ::core::result::Result::Ok({}) }})()",
+        construct
+    )
+}
+
+fn enum_from_json(input: &Input, variants: &[Variant]) -> String {
+    let name = &input.name;
+    let unknown_variant = |var: &str| {
+        format!("::core::result::Result::Err({J}::FromJsonError::new({J}::FromJsonErrorKind::UnknownVariant({var}.clone())))")
+    };
+    if input.attrs.untagged {
+        let mut attempts = String::new();
+        for variant in variants {
+            write!(
+                attempts,
+                "if let ::core::result::Result::Ok(variant) = {} {{ return ::core::result::Result::Ok(variant); }}",
+                variant_from_json(variant, "value")
+            )
+            .expect("infallible");
+        }
+        let message = format!("data did not match any variant of untagged enum {}", name);
+        return format!(
+            "{} ::core::result::Result::Err({J}::FromJsonError::new({J}::FromJsonErrorKind::Custom(::std::string::String::from({:?}))))",
+            attempts, message
+        );
+    }
+
+    let Some(tag) = &input.attrs.tag else {
+        // externally tagged
+        let mut unit_arms = String::new();
+        let mut other_arms = String::new();
+        for variant in variants {
+            let json_name = variant_name(input, variant);
+            if matches!(variant.fields, Fields::Unit) {
+                write!(
+                    unit_arms,
+                    "{:?} => ::core::result::Result::Ok(Self::{}),",
+                    json_name, variant.name
+                )
+            } else {
+                write!(
+                    other_arms,
+                    "{:?} => {}.map_err(|error| error.within({:?})),",
+                    json_name,
+                    variant_from_json(variant, "content"),
+                    json_name
+                )
+            }
+            .expect("infallible");
+        }
+        return format!(
+            "match value {{
+                {J}::Value::String(str) => match &**str {{ {unit_arms} _ => {unknown_str} }},
+                {J}::Value::Object(obj) if obj.len() == 1 => {{
+                    let (key, content) = obj.iter().next().expect(\"one member\");
+                    match &**key {{ {other_arms} _ => {unknown_key} }}
+                }}
+                _ => ::core::result::Result::Err({J}::FromJsonError::invalid_type(\"string or single-member object\", value)),
+            }}",
+            unknown_str = unknown_variant("str"),
+            unknown_key = unknown_variant("key"),
+        );
+    };
+
+    let mut arms = String::new();
+    for variant in variants {
+        let json_name = variant_name(input, variant);
+        let construct = match (&variant.fields, &input.attrs.content) {
+            (Fields::Unit, _) => format!("::core::result::Result::Ok(Self::{})", variant.name),
+            (_, None) => variant_from_json(variant, "value"),
+            (_, Some(content_key)) => format!(
+                "match obj.get({content_key:?}) {{
+                    ::core::option::Option::Some(content) => {}.map_err(|error| error.within({content_key:?})),
+                    ::core::option::Option::None => ::core::result::Result::Err({J}::FromJsonError::new(
+                        {J}::FromJsonErrorKind::MissingField({J}::Str::from({content_key:?}))
+                    )),
+                }}",
+                variant_from_json(variant, "content")
+            ),
+        };
+        write!(arms, "{:?} => {},", json_name, construct).expect("infallible");
+    }
+    format!(
+        "let {J}::Value::Object(obj) = value else {{
+            return ::core::result::Result::Err({J}::FromJsonError::invalid_type(\"object\", value));
+        }};
+        let tag = match obj.get({tag:?}) {{
+            ::core::option::Option::Some({J}::Value::String(tag)) => tag,
+            ::core::option::Option::Some(other) => {{
+                return ::core::result::Result::Err({J}::FromJsonError::invalid_type(\"string\", other).within({tag:?}));
+            }}
+            ::core::option::Option::None => {{
+                return ::core::result::Result::Err({J}::FromJsonError::new({J}::FromJsonErrorKind::MissingField({J}::Str::from({tag:?}))));
+            }}
+        }};
+        match &**tag {{ {arms} _ => {unknown}.map_err(|error| error.within({tag:?})) }}",
+        unknown = unknown_variant("tag"),
+    )
+}
@@ -0,0 +1,30 @@
+//! Derive macros for the `ToJson` and `FromJson` traits of [`rustic_json`],
+//! re-exported (and documented) there.
+//!
+//! Built directly on [`proc_macro`], without any dependency.
+//!
+//! [`rustic_json`]: https://guilliamxavier.github.io/rustic_json/
+
+#![forbid(unsafe_code)]
+
+use proc_macro::TokenStream;
+
+mod expand;
+mod parse;
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    derive(input, expand::to_json)
+}
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    derive(input, expand::from_json)
+}
+
+fn derive(input: TokenStream, expand: fn(&parse::Input) -> Result<String, String>) -> TokenStream {
+    let code = parse::parse_input(input)
+        .and_then(|input| expand(&input))
+        .unwrap_or_else(|message| format!("::core::compile_error!({:?});", message));
+    code.parse().expect("valid generated code")
+}
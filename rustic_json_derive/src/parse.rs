@@ -0,0 +1,540 @@
+//! Minimal parsing of the derive input (a struct or an enum) from raw tokens.
+
+use proc_macro::{Delimiter, Group, Spacing, TokenStream, TokenTree};
+use std::iter::Peekable;
+
+pub(crate) type ParseResult<T> = Result<T, String>;
+
+pub(crate) struct Input {
+    pub(crate) name: String,
+    pub(crate) generics: Generics,
+    pub(crate) attrs: ContainerAttrs,
+    pub(crate) data: Data,
+}
+
+#[derive(Default)]
+pub(crate) struct Generics {
+    /// Without defaults, e.g. `'a, T: Clone, const N: usize`.
+    pub(crate) impl_params: Vec<String>,
+    /// e.g. `'a, T, N`.
+    pub(crate) type_args: Vec<String>,
+    /// Type parameters only, which get a trait bound.
+    pub(crate) type_params: Vec<String>,
+    /// Without `where` nor trailing comma.
+    pub(crate) where_predicates: String,
+}
+
+pub(crate) enum Data {
+    Struct(Fields),
+    Enum(Vec<Variant>),
+}
+
+pub(crate) enum Fields {
+    Named(Vec<Field>),
+    Tuple(Vec<Field>),
+    Unit,
+}
+
+pub(crate) struct Field {
+    /// The Rust identifier (possibly raw, e.g. `r#type`), `None` for a tuple field.
+    pub(crate) ident: Option<String>,
+    pub(crate) attrs: FieldAttrs,
+    /// Whether the type is known not to convert to an object (see [`is_never_object`]).
+    pub(crate) is_never_object: bool,
+}
+
+impl Field {
+    /// The identifier without any `r#` prefix.
+    pub(crate) fn name(&self) -> &str {
+        let ident = self.ident.as_deref().expect("named field");
+        ident.strip_prefix("r#").unwrap_or(ident)
+    }
+}
+
+pub(crate) struct Variant {
+    pub(crate) name: String,
+    pub(crate) attrs: VariantAttrs,
+    pub(crate) fields: Fields,
+}
+
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    pub(crate) rename_all: Option<Case>,
+    pub(crate) tag: Option<String>,
+    pub(crate) content: Option<String>,
+    pub(crate) untagged: bool,
+}
+
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    pub(crate) rename: Option<String>,
+    pub(crate) rename_all: Option<Case>,
+}
+
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    pub(crate) rename: Option<String>,
+    pub(crate) skip: bool,
+    pub(crate) default: bool,
+    pub(crate) flatten: bool,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Case {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl Case {
+    fn from_name(name: &str) -> ParseResult<Self> {
+        Ok(match name {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return Err(format!("unknown `rename_all` case: {:?}", name)),
+        })
+    }
+
+    /// Converts a variant name, assumed to be in PascalCase.
+    pub(crate) fn apply_to_variant(self, name: &str) -> String {
+        match self {
+            Self::Lower => name.to_lowercase(),
+            Self::Upper => name.to_uppercase(),
+            Self::Pascal => String::from(name),
+            Self::Camel => {
+                let mut chars = name.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first.to_lowercase().chain(chars).collect()
+                })
+            }
+            _ => {
+                let mut snake = String::new();
+                for (i, c) in name.char_indices() {
+                    if i > 0 && c.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.extend(c.to_lowercase());
+                }
+                self.apply_to_field(&snake)
+            }
+        }
+    }
+
+    /// Converts a field name, assumed to be in snake_case.
+    pub(crate) fn apply_to_field(self, name: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => String::from(name),
+            Self::Upper | Self::ScreamingSnake => name.to_uppercase(),
+            Self::Pascal | Self::Camel => {
+                let mut result = String::new();
+                for (i, word) in name.split('_').enumerate() {
+                    let mut chars = word.chars();
+                    if let Some(first) = chars.next() {
+                        if i == 0 && matches!(self, Self::Camel) {
+                            result.extend(first.to_lowercase());
+                        } else {
+                            result.extend(first.to_uppercase());
+                        }
+                        result.extend(chars);
+                    }
+                }
+                result
+            }
+            Self::Kebab => name.replace('_', "-"),
+            Self::ScreamingKebab => name.to_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+type Tokens = Peekable<proc_macro::token_stream::IntoIter>;
+
+pub(crate) fn parse_input(input: TokenStream) -> ParseResult<Input> {
+    let mut tokens = input.into_iter().peekable();
+    let mut attrs = ContainerAttrs::default();
+    for (key, value) in parse_attrs(&mut tokens)? {
+        match (key.as_str(), value) {
+            ("rename_all", Some(value)) => attrs.rename_all = Some(Case::from_name(&value)?),
+            ("tag", Some(value)) => attrs.tag = Some(value),
+            ("content", Some(value)) => attrs.content = Some(value),
+            ("untagged", None) => attrs.untagged = true,
+            (key, _) => {
+                return Err(format!(
+                    "unknown or malformed container attribute `{}`",
+                    key
+                ))
+            }
+        }
+    }
+    skip_visibility(&mut tokens);
+    let keyword = expect_ident(&mut tokens)?;
+    let name = expect_ident(&mut tokens)?;
+    let mut generics = parse_generics(&mut tokens)?;
+    let data = match keyword.as_str() {
+        "struct" => {
+            parse_where(&mut tokens, &mut generics);
+            let fields = match tokens.next() {
+                Some(TokenTree::Group(group)) => parse_fields(&group)?,
+                _ => Fields::Unit,
+            };
+            parse_where(&mut tokens, &mut generics);
+            Data::Struct(fields)
+        }
+        "enum" => {
+            parse_where(&mut tokens, &mut generics);
+            match tokens.next() {
+                Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                    let is_internally_tagged = attrs.tag.is_some() && attrs.content.is_none();
+                    Data::Enum(parse_variants(group.stream(), is_internally_tagged)?)
+                }
+                _ => return Err(String::from("expected enum body")),
+            }
+        }
+        _ => return Err(String::from("only structs and enums are supported")),
+    };
+    if matches!(data, Data::Struct(_))
+        && (attrs.tag.is_some() || attrs.content.is_some() || attrs.untagged)
+    {
+        return Err(String::from(
+            "`tag`, `content` and `untagged` only apply to enums",
+        ));
+    }
+    if attrs.content.is_some() && attrs.tag.is_none() {
+        return Err(String::from("`content` requires `tag`"));
+    }
+    if attrs.untagged && attrs.tag.is_some() {
+        return Err(String::from("`untagged` conflicts with `tag`"));
+    }
+    Ok(Input {
+        name,
+        generics,
+        attrs,
+        data,
+    })
+}
+
+/// Parses outer attributes, returning the contents of the `#[json(...)]` ones
+/// as `key` or `key = "value"` pairs.
+fn parse_attrs(tokens: &mut Tokens) -> ParseResult<Vec<(String, Option<String>)>> {
+    let mut result = Vec::new();
+    while matches!(tokens.peek(), Some(TokenTree::Punct(punct)) if punct.as_char() == '#') {
+        tokens.next();
+        let Some(TokenTree::Group(group)) = tokens.next() else {
+            return Err(String::from("expected attribute"));
+        };
+        let mut inner = group.stream().into_iter();
+        match inner.next() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "json" => {
+                match (inner.next(), inner.next()) {
+                    (Some(TokenTree::Group(args)), None)
+                        if args.delimiter() == Delimiter::Parenthesis =>
+                    {
+                        result.extend(parse_attr_args(args.stream())?);
+                    }
+                    _ => return Err(String::from("expected `#[json(...)]`")),
+                }
+            }
+            // other attribute (e.g. doc comment)
+            _ => {}
+        }
+    }
+    Ok(result)
+}
+
+fn parse_attr_args(stream: TokenStream) -> ParseResult<Vec<(String, Option<String>)>> {
+    let mut result = Vec::new();
+    let mut tokens = stream.into_iter().peekable();
+    while tokens.peek().is_some() {
+        let key = expect_ident(&mut tokens)?;
+        let mut value = None;
+        if matches!(tokens.peek(), Some(TokenTree::Punct(punct)) if punct.as_char() == '=') {
+            tokens.next();
+            match tokens.next() {
+                Some(TokenTree::Literal(literal)) => value = Some(unquote(&literal.to_string())?),
+                _ => return Err(format!("expected string literal for `{}`", key)),
+            }
+        }
+        result.push((key, value));
+        match tokens.next() {
+            None => break,
+            Some(TokenTree::Punct(punct)) if punct.as_char() == ',' => {}
+            _ => return Err(String::from("expected `,` in attribute")),
+        }
+    }
+    Ok(result)
+}
+
+/// Simple string literal (no escapes) to its value.
+fn unquote(literal: &str) -> ParseResult<String> {
+    literal
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .filter(|value| !value.contains('\\'))
+        .map(String::from)
+        .ok_or_else(|| format!("expected simple string literal, found {}", literal))
+}
+
+fn skip_visibility(tokens: &mut Tokens) {
+    if matches!(tokens.peek(), Some(TokenTree::Ident(ident)) if ident.to_string() == "pub") {
+        tokens.next();
+        if matches!(tokens.peek(), Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis)
+        {
+            tokens.next();
+        }
+    }
+}
+
+fn expect_ident(tokens: &mut Tokens) -> ParseResult<String> {
+    match tokens.next() {
+        Some(TokenTree::Ident(ident)) => Ok(ident.to_string()),
+        other => Err(format!(
+            "expected identifier, found {:?}",
+            other.map(|t| t.to_string())
+        )),
+    }
+}
+
+fn is_punct(token: &TokenTree, c: char) -> bool {
+    matches!(token, TokenTree::Punct(punct) if punct.as_char() == c)
+}
+
+/// Collects tokens up to a `,` outside of angle brackets (consuming it), or to the end.
+fn collect_until_comma(tokens: &mut Tokens) -> Vec<TokenTree> {
+    let mut result = Vec::new();
+    let mut depth = 0_usize;
+    for token in tokens.by_ref() {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' && depth == 0 => break,
+            TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            TokenTree::Punct(punct) if punct.as_char() == '>' => {
+                // not the `>` of a `->`
+                let is_arrow = matches!(result.last(), Some(TokenTree::Punct(prev)) if prev.as_char() == '-' && prev.spacing() == Spacing::Joint);
+                if !is_arrow {
+                    depth = depth.saturating_sub(1);
+                }
+            }
+            _ => {}
+        }
+        result.push(token);
+    }
+    result
+}
+
+fn to_string(tokens: &[TokenTree]) -> String {
+    tokens.iter().cloned().collect::<TokenStream>().to_string()
+}
+
+fn parse_generics(tokens: &mut Tokens) -> ParseResult<Generics> {
+    let mut generics = Generics::default();
+    if !matches!(tokens.peek(), Some(token) if is_punct(token, '<')) {
+        return Ok(generics);
+    }
+    tokens.next();
+    // collect up to the matching `>`
+    let mut inner = Vec::new();
+    let mut depth = 1_usize;
+    for token in tokens.by_ref() {
+        if is_punct(&token, '<') {
+            depth += 1;
+        } else if is_punct(&token, '>') {
+            depth -= 1;
+            if depth == 0 {
+                break;
+            }
+        }
+        inner.push(token);
+    }
+    let mut inner = inner
+        .into_iter()
+        .collect::<TokenStream>()
+        .into_iter()
+        .peekable();
+    while inner.peek().is_some() {
+        let param = collect_until_comma(&mut inner);
+        // strip any default
+        let end = param
+            .iter()
+            .position(|token| is_punct(token, '='))
+            .unwrap_or(param.len());
+        let param = &param[..end];
+        match param {
+            [TokenTree::Punct(quote), TokenTree::Ident(lifetime), ..]
+                if quote.as_char() == '\'' =>
+            {
+                generics.type_args.push(format!("'{}", lifetime));
+            }
+            [TokenTree::Ident(keyword), TokenTree::Ident(name), ..]
+                if keyword.to_string() == "const" =>
+            {
+                generics.type_args.push(name.to_string());
+            }
+            [TokenTree::Ident(name), ..] => {
+                generics.type_args.push(name.to_string());
+                generics.type_params.push(name.to_string());
+            }
+            _ => return Err(String::from("unsupported generic parameter")),
+        }
+        generics.impl_params.push(to_string(param));
+    }
+    Ok(generics)
+}
+
+/// Parses an optional where clause, up to the body (or `;`).
+fn parse_where(tokens: &mut Tokens, generics: &mut Generics) {
+    if !matches!(tokens.peek(), Some(TokenTree::Ident(ident)) if ident.to_string() == "where") {
+        return;
+    }
+    tokens.next();
+    let mut predicates = Vec::new();
+    while let Some(token) = tokens.peek() {
+        if is_punct(token, ';')
+            || matches!(token, TokenTree::Group(group) if group.delimiter() == Delimiter::Brace)
+        {
+            break;
+        }
+        predicates.push(tokens.next().expect("peeked"));
+    }
+    while matches!(predicates.last(), Some(token) if is_punct(token, ',')) {
+        predicates.pop();
+    }
+    generics.where_predicates = to_string(&predicates);
+}
+
+fn parse_fields(group: &Group) -> ParseResult<Fields> {
+    let mut tokens = group.stream().into_iter().peekable();
+    let mut fields = Vec::new();
+    while tokens.peek().is_some() {
+        let mut attrs = FieldAttrs::default();
+        for (key, value) in parse_attrs(&mut tokens)? {
+            match (key.as_str(), value) {
+                ("rename", Some(value)) => attrs.rename = Some(value),
+                ("skip", None) => attrs.skip = true,
+                ("default", None) => attrs.default = true,
+                ("flatten", None) => attrs.flatten = true,
+                (key, _) => return Err(format!("unknown or malformed field attribute `{}`", key)),
+            }
+        }
+        skip_visibility(&mut tokens);
+        let ident = match group.delimiter() {
+            Delimiter::Brace => {
+                let ident = expect_ident(&mut tokens)?;
+                match tokens.next() {
+                    Some(token) if is_punct(&token, ':') => {}
+                    _ => return Err(String::from("expected `:`")),
+                }
+                Some(ident)
+            }
+            _ => {
+                if attrs.rename.is_some() || attrs.skip || attrs.default || attrs.flatten {
+                    return Err(String::from("attributes are not supported on tuple fields"));
+                }
+                None
+            }
+        };
+        let is_never_object = is_never_object(&collect_until_comma(&mut tokens));
+        if attrs.flatten && is_never_object {
+            return Err(format!(
+                "flattened field `{}` must convert to an object",
+                ident.as_deref().expect("named")
+            ));
+        }
+        fields.push(Field {
+            ident,
+            attrs,
+            is_never_object,
+        });
+    }
+    Ok(match group.delimiter() {
+        Delimiter::Brace => Fields::Named(fields),
+        _ => Fields::Tuple(fields),
+    })
+}
+
+const NEVER_OBJECT_TYPES: &[&str] = &[
+    "bool", "char", "str", "String", "Str", "Num", "Vec", "f32", "f64", "i8", "i16", "i32", "i64",
+    "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+/// Whether a type is known not to convert to an object:
+/// a primitive, a string, a `Vec`, an array, a slice or a tuple (possibly behind references).
+fn is_never_object(mut ty: &[TokenTree]) -> bool {
+    // strip references, like `&'a mut`
+    loop {
+        ty = match ty {
+            [TokenTree::Punct(punct), rest @ ..] if punct.as_char() == '&' => rest,
+            [TokenTree::Punct(quote), TokenTree::Ident(_), rest @ ..]
+                if quote.as_char() == '\'' =>
+            {
+                rest
+            }
+            [TokenTree::Ident(ident), rest @ ..] if ident.to_string() == "mut" => rest,
+            _ => break,
+        };
+    }
+    if let [TokenTree::Group(group)] = ty {
+        return matches!(
+            group.delimiter(),
+            Delimiter::Bracket | Delimiter::Parenthesis
+        );
+    }
+    // the last segment of the path, before any generic arguments
+    let end = ty
+        .iter()
+        .position(|token| is_punct(token, '<'))
+        .unwrap_or(ty.len());
+    match ty[..end].last() {
+        Some(TokenTree::Ident(ident)) => NEVER_OBJECT_TYPES.contains(&&*ident.to_string()),
+        _ => false,
+    }
+}
+
+fn parse_variants(stream: TokenStream, is_internally_tagged: bool) -> ParseResult<Vec<Variant>> {
+    let mut tokens = stream.into_iter().peekable();
+    let mut variants = Vec::new();
+    while tokens.peek().is_some() {
+        let mut attrs = VariantAttrs::default();
+        for (key, value) in parse_attrs(&mut tokens)? {
+            match (key.as_str(), value) {
+                ("rename", Some(value)) => attrs.rename = Some(value),
+                ("rename_all", Some(value)) => attrs.rename_all = Some(Case::from_name(&value)?),
+                (key, _) => {
+                    return Err(format!("unknown or malformed variant attribute `{}`", key))
+                }
+            }
+        }
+        let name = expect_ident(&mut tokens)?;
+        let fields = match tokens.peek() {
+            Some(TokenTree::Group(group)) => {
+                let fields = parse_fields(group)?;
+                tokens.next();
+                fields
+            }
+            _ => Fields::Unit,
+        };
+        // skip any discriminant, and the comma
+        collect_until_comma(&mut tokens);
+        let is_never_object = matches!(&fields, Fields::Tuple(fields) if fields.len() == 1 && fields[0].is_never_object);
+        if is_internally_tagged && is_never_object {
+            return Err(format!(
+                "internally tagged newtype variant `{}` must contain an object",
+                name
+            ));
+        }
+        variants.push(Variant {
+            name,
+            attrs,
+            fields,
+        });
+    }
+    Ok(variants)
+}
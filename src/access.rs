@@ -0,0 +1,229 @@
+use super::*;
+use std::ops;
+
+/// Typed accessors, each returning `None` for another variant.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::{json, Num};
+///
+/// let mut value = json!({"name": "demo", "tags": ["a", "b"], "port": 8080, "debug": null});
+///
+/// assert_eq!(value["name"].as_str(), Some("demo"));
+/// assert_eq!(value["port"].as_num(), Some(Num::from(8080)));
+/// assert_eq!(value["port"].as_str(), None);
+/// assert_eq!(value["tags"][1], json!("b"));
+/// assert!(value["debug"].is_null());
+/// // missing keys (and out-of-bounds indexes) read as null
+/// assert!(value["nope"][42].is_null());
+///
+/// value["tags"].as_array_mut().unwrap().push(json!("c"));
+/// value["debug"] = json!(true);
+/// value["extra"]["nested"] = json!(1); // inserted (through null)
+/// assert_eq!(value.get("tags").and_then(|tags| tags.get(2)), Some(&json!("c")));
+/// assert_eq!(value["debug"].as_bool(), Some(true));
+/// assert_eq!(value["extra"], json!({"nested": 1}));
+///
+/// let name = value["name"].take();
+/// assert_eq!(name, json!("demo"));
+/// assert!(value["name"].is_null());
+/// ```
+impl Value {
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_num(&self) -> Option<Num> {
+        match self {
+            Self::Number(num) => Some(*num),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(str) => Some(str),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_array(&self) -> Option<&Arr> {
+        match self {
+            Self::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_array_mut(&mut self) -> Option<&mut Arr> {
+        match self {
+            Self::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_object(&self) -> Option<&Obj> {
+        match self {
+            Self::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_object_mut(&mut self) -> Option<&mut Obj> {
+        match self {
+            Self::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
+
+    /// Looks up an object entry by key, or an array element by index.
+    #[must_use]
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&Self> {
+        index.index_into(self)
+    }
+
+    /// Looks up an object entry by key, or an array element by index, mutably.
+    #[must_use]
+    pub fn get_mut<I: ValueIndex>(&mut self, index: I) -> Option<&mut Self> {
+        index.index_into_mut(self)
+    }
+
+    /// Replaces with `null`, returning the previous value.
+    #[must_use = "use `*value = Value::Null` to just discard"]
+    pub fn take(&mut self) -> Self {
+        std::mem::replace(self, Self::Null)
+    }
+}
+
+/// A key (string) or an index (`usize`) usable with [`Value::get`] and
+/// the `Index`/`IndexMut` impls of [`Value`].
+///
+/// This trait is sealed.
+pub trait ValueIndex: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value>;
+
+    #[doc(hidden)]
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value>;
+
+    /// Panics if the value is not of the indexable variant
+    /// (or, for an index, if it is out of bounds).
+    #[doc(hidden)]
+    fn index_or_insert<'v>(&self, value: &'v mut Value) -> &'v mut Value;
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl<T: Sealed + ?Sized> Sealed for &T {}
+}
+
+impl ValueIndex for usize {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        value.as_array()?.get(*self)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        value.as_array_mut()?.get_mut(*self)
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut Value) -> &'v mut Value {
+        match value {
+            Value::Array(arr) => {
+                let len = arr.len();
+                arr.get_mut(*self)
+                    .unwrap_or_else(|| panic!("cannot index array of length {len} with {self}"))
+            }
+            _ => panic!("cannot index {} with {self}", value.type_name()),
+        }
+    }
+}
+
+impl ValueIndex for str {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        value.as_object()?.get(self)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        value.as_object_mut()?.get_mut(self)
+    }
+
+    /// Also turns a `null` into an empty object, and inserts a missing key as `null`.
+    fn index_or_insert<'v>(&self, value: &'v mut Value) -> &'v mut Value {
+        if value.is_null() {
+            *value = Value::Object(Obj::new());
+        }
+        match value {
+            Value::Object(obj) => obj.entry(Str::from(self.to_owned())).or_insert(Value::Null),
+            _ => panic!("cannot index {} with {self:?}", value.type_name()),
+        }
+    }
+}
+
+impl ValueIndex for String {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.as_str().index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        self.as_str().index_into_mut(value)
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut Value) -> &'v mut Value {
+        self.as_str().index_or_insert(value)
+    }
+}
+
+impl<T: ValueIndex + ?Sized> ValueIndex for &T {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        (**self).index_into_mut(value)
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut Value) -> &'v mut Value {
+        (**self).index_or_insert(value)
+    }
+}
+
+static NULL: Value = Value::Null;
+
+/// Returns `null` for a missing key or index (or another variant).
+impl<I: ValueIndex> ops::Index<I> for Value {
+    type Output = Self;
+
+    fn index(&self, index: I) -> &Self {
+        index.index_into(self).unwrap_or(&NULL)
+    }
+}
+
+/// Inserts a missing key (into an object, or a `null` turned into an empty object).
+///
+/// # Panics
+///
+/// For another variant, or an index out of bounds.
+impl<I: ValueIndex> ops::IndexMut<I> for Value {
+    fn index_mut(&mut self, index: I) -> &mut Self {
+        index.index_or_insert(self)
+    }
+}
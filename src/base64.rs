@@ -0,0 +1,52 @@
+//! Base64 (RFC 4648), for byte strings of binary formats.
+
+pub(crate) const STANDARD: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+pub(crate) const URL_SAFE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub(crate) fn encode(bytes: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut string = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0_u32, |acc, (i, &byte)| {
+            acc | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            string.push(char::from(alphabet[(n >> (18 - 6 * i) & 0x3f) as usize]));
+        }
+        if pad {
+            for _ in chunk.len()..3 {
+                string.push('=');
+            }
+        }
+    }
+    string
+}
+
+/// Accepts either alphabet, with or without padding.
+pub(crate) fn decode(string: &str) -> Option<Vec<u8>> {
+    let string = string
+        .strip_suffix("==")
+        .or_else(|| string.strip_suffix('='))
+        .unwrap_or(string);
+    if string.len() % 4 == 1 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(string.len() / 4 * 3 + 2);
+    for chunk in string.as_bytes().chunks(4) {
+        let mut n = 0_u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let sextet = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' | b'-' => 62,
+                b'/' | b'_' => 63,
+                _ => return None,
+            };
+            n |= u32::from(sextet) << (18 - 6 * i);
+        }
+        bytes.extend_from_slice(&n.to_be_bytes()[1..chunk.len()]);
+    }
+    Some(bytes)
+}
@@ -0,0 +1,279 @@
+use super::*;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+impl Value {
+    /// Encodes an object into a [BSON] document, with default options
+    /// (without [Extended JSON]).
+    ///
+    /// Whole numbers are encoded as 32-bit integers if they fit, otherwise
+    /// as 64-bit integers if they fit, and other numbers as doubles.
+    ///
+    /// [BSON]: https://bsonspec.org/spec.html
+    /// [Extended JSON]: https://www.mongodb.com/docs/manual/reference/mongodb-extended-json/
+    ///
+    /// # Errors
+    ///
+    /// If the value is not an object, if a key contains a NUL character,
+    /// or if a document would exceed 2<sup>31</sup> - 1 bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{BsonEncodeErrorKind, Value};
+    ///
+    /// assert_eq!(json!({"hello": "world"}).to_bson().unwrap(), b"\x16\x00\x00\x00\x02hello\x00\x06\x00\x00\x00world\x00\x00");
+    ///
+    /// let value = json!({"a": [1, 0.5, 3000000000_u32, "x"], "b": {"c": null, "d": true}});
+    /// assert_eq!(Value::from_bson(&value.to_bson().unwrap()), Ok(value));
+    ///
+    /// let error = json!([1]).to_bson().unwrap_err();
+    /// assert_eq!(error.kind, BsonEncodeErrorKind::NotAnObject);
+    /// let error = json!({"a": [{"b\u{0}": 1}]}).to_bson().unwrap_err();
+    /// assert_eq!(error.kind, BsonEncodeErrorKind::NulInKey);
+    /// assert_eq!(error.path.as_str(), "/a/0/b\0");
+    /// ```
+    pub fn to_bson(&self) -> Result<Vec<u8>, BsonEncodeError> {
+        self.to_bson_with(&BsonOptions::default())
+    }
+
+    /// Encodes an object into a [BSON] document, also recognizing [Extended JSON]
+    /// (in either canonical or relaxed format) if enabled by the options,
+    /// to encode BSON-only types (e.g. `{"$oid": "..."}` as an ObjectId).
+    ///
+    /// [BSON]: https://bsonspec.org/spec.html
+    /// [Extended JSON]: https://www.mongodb.com/docs/manual/reference/mongodb-extended-json/
+    ///
+    /// # Errors
+    ///
+    /// See [`Value::to_bson`]; also for an invalid Extended JSON type wrapper
+    /// (e.g. `{"$oid": 1}`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{BsonEncodeErrorKind, BsonErrorKind, BsonOptions, ExtendedJsonMode, Value};
+    ///
+    /// let value = json!({
+    ///     "_id": {"$oid": "5f1b2c3d4e5f60718293a4b5"},
+    ///     "at": {"$date": "2012-12-24T12:15:30.501Z"},
+    ///     "big": {"$numberLong": "9007199254740993"},
+    ///     "small": {"$numberLong": "42"},
+    ///     "price": {"$numberDecimal": "12.50"},
+    ///     "data": {"$binary": {"base64": "AQID", "subType": "80"}},
+    /// });
+    /// let relaxed = BsonOptions { extended_json: Some(ExtendedJsonMode::Relaxed), ..BsonOptions::default() };
+    /// let bytes = value.to_bson_with(&relaxed).unwrap();
+    ///
+    /// assert_eq!(
+    ///     Value::from_bson_with(&bytes, &relaxed),
+    ///     Ok(json!({
+    ///         "_id": {"$oid": "5f1b2c3d4e5f60718293a4b5"},
+    ///         "at": {"$date": "2012-12-24T12:15:30.501Z"},
+    ///         "big": {"$numberLong": "9007199254740993"},
+    ///         "small": 42,
+    ///         "price": {"$numberDecimal": "12.50"},
+    ///         "data": {"$binary": {"base64": "AQID", "subType": "80"}},
+    ///     }))
+    /// );
+    /// let canonical = BsonOptions { extended_json: Some(ExtendedJsonMode::Canonical), ..relaxed };
+    /// assert_eq!(
+    ///     Value::from_bson_with(&bytes, &canonical),
+    ///     Ok(json!({
+    ///         "_id": {"$oid": "5f1b2c3d4e5f60718293a4b5"},
+    ///         "at": {"$date": {"$numberLong": "1356351330501"}},
+    ///         "big": {"$numberLong": "9007199254740993"},
+    ///         "small": {"$numberLong": "42"},
+    ///         "price": {"$numberDecimal": "12.50"},
+    ///         "data": {"$binary": {"base64": "AQID", "subType": "80"}},
+    ///     }))
+    /// );
+    ///
+    /// // without Extended JSON, BSON-only types are rejected (here the ObjectId)
+    /// let error = Value::from_bson(&bytes).unwrap_err();
+    /// assert_eq!((error.kind, error.offset), (BsonErrorKind::UnsupportedType(0x07), 4));
+    ///
+    /// let error = json!({"a": {"$oid": "xyz"}}).to_bson_with(&relaxed).unwrap_err();
+    /// assert_eq!(error.kind, BsonEncodeErrorKind::InvalidExtendedJson);
+    /// assert_eq!(error.path.as_str(), "/a");
+    ///
+    /// // dates must exist (leap years included), with offsets as `±HH:MM` or `±HHMM`
+    /// for date in ["2020-02-29T00:00:00Z", "2020-01-01T00:00:00+05:30", "2020-01-01T00:00:00-0530"] {
+    ///     assert!(json!({"d": {"$date": date}}).to_bson_with(&relaxed).is_ok());
+    /// }
+    /// for date in [
+    ///     "2021-02-29T00:00:00Z",
+    ///     "2021-04-31T00:00:00Z",
+    ///     "2020-01-01T00:00:0é",
+    ///     "2020-01-01T00:00:00+0:530",
+    ///     "2020-01-01T00:00:00+053:0",
+    ///     "2020-01-01T00:00:00+0099",
+    /// ] {
+    ///     let error = json!({"d": {"$date": date}}).to_bson_with(&relaxed).unwrap_err();
+    ///     assert_eq!(error.kind, BsonEncodeErrorKind::InvalidExtendedJson);
+    /// }
+    /// ```
+    pub fn to_bson_with(&self, options: &BsonOptions) -> Result<Vec<u8>, BsonEncodeError> {
+        helper::encode(self, options)
+    }
+
+    /// Decodes a [BSON] document into an object, with default options
+    /// (rejecting BSON-only types).
+    ///
+    /// 64-bit integers are converted to `f64` (so possibly rounded beyond ±2<sup>53</sup>).
+    ///
+    /// [BSON]: https://bsonspec.org/spec.html
+    ///
+    /// # Errors
+    ///
+    /// If the data is invalid, not completely consumed,
+    /// or contains a BSON-only type or a non-finite double.
+    pub fn from_bson(bytes: &[u8]) -> Result<Self, BsonError> {
+        Self::from_bson_with(bytes, &BsonOptions::default())
+    }
+
+    /// Decodes a [BSON] document into an object, representing BSON-only types
+    /// (and, in canonical format, all numbers) as [Extended JSON] if enabled by the options.
+    ///
+    /// Deviating from the relaxed format, 64-bit integers beyond ±2<sup>53</sup>
+    /// are kept as `{"$numberLong": "..."}` so that they are not rounded.
+    ///
+    /// [BSON]: https://bsonspec.org/spec.html
+    /// [Extended JSON]: https://www.mongodb.com/docs/manual/reference/mongodb-extended-json/
+    ///
+    /// # Errors
+    ///
+    /// See [`Value::from_bson`].
+    pub fn from_bson_with(bytes: &[u8], options: &BsonOptions) -> Result<Self, BsonError> {
+        helper::decode(bytes, options)
+    }
+}
+
+/// Options for [`Value::to_bson_with`] and [`Value::from_bson_with`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BsonOptions {
+    /// Whether to use [Extended JSON] for BSON-only types, and in which format
+    /// (when encoding, both formats are recognized).
+    ///
+    /// [Extended JSON]: https://www.mongodb.com/docs/manual/reference/mongodb-extended-json/
+    pub extended_json: Option<ExtendedJsonMode>,
+    /// Maximum nesting of documents (including the root).
+    pub max_depth: usize,
+}
+
+impl Default for BsonOptions {
+    fn default() -> Self {
+        Self {
+            extended_json: None,
+            max_depth: 512,
+        }
+    }
+}
+
+/// Format of [Extended JSON] (v2).
+///
+/// [Extended JSON]: https://www.mongodb.com/docs/manual/reference/mongodb-extended-json/
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExtendedJsonMode {
+    /// Preserving all type information (e.g. `{"$numberInt": "1"}`).
+    Canonical,
+    /// Using plain JSON numbers where possible, and ISO-8601 dates (for years 1970 to 9999).
+    Relaxed,
+}
+
+/// Error from [`Value::from_bson`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BsonError {
+    pub kind: BsonErrorKind,
+    /// Of the start of the offending element (or value).
+    pub offset: usize,
+}
+
+impl Display for BsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl Error for BsonError {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BsonErrorKind {
+    PrematureEnd,
+    /// Data after the (root) document.
+    TrailingData,
+    /// A document or string length inconsistent with its content.
+    InvalidLength,
+    /// An unknown element type.
+    InvalidType(u8),
+    /// Invalid content for the element type (e.g. a boolean other than 0 or 1).
+    InvalidValue,
+    InvalidUtf8,
+    DuplicateKey(Str),
+    TooDeep,
+    /// A BSON-only element type (without Extended JSON).
+    UnsupportedType(u8),
+    /// NaN or infinity (without Extended JSON).
+    NonFiniteFloat,
+}
+
+impl Display for BsonErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PrematureEnd => f.write_str("premature end of data"),
+            Self::TrailingData => f.write_str("trailing data"),
+            Self::InvalidLength => f.write_str("invalid length"),
+            Self::InvalidType(typ) => write!(f, "invalid element type 0x{:02x}", typ),
+            Self::InvalidValue => f.write_str("invalid value"),
+            Self::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            Self::DuplicateKey(key) => write!(f, "duplicate key {:?}", key),
+            Self::TooDeep => f.write_str("too deep nesting"),
+            Self::UnsupportedType(typ) => write!(f, "unsupported element type 0x{:02x}", typ),
+            Self::NonFiniteFloat => f.write_str("unsupported non-finite float"),
+        }
+    }
+}
+
+/// Error from [`Value::to_bson`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BsonEncodeError {
+    pub kind: BsonEncodeErrorKind,
+    /// Location of the offending value.
+    pub path: JsonPointer,
+}
+
+impl Display for BsonEncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at \"{}\"", self.kind, self.path)
+    }
+}
+
+impl Error for BsonEncodeError {}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BsonEncodeErrorKind {
+    /// The root value is not an object.
+    NotAnObject,
+    /// A key (or a regular expression) containing a NUL character.
+    NulInKey,
+    /// A document longer than 2<sup>31</sup> - 1 bytes.
+    TooLarge,
+    /// A malformed Extended JSON type wrapper.
+    InvalidExtendedJson,
+}
+
+impl Display for BsonEncodeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NotAnObject => "not an object",
+            Self::NulInKey => "NUL character in key",
+            Self::TooLarge => "too large document",
+            Self::InvalidExtendedJson => "invalid Extended JSON",
+        })
+    }
+}
+
+mod extended;
+mod helper;
@@ -0,0 +1,489 @@
+//! Extended JSON (v2) type wrappers, dates and Decimal128.
+
+use super::*;
+
+/// A BSON value without a plain JSON equivalent (or with a specific numeric type).
+pub(super) enum Special<'v> {
+    Double(f64),
+    Int32(i32),
+    Int64(i64),
+    Decimal128(u128),
+    ObjectId([u8; 12]),
+    DateTime(i64),
+    Binary(u8, Vec<u8>),
+    Regex(&'v str, &'v str),
+    Timestamp(u32, u32),
+    Symbol(&'v str),
+    Code(&'v str),
+    CodeWithScope(&'v str, &'v Obj),
+    DbPointer(&'v str, [u8; 12]),
+    Undefined,
+    MinKey,
+    MaxKey,
+}
+
+/// Keys identifying a type wrapper.
+const TYPE_KEYS: [&str; 15] = [
+    "$oid",
+    "$symbol",
+    "$numberInt",
+    "$numberLong",
+    "$numberDouble",
+    "$numberDecimal",
+    "$binary",
+    "$code",
+    "$timestamp",
+    "$regularExpression",
+    "$dbPointer",
+    "$date",
+    "$minKey",
+    "$maxKey",
+    "$undefined",
+];
+
+/// Recognizes a type wrapper: `Ok(None)` for an ordinary object, `Err` for a malformed wrapper.
+pub(super) fn parse(obj: &Obj) -> Result<Option<Special<'_>>, ()> {
+    let Some(key) = obj.keys().find(|key| TYPE_KEYS.contains(&key.as_ref())) else {
+        return Ok(None);
+    };
+    let value = &obj[key];
+    let arity = if key == "$code" && obj.contains_key("$scope") {
+        2
+    } else {
+        1
+    };
+    if obj.len() != arity {
+        return Err(());
+    }
+    let special = match &**key {
+        "$oid" => Special::ObjectId(parse_object_id(value)?),
+        "$symbol" => Special::Symbol(value.as_str().ok_or(())?),
+        "$numberInt" => Special::Int32(value.as_str().ok_or(())?.parse().map_err(|_| ())?),
+        "$numberLong" => Special::Int64(parse_long(value)?),
+        "$numberDouble" => Special::Double(parse_double(value.as_str().ok_or(())?)?),
+        "$numberDecimal" => Special::Decimal128(parse_decimal128(value.as_str().ok_or(())?)?),
+        "$binary" => {
+            let fields = fields(value, &["base64", "subType"])?;
+            let bytes = base64::decode(fields[0].as_str().ok_or(())?).ok_or(())?;
+            let subtype = fields[1].as_str().ok_or(())?;
+            if subtype.is_empty() || subtype.len() > 2 {
+                return Err(());
+            }
+            Special::Binary(u8::from_str_radix(subtype, 16).map_err(|_| ())?, bytes)
+        }
+        "$code" => {
+            let code = value.as_str().ok_or(())?;
+            match obj.get("$scope") {
+                Some(Value::Object(scope)) => Special::CodeWithScope(code, scope),
+                Some(_) => return Err(()),
+                None => Special::Code(code),
+            }
+        }
+        "$timestamp" => {
+            let fields = fields(value, &["i", "t"])?;
+            Special::Timestamp(parse_u32(fields[1])?, parse_u32(fields[0])?)
+        }
+        "$regularExpression" => {
+            let fields = fields(value, &["options", "pattern"])?;
+            Special::Regex(fields[1].as_str().ok_or(())?, fields[0].as_str().ok_or(())?)
+        }
+        "$dbPointer" => {
+            let fields = fields(value, &["$id", "$ref"])?;
+            let id = match fields[0] {
+                Value::Object(id) if id.len() == 1 => parse_object_id(id.get("$oid").ok_or(())?)?,
+                _ => return Err(()),
+            };
+            Special::DbPointer(fields[1].as_str().ok_or(())?, id)
+        }
+        "$date" => Special::DateTime(match value {
+            Value::String(str) => parse_iso_date(str).ok_or(())?,
+            _ => parse_long(value)?,
+        }),
+        "$minKey" if *value == Value::from(1) => Special::MinKey,
+        "$maxKey" if *value == Value::from(1) => Special::MaxKey,
+        "$undefined" if *value == Value::Boolean(true) => Special::Undefined,
+        _ => return Err(()),
+    };
+    Ok(Some(special))
+}
+
+/// The values of an object with exactly the given keys (in sorted order).
+fn fields<'v>(value: &'v Value, keys: &[&str]) -> Result<Vec<&'v Value>, ()> {
+    match value {
+        Value::Object(obj) if obj.len() == keys.len() => {
+            keys.iter().map(|key| obj.get(*key).ok_or(())).collect()
+        }
+        _ => Err(()),
+    }
+}
+
+fn parse_object_id(value: &Value) -> Result<[u8; 12], ()> {
+    let hex = value.as_str().filter(|hex| hex.len() == 24).ok_or(())?;
+    let mut id = [0; 12];
+    for (byte, pair) in id.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|_| ())?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| ())?;
+    }
+    Ok(id)
+}
+
+/// `{"$numberLong": "..."}`'s content, or `{"$numberLong": "..."}` itself (within `$date`).
+fn parse_long(value: &Value) -> Result<i64, ()> {
+    match value {
+        Value::String(str) => str.parse().map_err(|_| ()),
+        Value::Object(obj) if obj.len() == 1 => parse_long(obj.get("$numberLong").ok_or(())?),
+        _ => Err(()),
+    }
+}
+
+fn parse_u32(value: &Value) -> Result<u32, ()> {
+    let f = value.as_num().ok_or(())?.get();
+    if f.fract() != 0.0 || !(0.0..=f64::from(u32::MAX)).contains(&f) {
+        return Err(());
+    }
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Ok(f as u32)
+}
+
+fn parse_double(str: &str) -> Result<f64, ()> {
+    match str {
+        "Infinity" => Ok(f64::INFINITY),
+        "-Infinity" => Ok(f64::NEG_INFINITY),
+        "NaN" => Ok(f64::NAN),
+        _ => match str.parse::<f64>() {
+            Ok(f) if f.is_finite() => Ok(f),
+            _ => Err(()),
+        },
+    }
+}
+
+/// Wraps a value as Extended JSON (or as plain JSON where the relaxed format allows it).
+pub(super) fn wrap(special: Special<'_>, mode: ExtendedJsonMode) -> Value {
+    let relaxed = mode == ExtendedJsonMode::Relaxed;
+    let (key, content) = match special {
+        Special::Double(f) => match Num::new(f) {
+            Some(num) if relaxed => return Value::Number(num),
+            _ => ("$numberDouble", Value::from(format_double(f))),
+        },
+        Special::Int32(i) if relaxed => return Value::from(i),
+        Special::Int32(i) => ("$numberInt", Value::from(i.to_string())),
+        #[allow(clippy::cast_precision_loss)]
+        Special::Int64(i) if relaxed && i.unsigned_abs() <= 1 << 53 => {
+            return Value::Number(Num::new(i as f64).expect("finite number"));
+        }
+        Special::Int64(i) => ("$numberLong", Value::from(i.to_string())),
+        Special::Decimal128(bits) => ("$numberDecimal", Value::from(format_decimal128(bits))),
+        Special::ObjectId(id) => ("$oid", Value::from(format_hex(&id))),
+        Special::DateTime(ms) => match format_iso_date(ms) {
+            Some(iso) if relaxed => ("$date", Value::from(iso)),
+            _ => (
+                "$date",
+                wrap(Special::Int64(ms), ExtendedJsonMode::Canonical),
+            ),
+        },
+        Special::Binary(subtype, bytes) => (
+            "$binary",
+            Value::Object(Obj::from([
+                (
+                    Str::from("base64"),
+                    Value::from(base64::encode(&bytes, base64::STANDARD, true)),
+                ),
+                (
+                    Str::from("subType"),
+                    Value::from(format!("{:02x}", subtype)),
+                ),
+            ])),
+        ),
+        Special::Regex(pattern, options) => (
+            "$regularExpression",
+            Value::Object(Obj::from([
+                (Str::from("pattern"), Value::from(String::from(pattern))),
+                (Str::from("options"), Value::from(String::from(options))),
+            ])),
+        ),
+        Special::Timestamp(t, i) => (
+            "$timestamp",
+            Value::Object(Obj::from([
+                (Str::from("t"), Value::from(t)),
+                (Str::from("i"), Value::from(i)),
+            ])),
+        ),
+        Special::Symbol(symbol) => ("$symbol", Value::from(String::from(symbol))),
+        Special::Code(code) => ("$code", Value::from(String::from(code))),
+        Special::CodeWithScope(code, scope) => {
+            return Value::Object(Obj::from([
+                (Str::from("$code"), Value::from(String::from(code))),
+                (Str::from("$scope"), Value::Object(scope.clone())),
+            ]));
+        }
+        Special::DbPointer(namespace, id) => (
+            "$dbPointer",
+            Value::Object(Obj::from([
+                (Str::from("$ref"), Value::from(String::from(namespace))),
+                (Str::from("$id"), wrap(Special::ObjectId(id), mode)),
+            ])),
+        ),
+        Special::Undefined => ("$undefined", Value::Boolean(true)),
+        Special::MinKey => ("$minKey", Value::from(1)),
+        Special::MaxKey => ("$maxKey", Value::from(1)),
+    };
+    Value::Object(Obj::from([(Str::from(key), content)]))
+}
+
+fn format_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Always with a fractional part or an exponent (e.g. `1.0`, `1e300`).
+fn format_double(f: f64) -> String {
+    if f.is_nan() {
+        String::from("NaN")
+    } else if f.is_infinite() {
+        String::from(if f > 0.0 { "Infinity" } else { "-Infinity" })
+    } else {
+        format!("{:?}", f)
+    }
+}
+
+const MS_PER_DAY: i64 = 86_400_000;
+
+/// Days since the Unix epoch of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Proleptic Gregorian date of a number of days since the Unix epoch.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// `YYYY-MM-DDTHH:MM:SS[.mmm]Z`, for years 1970 to 9999 only.
+fn format_iso_date(ms: i64) -> Option<String> {
+    let (days, ms_of_day) = (ms.div_euclid(MS_PER_DAY), ms.rem_euclid(MS_PER_DAY));
+    let (year, month, day) = civil_from_days(days);
+    if !(1970..=9999).contains(&year) {
+        return None;
+    }
+    let (seconds, millis) = (ms_of_day / 1000, ms_of_day % 1000);
+    let mut iso = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    if millis != 0 {
+        iso.push_str(&format!(".{:03}", millis));
+    }
+    iso.push('Z');
+    Some(iso)
+}
+
+/// `YYYY-MM-DDTHH:MM:SS[.fraction](Z|±HH:MM|±HHMM)`, truncated to milliseconds.
+fn parse_iso_date(iso: &str) -> Option<i64> {
+    fn number(digits: &str) -> Option<i64> {
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse().ok())?
+    }
+    let bytes = iso.as_bytes();
+    // (so that slicing by byte offsets is on char boundaries)
+    if !iso.is_ascii()
+        || bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || bytes[10] != b'T'
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+    let (year, month, day) = (
+        number(&iso[0..4])?,
+        number(&iso[5..7])?,
+        number(&iso[8..10])?,
+    );
+    let (hours, minutes, seconds) = (
+        number(&iso[11..13])?,
+        number(&iso[14..16])?,
+        number(&iso[17..19])?,
+    );
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hours > 23
+        || minutes > 59
+        || seconds > 60
+    {
+        return None;
+    }
+    // (e.g. not February 30th)
+    if civil_from_days(days_from_civil(year, month, day)) != (year, month, day) {
+        return None;
+    }
+    let mut rest = &iso[19..];
+    let mut millis = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
+        }
+        millis = number(&format!("{:0<3}", &fraction[..len.min(3)]))?;
+        rest = &fraction[len..];
+    }
+    let offset_minutes = match rest {
+        "Z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            // exactly `HH:MM` or `HHMM`
+            let (offset_hours, offset_minutes) = match &rest.as_bytes()[1..] {
+                [_, _, b':', _, _] => (number(&rest[1..3])?, number(&rest[4..6])?),
+                [_, _, _, _] => (number(&rest[1..3])?, number(&rest[3..5])?),
+                _ => return None,
+            };
+            if offset_hours > 23 || offset_minutes > 59 {
+                return None;
+            }
+            sign * (offset_hours * 60 + offset_minutes)
+        }
+    };
+    let days = days_from_civil(year, month, day);
+    let seconds = days * 86_400 + hours * 3600 + minutes * 60 + seconds - offset_minutes * 60;
+    Some(seconds * 1000 + millis)
+}
+
+const DECIMAL128_MAX_COEFFICIENT: u128 = 10_u128.pow(34) - 1;
+const DECIMAL128_EXPONENT_BIAS: i32 = 6176;
+const DECIMAL128_MAX_EXPONENT: i32 = 6111;
+const DECIMAL128_INFINITY: u128 = 0x7800 << 112;
+const DECIMAL128_NAN: u128 = 0x7c00 << 112;
+const SIGN_BIT: u128 = 1 << 127;
+
+/// The "to-scientific-string" of IEEE 754-2008 (binary integer decimal encoding).
+fn format_decimal128(bits: u128) -> String {
+    let sign = if bits & SIGN_BIT == 0 { "" } else { "-" };
+    match bits >> 122 & 0x1f {
+        0x1f => return String::from("NaN"),
+        0x1e => return format!("{}Infinity", sign),
+        _ => {}
+    }
+    let (biased, coefficient) = if bits >> 125 & 0b11 == 0b11 {
+        // coefficient with an implicit `100` prefix: above the maximum, so non-canonical zero
+        (bits >> 111 & 0x3fff, 0)
+    } else {
+        (bits >> 113 & 0x3fff, bits & ((1 << 113) - 1))
+    };
+    let coefficient = if coefficient > DECIMAL128_MAX_COEFFICIENT {
+        0
+    } else {
+        coefficient
+    };
+    let exponent = i32::try_from(biased).expect("14 bits") - DECIMAL128_EXPONENT_BIAS;
+    let digits = coefficient.to_string();
+    let len = i32::try_from(digits.len()).expect("at most 34 digits");
+    let adjusted = exponent + len - 1;
+    if exponent <= 0 && adjusted >= -6 {
+        let point = len + exponent;
+        if exponent == 0 {
+            format!("{}{}", sign, digits)
+        } else if point > 0 {
+            let (int, fraction) = digits.split_at(point.unsigned_abs() as usize);
+            format!("{}{}.{}", sign, int, fraction)
+        } else {
+            format!(
+                "{}0.{}{}",
+                sign,
+                "0".repeat(point.unsigned_abs() as usize),
+                digits
+            )
+        }
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let point = if rest.is_empty() { "" } else { "." };
+        format!("{}{}{}{}E{:+}", sign, first, point, rest, adjusted)
+    }
+}
+
+/// Exactly (failing rather than rounding).
+fn parse_decimal128(str: &str) -> Result<u128, ()> {
+    let (sign, unsigned) = match str.strip_prefix('-') {
+        Some(unsigned) => (SIGN_BIT, unsigned),
+        None => (0, str.strip_prefix('+').unwrap_or(str)),
+    };
+    if unsigned.eq_ignore_ascii_case("infinity") || unsigned.eq_ignore_ascii_case("inf") {
+        return Ok(sign | DECIMAL128_INFINITY);
+    }
+    if unsigned.eq_ignore_ascii_case("nan") {
+        return Ok(DECIMAL128_NAN);
+    }
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(e) => (
+            &unsigned[..e],
+            unsigned[e + 1..].parse::<i32>().map_err(|_| ())?,
+        ),
+        None => (unsigned, 0),
+    };
+    let (int, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if int.is_empty() && fraction.is_empty()
+        || !(int.bytes().chain(fraction.bytes())).all(|b| b.is_ascii_digit())
+    {
+        return Err(());
+    }
+    let mut exponent = exponent
+        .checked_sub(i32::try_from(fraction.len()).map_err(|_| ())?)
+        .ok_or(())?;
+    let mut digits = format!("{}{}", int, fraction)
+        .trim_start_matches('0')
+        .to_owned();
+    // drop trailing zeros (exactly) while too many digits or too small an exponent
+    while digits.ends_with('0') && (digits.len() > 34 || exponent < -DECIMAL128_EXPONENT_BIAS) {
+        digits.pop();
+        exponent += 1;
+    }
+    if digits.len() > 34 {
+        return Err(());
+    }
+    let mut coefficient: u128 = if digits.is_empty() {
+        0
+    } else {
+        digits.parse().map_err(|_| ())?
+    };
+    if coefficient == 0 {
+        exponent = exponent.clamp(-DECIMAL128_EXPONENT_BIAS, DECIMAL128_MAX_EXPONENT);
+    }
+    // pad with zeros (exactly) while too big an exponent
+    while exponent > DECIMAL128_MAX_EXPONENT && coefficient * 10 <= DECIMAL128_MAX_COEFFICIENT {
+        coefficient *= 10;
+        exponent -= 1;
+    }
+    if !(-DECIMAL128_EXPONENT_BIAS..=DECIMAL128_MAX_EXPONENT).contains(&exponent) {
+        return Err(());
+    }
+    let biased = u128::from((exponent + DECIMAL128_EXPONENT_BIAS).unsigned_abs());
+    Ok(sign | biased << 113 | coefficient)
+}
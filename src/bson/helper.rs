@@ -0,0 +1,407 @@
+use super::*;
+use extended::Special;
+
+type EncodeResult<T> = Result<T, BsonEncodeError>;
+type DecodeResult<T> = Result<T, BsonError>;
+
+use BsonEncodeErrorKind as EK;
+use BsonErrorKind as K;
+
+const DOUBLE: u8 = 0x01;
+const STRING: u8 = 0x02;
+const DOCUMENT: u8 = 0x03;
+const ARRAY: u8 = 0x04;
+const BINARY: u8 = 0x05;
+const UNDEFINED: u8 = 0x06;
+const OBJECT_ID: u8 = 0x07;
+const BOOLEAN: u8 = 0x08;
+const DATE_TIME: u8 = 0x09;
+const NULL: u8 = 0x0a;
+const REGEX: u8 = 0x0b;
+const DB_POINTER: u8 = 0x0c;
+const CODE: u8 = 0x0d;
+const SYMBOL: u8 = 0x0e;
+const CODE_WITH_SCOPE: u8 = 0x0f;
+const INT32: u8 = 0x10;
+const TIMESTAMP: u8 = 0x11;
+const INT64: u8 = 0x12;
+const DECIMAL128: u8 = 0x13;
+const MIN_KEY: u8 = 0xff;
+const MAX_KEY: u8 = 0x7f;
+
+pub(super) fn encode(value: &Value, options: &BsonOptions) -> EncodeResult<Vec<u8>> {
+    let mut encoder = Encoder {
+        out: Vec::new(),
+        extended: options.extended_json.is_some(),
+        path: JsonPointer::root(),
+    };
+    match value {
+        Value::Object(obj) => {
+            encoder.encode_document(obj.iter().map(|(key, value)| (&**key, value)))?
+        }
+        _ => return Err(encoder.error(EK::NotAnObject)),
+    }
+    Ok(encoder.out)
+}
+
+struct Encoder {
+    out: Vec<u8>,
+    /// Whether to recognize Extended JSON type wrappers.
+    extended: bool,
+    path: JsonPointer,
+}
+
+impl Encoder {
+    fn error(&self, kind: BsonEncodeErrorKind) -> BsonEncodeError {
+        BsonEncodeError {
+            kind,
+            path: self.path.clone(),
+        }
+    }
+
+    fn encode_document<'v>(
+        &mut self,
+        members: impl Iterator<Item = (&'v str, &'v Value)>,
+    ) -> EncodeResult<()> {
+        let start = self.out.len();
+        self.out.extend_from_slice(&[0; 4]);
+        for (key, value) in members {
+            self.path.push_key(key);
+            self.encode_element(key, value)?;
+            self.path.pop();
+        }
+        self.out.push(0);
+        let len = i32::try_from(self.out.len() - start).map_err(|_| self.error(EK::TooLarge))?;
+        self.out[start..start + 4].copy_from_slice(&len.to_le_bytes());
+        Ok(())
+    }
+
+    fn encode_array(&mut self, arr: &Arr) -> EncodeResult<()> {
+        let keys: Vec<String> = (0..arr.len()).map(|index| index.to_string()).collect();
+        self.encode_document(keys.iter().map(String::as_str).zip(arr))
+    }
+
+    fn write_header(&mut self, typ: u8, key: &str) -> EncodeResult<()> {
+        self.out.push(typ);
+        self.write_cstring(key)
+    }
+
+    fn write_cstring(&mut self, str: &str) -> EncodeResult<()> {
+        if str.contains('\0') {
+            return Err(self.error(EK::NulInKey));
+        }
+        self.out.extend_from_slice(str.as_bytes());
+        self.out.push(0);
+        Ok(())
+    }
+
+    fn write_string(&mut self, str: &str) -> EncodeResult<()> {
+        let len = i32::try_from(str.len() + 1).map_err(|_| self.error(EK::TooLarge))?;
+        self.out.extend_from_slice(&len.to_le_bytes());
+        self.out.extend_from_slice(str.as_bytes());
+        self.out.push(0);
+        Ok(())
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn encode_element(&mut self, key: &str, value: &Value) -> EncodeResult<()> {
+        match value {
+            Value::Null => self.write_header(NULL, key),
+            Value::Boolean(b) => {
+                self.write_header(BOOLEAN, key)?;
+                self.out.push(u8::from(*b));
+                Ok(())
+            }
+            Value::Number(num) => match num.as_i64() {
+                Some(i) => match i32::try_from(i) {
+                    Ok(i) => self.encode_special(key, Special::Int32(i)),
+                    Err(_) => self.encode_special(key, Special::Int64(i)),
+                },
+                None => self.encode_special(key, Special::Double(num.get())),
+            },
+            Value::String(str) => {
+                self.write_header(STRING, key)?;
+                self.write_string(str)
+            }
+            Value::Array(arr) => {
+                self.write_header(ARRAY, key)?;
+                self.encode_array(arr)
+            }
+            Value::Object(obj) => {
+                if self.extended {
+                    match extended::parse(obj) {
+                        Ok(Some(special)) => return self.encode_special(key, special),
+                        Ok(None) => {}
+                        Err(()) => return Err(self.error(EK::InvalidExtendedJson)),
+                    }
+                }
+                self.write_header(DOCUMENT, key)?;
+                self.encode_document(obj.iter().map(|(key, value)| (&**key, value)))
+            }
+        }
+    }
+
+    fn encode_special(&mut self, key: &str, special: Special<'_>) -> EncodeResult<()> {
+        match special {
+            Special::Double(f) => {
+                self.write_header(DOUBLE, key)?;
+                self.out.extend_from_slice(&f.to_le_bytes());
+            }
+            Special::Int32(i) => {
+                self.write_header(INT32, key)?;
+                self.out.extend_from_slice(&i.to_le_bytes());
+            }
+            Special::Int64(i) => {
+                self.write_header(INT64, key)?;
+                self.out.extend_from_slice(&i.to_le_bytes());
+            }
+            Special::Decimal128(bits) => {
+                self.write_header(DECIMAL128, key)?;
+                self.out.extend_from_slice(&bits.to_le_bytes());
+            }
+            Special::ObjectId(id) => {
+                self.write_header(OBJECT_ID, key)?;
+                self.out.extend_from_slice(&id);
+            }
+            Special::DateTime(ms) => {
+                self.write_header(DATE_TIME, key)?;
+                self.out.extend_from_slice(&ms.to_le_bytes());
+            }
+            Special::Binary(subtype, bytes) => {
+                self.write_header(BINARY, key)?;
+                let len = i32::try_from(bytes.len()).map_err(|_| self.error(EK::TooLarge))?;
+                self.out.extend_from_slice(&len.to_le_bytes());
+                self.out.push(subtype);
+                self.out.extend_from_slice(&bytes);
+            }
+            Special::Regex(pattern, options) => {
+                self.write_header(REGEX, key)?;
+                self.write_cstring(pattern)?;
+                // options are stored in alphabetical order
+                let mut options: Vec<char> = options.chars().collect();
+                options.sort_unstable();
+                self.write_cstring(&options.into_iter().collect::<String>())?;
+            }
+            Special::Timestamp(t, i) => {
+                self.write_header(TIMESTAMP, key)?;
+                self.out.extend_from_slice(&i.to_le_bytes());
+                self.out.extend_from_slice(&t.to_le_bytes());
+            }
+            Special::Symbol(symbol) => {
+                self.write_header(SYMBOL, key)?;
+                self.write_string(symbol)?;
+            }
+            Special::Code(code) => {
+                self.write_header(CODE, key)?;
+                self.write_string(code)?;
+            }
+            Special::CodeWithScope(code, scope) => {
+                self.write_header(CODE_WITH_SCOPE, key)?;
+                let start = self.out.len();
+                self.out.extend_from_slice(&[0; 4]);
+                self.write_string(code)?;
+                self.path.push_key("$scope");
+                self.encode_document(scope.iter().map(|(key, value)| (&**key, value)))?;
+                self.path.pop();
+                let len =
+                    i32::try_from(self.out.len() - start).map_err(|_| self.error(EK::TooLarge))?;
+                self.out[start..start + 4].copy_from_slice(&len.to_le_bytes());
+            }
+            Special::DbPointer(namespace, id) => {
+                self.write_header(DB_POINTER, key)?;
+                self.write_string(namespace)?;
+                self.out.extend_from_slice(&id);
+            }
+            Special::Undefined => self.write_header(UNDEFINED, key)?,
+            Special::MinKey => self.write_header(MIN_KEY, key)?,
+            Special::MaxKey => self.write_header(MAX_KEY, key)?,
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn decode(bytes: &[u8], options: &BsonOptions) -> DecodeResult<Value> {
+    let mut decoder = Decoder {
+        bytes,
+        offset: 0,
+        options,
+        depth: 0,
+    };
+    let obj = decoder.decode_document()?;
+    if decoder.offset < bytes.len() {
+        return Err(decoder.error_at(K::TrailingData, decoder.offset));
+    }
+    Ok(Value::Object(obj))
+}
+
+struct Decoder<'b, 'o> {
+    bytes: &'b [u8],
+    offset: usize,
+    options: &'o BsonOptions,
+    depth: usize,
+}
+
+impl<'b> Decoder<'b, '_> {
+    fn error_at(&self, kind: BsonErrorKind, offset: usize) -> BsonError {
+        BsonError { kind, offset }
+    }
+
+    fn take(&mut self, len: usize) -> DecodeResult<&'b [u8]> {
+        match self
+            .bytes
+            .get(self.offset..)
+            .and_then(|rest| rest.get(..len))
+        {
+            Some(taken) => {
+                self.offset += len;
+                Ok(taken)
+            }
+            None => Err(self.error_at(K::PrematureEnd, self.bytes.len())),
+        }
+    }
+
+    fn array<const N: usize>(&mut self) -> DecodeResult<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("N bytes"))
+    }
+
+    /// A non-negative `int32` length (at least the given minimum).
+    fn read_len(&mut self, min: usize) -> DecodeResult<usize> {
+        let start = self.offset;
+        let len = i32::from_le_bytes(self.array()?);
+        usize::try_from(len)
+            .ok()
+            .filter(|&len| len >= min)
+            .ok_or_else(|| self.error_at(K::InvalidLength, start))
+    }
+
+    fn utf8(&self, bytes: &'b [u8], start: usize) -> DecodeResult<&'b str> {
+        std::str::from_utf8(bytes).map_err(|_| self.error_at(K::InvalidUtf8, start))
+    }
+
+    fn read_cstring(&mut self) -> DecodeResult<&'b str> {
+        let start = self.offset;
+        let rest = &self.bytes[start..];
+        let Some(nul) = rest.iter().position(|&byte| byte == 0) else {
+            return Err(self.error_at(K::PrematureEnd, self.bytes.len()));
+        };
+        self.offset += nul + 1;
+        self.utf8(&rest[..nul], start)
+    }
+
+    fn read_string(&mut self) -> DecodeResult<&'b str> {
+        let start = self.offset;
+        let len = self.read_len(1)?;
+        let bytes = self.take(len)?;
+        match bytes.split_last() {
+            Some((0, content)) => self.utf8(content, start),
+            _ => Err(self.error_at(K::InvalidLength, start)),
+        }
+    }
+
+    fn decode_document(&mut self) -> DecodeResult<Obj> {
+        let start = self.offset;
+        if self.depth >= self.options.max_depth {
+            return Err(self.error_at(K::TooDeep, start));
+        }
+        self.depth += 1;
+        let len = self.read_len(5)?;
+        let end = start
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len());
+        let Some(end) = end else {
+            return Err(self.error_at(K::PrematureEnd, self.bytes.len()));
+        };
+        let mut obj = Obj::new();
+        loop {
+            let element_start = self.offset;
+            let typ = self.array::<1>()?[0];
+            if typ == 0 {
+                break;
+            }
+            let key = Str::from(String::from(self.read_cstring()?));
+            let value = self.decode_value(typ, element_start)?;
+            if obj.contains_key(&key) {
+                return Err(self.error_at(K::DuplicateKey(key), element_start));
+            }
+            obj.insert(key, value);
+            if self.offset >= end {
+                return Err(self.error_at(K::InvalidLength, start));
+            }
+        }
+        if self.offset != end {
+            return Err(self.error_at(K::InvalidLength, start));
+        }
+        self.depth -= 1;
+        Ok(obj)
+    }
+
+    fn decode_value(&mut self, typ: u8, start: usize) -> DecodeResult<Value> {
+        let special = match typ {
+            NULL => return Ok(Value::Null),
+            BOOLEAN => {
+                return match self.array::<1>()?[0] {
+                    0 => Ok(Value::Boolean(false)),
+                    1 => Ok(Value::Boolean(true)),
+                    _ => Err(self.error_at(K::InvalidValue, start)),
+                };
+            }
+            STRING => return Ok(Value::from(String::from(self.read_string()?))),
+            DOCUMENT => return Ok(Value::Object(self.decode_document()?)),
+            // keys are ignored (they should be "0", "1", ...)
+            ARRAY => {
+                return Ok(Value::Array(
+                    self.decode_document()?.into_values().collect(),
+                ))
+            }
+            DOUBLE => Special::Double(f64::from_le_bytes(self.array()?)),
+            INT32 => Special::Int32(i32::from_le_bytes(self.array()?)),
+            INT64 => Special::Int64(i64::from_le_bytes(self.array()?)),
+            DECIMAL128 => Special::Decimal128(u128::from_le_bytes(self.array()?)),
+            OBJECT_ID => Special::ObjectId(self.array()?),
+            DATE_TIME => Special::DateTime(i64::from_le_bytes(self.array()?)),
+            BINARY => {
+                let len = self.read_len(0)?;
+                let subtype = self.array::<1>()?[0];
+                Special::Binary(subtype, self.take(len)?.to_vec())
+            }
+            REGEX => Special::Regex(self.read_cstring()?, self.read_cstring()?),
+            TIMESTAMP => {
+                let increment = u32::from_le_bytes(self.array()?);
+                Special::Timestamp(u32::from_le_bytes(self.array()?), increment)
+            }
+            SYMBOL => Special::Symbol(self.read_string()?),
+            CODE => Special::Code(self.read_string()?),
+            CODE_WITH_SCOPE => {
+                let len_start = self.offset;
+                let len = self.read_len(14)?;
+                let code = self.read_string()?;
+                let scope = self.decode_document()?;
+                if self.offset - len_start != len {
+                    return Err(self.error_at(K::InvalidLength, len_start));
+                }
+                let Some(mode) = self.options.extended_json else {
+                    return Err(self.error_at(K::UnsupportedType(typ), start));
+                };
+                return Ok(extended::wrap(Special::CodeWithScope(code, &scope), mode));
+            }
+            DB_POINTER => Special::DbPointer(self.read_string()?, self.array()?),
+            UNDEFINED => Special::Undefined,
+            MIN_KEY => Special::MinKey,
+            MAX_KEY => Special::MaxKey,
+            _ => return Err(self.error_at(K::InvalidType(typ), start)),
+        };
+        if let Some(mode) = self.options.extended_json {
+            return Ok(extended::wrap(special, mode));
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let f = match special {
+            Special::Double(f) => f,
+            Special::Int32(i) => f64::from(i),
+            Special::Int64(i) => i as f64,
+            _ => return Err(self.error_at(K::UnsupportedType(typ), start)),
+        };
+        Num::new(f)
+            .map(Value::Number)
+            .ok_or_else(|| self.error_at(K::NonFiniteFloat, start))
+    }
+}
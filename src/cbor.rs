@@ -0,0 +1,215 @@
+use super::*;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+impl Value {
+    /// Encodes into [CBOR] (RFC 8949), deterministically (§4.2.1):
+    /// - whole numbers within ±2<sup>64</sup> as integers,
+    ///   other numbers in the shortest float format that preserves them (half, single or double);
+    /// - arrays, objects and strings with definite lengths;
+    /// - object keys sorted by their encoded bytes (i.e. shorter keys first).
+    ///
+    /// [CBOR]: https://www.rfc-editor.org/rfc/rfc8949
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    ///
+    /// assert_eq!(json!([1, [2, 3], (-4.1)]).to_cbor(), [
+    ///     0x83, 0x01, 0x82, 0x02, 0x03, 0xfb, 0xc0, 0x10, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    /// ]);
+    /// assert_eq!(json!([1.5, 65504, 100000.5]).to_cbor(), [
+    ///     0x83, 0xf9, 0x3e, 0x00, 0x19, 0xff, 0xe0, 0xfa, 0x47, 0xc3, 0x50, 0x40,
+    /// ]);
+    /// assert_eq!(json!({"aa": null, "b": true}).to_cbor(), [
+    ///     0xa2, 0x61, b'b', 0xf5, 0x62, b'a', b'a', 0xf6,
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        helper::encode(self, &mut out);
+        out
+    }
+
+    /// Decodes a single [CBOR] (RFC 8949) data item, with default options
+    /// (rejecting any construct without a JSON equivalent).
+    ///
+    /// Definite and indefinite lengths are accepted, as well as non-preferred
+    /// serializations. Integers are converted to `f64` (so possibly rounded
+    /// beyond ±2<sup>53</sup>).
+    ///
+    /// [CBOR]: https://www.rfc-editor.org/rfc/rfc8949
+    ///
+    /// # Errors
+    ///
+    /// If the data is not well-formed, not valid (invalid UTF-8, duplicate keys),
+    /// not completely consumed, or contains an item rejected by the options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{CborError, CborErrorKind, Value};
+    ///
+    /// let value = json!({"a": [1, 0.5, "x"], "b": null});
+    /// assert_eq!(Value::from_cbor(&value.to_cbor()), Ok(value));
+    ///
+    /// // indefinite-length array and (chunked) string
+    /// let bytes = [0x9f, 0x01, 0x7f, 0x62, b'a', b'b', 0x61, b'c', 0xff, 0xff];
+    /// assert_eq!(Value::from_cbor(&bytes), Ok(json!([1, "abc"])));
+    ///
+    /// let error = Value::from_cbor(&[0x82, 0x01]).unwrap_err();
+    /// assert_eq!(error, CborError { kind: CborErrorKind::PrematureEnd, offset: 2 });
+    /// assert_eq!(error.to_string(), "premature end of data at byte 2");
+    /// // byte string
+    /// let error = Value::from_cbor(&[0x81, 0x42, 0x01, 0x02]).unwrap_err();
+    /// assert_eq!(error, CborError { kind: CborErrorKind::ByteString, offset: 1 });
+    /// ```
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, CborError> {
+        Self::from_cbor_with(bytes, &CborOptions::default())
+    }
+
+    /// Decodes a single [CBOR] (RFC 8949) data item, mapping constructs
+    /// without a JSON equivalent as configured (see [`CborOptions`]).
+    ///
+    /// [CBOR]: https://www.rfc-editor.org/rfc/rfc8949
+    ///
+    /// # Errors
+    ///
+    /// See [`Value::from_cbor`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{CborErrorKind, CborOptions, NonJsonPolicy, Value};
+    ///
+    /// // {1: h'01ff', "t": 0("2013-03-21T20:04:00Z"), "u": undefined, "n": -Infinity}
+    /// let mut bytes = vec![0xa4, 0x01, 0x42, 0x01, 0xff, 0x61, b't', 0xc0, 0x74];
+    /// bytes.extend_from_slice(b"2013-03-21T20:04:00Z");
+    /// bytes.extend_from_slice(&[0x61, b'u', 0xf7, 0x61, b'n', 0xf9, 0xfc, 0x00]);
+    ///
+    /// assert_eq!(
+    ///     Value::from_cbor_with(&bytes, &CborOptions::lossy()),
+    ///     Ok(json!({"1": "Af8", "t": "2013-03-21T20:04:00Z", "u": null, "n": null}))
+    /// );
+    ///
+    /// let options = CborOptions { non_string_keys: NonJsonPolicy::Convert, ..CborOptions::default() };
+    /// let error = Value::from_cbor_with(&bytes, &options).unwrap_err();
+    /// assert_eq!((error.kind, error.offset), (CborErrorKind::ByteString, 2));
+    /// let options = CborOptions { byte_strings: NonJsonPolicy::Convert, ..options };
+    /// let error = Value::from_cbor_with(&bytes, &options).unwrap_err();
+    /// assert_eq!((error.kind, error.offset), (CborErrorKind::Tag(0), 7));
+    /// ```
+    pub fn from_cbor_with(bytes: &[u8], options: &CborOptions) -> Result<Self, CborError> {
+        helper::decode(bytes, options)
+    }
+}
+
+/// Options for [`Value::from_cbor_with`].
+///
+/// Converting follows RFC 8949 §6.1.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CborOptions {
+    /// Tagged items: converted to their content (bignums to numbers,
+    /// with tags 21 to 23 selecting the encoding of nested byte strings).
+    pub tags: NonJsonPolicy,
+    /// Byte strings: converted to base64url strings (without padding).
+    pub byte_strings: NonJsonPolicy,
+    /// Map keys other than text strings: converted to their JSON text.
+    pub non_string_keys: NonJsonPolicy,
+    /// `undefined`, unassigned simple values, and non-finite floats: converted to `null`.
+    pub special_values: NonJsonPolicy,
+    /// Maximum nesting of arrays, maps and tags.
+    pub max_depth: usize,
+}
+
+impl CborOptions {
+    /// Converting every construct without a JSON equivalent.
+    #[must_use]
+    pub fn lossy() -> Self {
+        Self {
+            tags: NonJsonPolicy::Convert,
+            byte_strings: NonJsonPolicy::Convert,
+            non_string_keys: NonJsonPolicy::Convert,
+            special_values: NonJsonPolicy::Convert,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for CborOptions {
+    /// Rejecting every construct without a JSON equivalent.
+    fn default() -> Self {
+        Self {
+            tags: NonJsonPolicy::Reject,
+            byte_strings: NonJsonPolicy::Reject,
+            non_string_keys: NonJsonPolicy::Reject,
+            special_values: NonJsonPolicy::Reject,
+            max_depth: 512,
+        }
+    }
+}
+
+/// Error from [`Value::from_cbor`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CborError {
+    pub kind: CborErrorKind,
+    /// Of the start of the offending data item (or of the end of data).
+    pub offset: usize,
+}
+
+impl Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl Error for CborError {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CborErrorKind {
+    PrematureEnd,
+    /// Data after the (first) data item.
+    TrailingData,
+    /// Not well-formed: reserved additional information,
+    /// or misplaced indefinite length or "break".
+    InvalidHeader,
+    InvalidUtf8,
+    DuplicateKey(Str),
+    TooDeep,
+    /// A bignum that does not fit into a finite `f64`.
+    TooBigNumber,
+    /// With the tag number.
+    Tag(u64),
+    ByteString,
+    NonStringKey,
+    Undefined,
+    /// An unassigned simple value, with its number.
+    SimpleValue(u8),
+    NonFiniteFloat,
+}
+
+impl Display for CborErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PrematureEnd => f.write_str("premature end of data"),
+            Self::TrailingData => f.write_str("trailing data"),
+            Self::InvalidHeader => f.write_str("invalid header"),
+            Self::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            Self::DuplicateKey(key) => write!(f, "duplicate key {:?}", key),
+            Self::TooDeep => f.write_str("too deep nesting"),
+            Self::TooBigNumber => f.write_str("too big number"),
+            Self::Tag(tag) => write!(f, "unsupported tag {}", tag),
+            Self::ByteString => f.write_str("unsupported byte string"),
+            Self::NonStringKey => f.write_str("unsupported non-string key"),
+            Self::Undefined => f.write_str("unsupported undefined"),
+            Self::SimpleValue(simple) => write!(f, "unsupported simple value {}", simple),
+            Self::NonFiniteFloat => f.write_str("unsupported non-finite float"),
+        }
+    }
+}
+
+mod helper;
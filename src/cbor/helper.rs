@@ -0,0 +1,437 @@
+use super::*;
+
+type DecodeResult<T> = Result<T, CborError>;
+
+use CborErrorKind as K;
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+
+const INDEFINITE: u8 = 31;
+const BREAK: u8 = 0xff;
+
+pub(super) fn encode(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.push(0xf6),
+        Value::Boolean(b) => out.push(if *b { 0xf5 } else { 0xf4 }),
+        Value::Number(num) => encode_number(*num, out),
+        Value::String(str) => {
+            write_head(MAJOR_TEXT, str.len() as u64, out);
+            out.extend_from_slice(str.as_bytes());
+        }
+        Value::Array(arr) => {
+            write_head(MAJOR_ARRAY, arr.len() as u64, out);
+            for element in arr {
+                encode(element, out);
+            }
+        }
+        Value::Object(obj) => {
+            write_head(MAJOR_MAP, obj.len() as u64, out);
+            // bytewise order of the encoded keys: by length first (stable sort, so then bytewise)
+            let mut members: Vec<_> = obj.iter().collect();
+            members.sort_by_key(|(key, _)| key.len());
+            for (key, value) in members {
+                write_head(MAJOR_TEXT, key.len() as u64, out);
+                out.extend_from_slice(key.as_bytes());
+                encode(value, out);
+            }
+        }
+    }
+}
+
+/// In the shortest form.
+fn write_head(major: u8, n: u64, out: &mut Vec<u8>) {
+    let major = major << 5;
+    if n < 24 {
+        out.push(major | n as u8);
+    } else if let Ok(n) = u8::try_from(n) {
+        out.extend_from_slice(&[major | 24, n]);
+    } else if let Ok(n) = u16::try_from(n) {
+        out.push(major | 25);
+        out.extend_from_slice(&n.to_be_bytes());
+    } else if let Ok(n) = u32::try_from(n) {
+        out.push(major | 26);
+        out.extend_from_slice(&n.to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&n.to_be_bytes());
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn encode_number(num: Num, out: &mut Vec<u8>) {
+    let f = num.get();
+    if let Some(u) = num.as_u64() {
+        write_head(MAJOR_UNSIGNED, u, out);
+    } else if let Some(u) = Num::new(-f).and_then(Num::as_u64).filter(|&u| u > 0) {
+        // (down to -2^64)
+        write_head(MAJOR_NEGATIVE, u - 1, out);
+    } else if let Some(bits) = f16_bits(f) {
+        out.push(0xf9);
+        out.extend_from_slice(&bits.to_be_bytes());
+    } else if f64::from(f as f32) == f {
+        out.push(0xfa);
+        out.extend_from_slice(&(f as f32).to_be_bytes());
+    } else {
+        out.push(0xfb);
+        out.extend_from_slice(&f.to_be_bytes());
+    }
+}
+
+/// Bits of the half-precision float exactly equal to the given finite value, if any.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn f16_bits(f: f64) -> Option<u16> {
+    let bits = f.to_bits();
+    let sign = ((bits >> 48) & 0x8000) as u16;
+    if f == 0.0 {
+        return Some(sign);
+    }
+    let exponent = ((bits >> 52) & 0x7ff) as i32 - 1023;
+    let mantissa = bits & ((1 << 52) - 1);
+    if exponent > 15 {
+        None
+    } else if exponent >= -14 {
+        // normal: only the top 10 bits of the mantissa may be set
+        (mantissa & ((1 << 42) - 1) == 0)
+            .then(|| sign | ((exponent + 15) as u16) << 10 | (mantissa >> 42) as u16)
+    } else {
+        // subnormal: a multiple of 2^-24
+        let scaled = f.abs() * 16_777_216.0;
+        (scaled.fract() == 0.0 && scaled >= 1.0).then_some(sign | scaled as u16)
+    }
+}
+
+fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 == 0 { 1.0 } else { -1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f64::from(bits & 0x3ff);
+    sign * match exponent {
+        0 => mantissa * 2_f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (1024.0 + mantissa) * 2_f64.powi(exponent - 25),
+    }
+}
+
+pub(super) fn decode(bytes: &[u8], options: &CborOptions) -> DecodeResult<Value> {
+    let mut decoder = Decoder {
+        bytes,
+        offset: 0,
+        options,
+        depth: 0,
+    };
+    let value = decoder.decode_item(ByteEncoding::Base64Url)?;
+    if decoder.offset < bytes.len() {
+        return Err(decoder.error_at(K::TrailingData, decoder.offset));
+    }
+    Ok(value)
+}
+
+/// How byte strings are converted to text (RFC 8949 §6.1, tags 21 to 23).
+#[derive(Clone, Copy)]
+enum ByteEncoding {
+    Base64Url,
+    Base64,
+    Base16,
+}
+
+struct Decoder<'b, 'o> {
+    bytes: &'b [u8],
+    offset: usize,
+    options: &'o CborOptions,
+    depth: usize,
+}
+
+/// Argument of a data item header.
+enum Argument {
+    Value(u64),
+    Indefinite,
+}
+
+impl<'b> Decoder<'b, '_> {
+    fn error_at(&self, kind: CborErrorKind, offset: usize) -> CborError {
+        CborError { kind, offset }
+    }
+
+    /// Fails with the error unless the options convert the construct.
+    fn check(
+        &self,
+        mapping: NonJsonPolicy,
+        kind: CborErrorKind,
+        offset: usize,
+    ) -> DecodeResult<()> {
+        match mapping {
+            NonJsonPolicy::Reject => Err(self.error_at(kind, offset)),
+            NonJsonPolicy::Convert => Ok(()),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> DecodeResult<&'b [u8]> {
+        match self
+            .bytes
+            .get(self.offset..)
+            .and_then(|rest| rest.get(..len))
+        {
+            Some(taken) => {
+                self.offset += len;
+                Ok(taken)
+            }
+            None => Err(self.error_at(K::PrematureEnd, self.bytes.len())),
+        }
+    }
+
+    fn peek_break(&self) -> DecodeResult<bool> {
+        match self.bytes.get(self.offset) {
+            Some(&byte) => Ok(byte == BREAK),
+            None => Err(self.error_at(K::PrematureEnd, self.offset)),
+        }
+    }
+
+    /// Returns the major type, additional information and argument.
+    fn read_head(&mut self) -> DecodeResult<(u8, u8, Argument)> {
+        let start = self.offset;
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        let argument = match info {
+            0..=23 => Argument::Value(u64::from(info)),
+            24 => Argument::Value(u64::from(self.take(1)?[0])),
+            25 => Argument::Value(u64::from(u16::from_be_bytes(self.array()?))),
+            26 => Argument::Value(u64::from(u32::from_be_bytes(self.array()?))),
+            27 => Argument::Value(u64::from_be_bytes(self.array()?)),
+            INDEFINITE if matches!(major, MAJOR_BYTES..=MAJOR_MAP) => Argument::Indefinite,
+            _ => return Err(self.error_at(K::InvalidHeader, start)),
+        };
+        Ok((major, info, argument))
+    }
+
+    fn array<const N: usize>(&mut self) -> DecodeResult<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("N bytes"))
+    }
+
+    fn length(&self, n: u64, start: usize) -> DecodeResult<usize> {
+        usize::try_from(n).map_err(|_| self.error_at(K::PrematureEnd, start))
+    }
+
+    fn enter(&mut self, start: usize) -> DecodeResult<()> {
+        if self.depth >= self.options.max_depth {
+            return Err(self.error_at(K::TooDeep, start));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn decode_item(&mut self, encoding: ByteEncoding) -> DecodeResult<Value> {
+        let start = self.offset;
+        let (major, info, argument) = self.read_head()?;
+        match (major, argument) {
+            (MAJOR_UNSIGNED, Argument::Value(n)) => Ok(number(n as f64)),
+            (MAJOR_NEGATIVE, Argument::Value(n)) => Ok(number(-1.0 - n as f64)),
+            (MAJOR_BYTES, argument) => {
+                self.check(self.options.byte_strings, K::ByteString, start)?;
+                let bytes = self.read_string(MAJOR_BYTES, argument, start)?;
+                Ok(Value::from(encode_bytes(&bytes, encoding)))
+            }
+            (MAJOR_TEXT, argument) => {
+                let bytes = self.read_string(MAJOR_TEXT, argument, start)?;
+                let string = String::from_utf8(bytes).expect("validated chunks");
+                Ok(Value::from(string))
+            }
+            (MAJOR_ARRAY, argument) => {
+                self.enter(start)?;
+                let mut arr = Arr::new();
+                match argument {
+                    Argument::Value(n) => {
+                        for _ in 0..n {
+                            arr.push(self.decode_item(encoding)?);
+                        }
+                    }
+                    Argument::Indefinite => {
+                        while !self.peek_break()? {
+                            arr.push(self.decode_item(encoding)?);
+                        }
+                        self.offset += 1;
+                    }
+                }
+                self.depth -= 1;
+                Ok(Value::Array(arr))
+            }
+            (MAJOR_MAP, argument) => {
+                self.enter(start)?;
+                let mut obj = Obj::new();
+                match argument {
+                    Argument::Value(n) => {
+                        for _ in 0..n {
+                            self.decode_member(&mut obj, encoding)?;
+                        }
+                    }
+                    Argument::Indefinite => {
+                        while !self.peek_break()? {
+                            self.decode_member(&mut obj, encoding)?;
+                        }
+                        self.offset += 1;
+                    }
+                }
+                self.depth -= 1;
+                Ok(Value::Object(obj))
+            }
+            (MAJOR_TAG, Argument::Value(tag)) => {
+                self.check(self.options.tags, K::Tag(tag), start)?;
+                self.enter(start)?;
+                let value = self.decode_tagged(tag, encoding, start)?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            (MAJOR_SIMPLE, Argument::Value(n)) => self.decode_simple(info, n, start),
+            _ => unreachable!("indefinite length only for strings and containers"),
+        }
+    }
+
+    /// Reads the content of a byte or text string, of definite or indefinite length.
+    fn read_string(
+        &mut self,
+        major: u8,
+        argument: Argument,
+        start: usize,
+    ) -> DecodeResult<Vec<u8>> {
+        let Argument::Value(n) = argument else {
+            let mut bytes = Vec::new();
+            while !self.peek_break()? {
+                let chunk_start = self.offset;
+                match self.read_head()? {
+                    (chunk_major, _, Argument::Value(n)) if chunk_major == major => {
+                        bytes.extend_from_slice(self.read_chunk(major, n, chunk_start)?);
+                    }
+                    _ => return Err(self.error_at(K::InvalidHeader, chunk_start)),
+                }
+            }
+            self.offset += 1;
+            return Ok(bytes);
+        };
+        Ok(self.read_chunk(major, n, start)?.to_vec())
+    }
+
+    fn read_chunk(&mut self, major: u8, n: u64, start: usize) -> DecodeResult<&'b [u8]> {
+        let len = self.length(n, start)?;
+        let chunk = self.take(len)?;
+        if major == MAJOR_TEXT && std::str::from_utf8(chunk).is_err() {
+            return Err(self.error_at(K::InvalidUtf8, start));
+        }
+        Ok(chunk)
+    }
+
+    fn decode_member(&mut self, obj: &mut Obj, encoding: ByteEncoding) -> DecodeResult<()> {
+        let start = self.offset;
+        let is_text = self
+            .bytes
+            .get(start)
+            .is_some_and(|byte| byte >> 5 == MAJOR_TEXT);
+        if !is_text {
+            self.check(self.options.non_string_keys, K::NonStringKey, start)?;
+        }
+        let key = match self.decode_item(encoding)? {
+            Value::String(str) if is_text => str,
+            other => Str::from(other.to_string()),
+        };
+        if obj.contains_key(&key) {
+            return Err(self.error_at(K::DuplicateKey(key), start));
+        }
+        let value = self.decode_item(encoding)?;
+        obj.insert(key, value);
+        Ok(())
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn decode_tagged(
+        &mut self,
+        tag: u64,
+        encoding: ByteEncoding,
+        start: usize,
+    ) -> DecodeResult<Value> {
+        let encoding = match tag {
+            21 => ByteEncoding::Base64Url,
+            22 => ByteEncoding::Base64,
+            23 => ByteEncoding::Base16,
+            _ => encoding,
+        };
+        let content_start = self.offset;
+        let is_bignum = matches!(tag, 2 | 3)
+            && self
+                .bytes
+                .get(content_start)
+                .is_some_and(|byte| byte >> 5 == MAJOR_BYTES);
+        if !is_bignum {
+            return self.decode_item(encoding);
+        }
+        let (_, _, argument) = self.read_head()?;
+        let bytes = self.read_string(MAJOR_BYTES, argument, content_start)?;
+        let magnitude = bytes
+            .iter()
+            .fold(0.0, |acc, &byte| acc * 256.0 + f64::from(byte));
+        let f = if tag == 2 {
+            magnitude
+        } else {
+            -1.0 - magnitude
+        };
+        Num::new(f)
+            .map(Value::Number)
+            .ok_or_else(|| self.error_at(K::TooBigNumber, start))
+    }
+
+    fn decode_simple(&mut self, info: u8, n: u64, start: usize) -> DecodeResult<Value> {
+        let f = match info {
+            20 => return Ok(Value::Boolean(false)),
+            21 => return Ok(Value::Boolean(true)),
+            22 => return Ok(Value::Null),
+            23 => {
+                self.check(self.options.special_values, K::Undefined, start)?;
+                return Ok(Value::Null);
+            }
+            // simple value in the following byte: only 32..=255 (RFC 8949 §3.3)
+            24 if n < 32 => return Err(self.error_at(K::InvalidHeader, start)),
+            0..=19 | 24 => {
+                let simple = u8::try_from(n).expect("one byte");
+                self.check(self.options.special_values, K::SimpleValue(simple), start)?;
+                return Ok(Value::Null);
+            }
+            25 => f16_to_f64(u16::try_from(n).expect("two bytes")),
+            26 => f64::from(f32::from_bits(u32::try_from(n).expect("four bytes"))),
+            _ => f64::from_bits(n),
+        };
+        match Num::new(f) {
+            Some(num) => Ok(Value::Number(num)),
+            None => {
+                self.check(self.options.special_values, K::NonFiniteFloat, start)?;
+                Ok(Value::Null)
+            }
+        }
+    }
+}
+
+fn number(f: f64) -> Value {
+    Value::Number(Num::new(f).expect("finite number"))
+}
+
+fn encode_bytes(bytes: &[u8], encoding: ByteEncoding) -> String {
+    const BASE16: &[u8; 16] = b"0123456789abcdef";
+    match encoding {
+        ByteEncoding::Base16 => bytes
+            .iter()
+            .flat_map(|byte| {
+                [
+                    BASE16[usize::from(byte >> 4)],
+                    BASE16[usize::from(byte & 0xf)],
+                ]
+            })
+            .map(char::from)
+            .collect(),
+        ByteEncoding::Base64 => base64::encode(bytes, base64::STANDARD, true),
+        ByteEncoding::Base64Url => base64::encode(bytes, base64::URL_SAFE, false),
+    }
+}
@@ -0,0 +1,740 @@
+use super::*;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::hash::{BuildHasher, Hash};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Conversion of a Rust value into a [`Value`].
+///
+/// Implemented for:
+/// - all integer types (converted to `f64`, so possibly rounded beyond ±2<sup>53</sup>),
+///   `f32` and `f64` (NaN and infinities become `null`, as in JavaScript), [`Num`];
+/// - `bool`, `()` (as `null`);
+/// - `str`, `String`, [`Str`], `char`;
+/// - `Option` (`None` as `null`);
+/// - `[T]`, `[T; N]`, `Vec`, tuples (as arrays);
+/// - `BTreeMap` and `HashMap` with string-like keys (as objects);
+/// - `&T`, `Box`, `Rc`, `Arc` (transparently);
+/// - [`Value`] itself.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::ToJson;
+/// use std::collections::BTreeMap;
+///
+/// let ports: BTreeMap<&str, Vec<u16>> = BTreeMap::from([("http", vec![80, 8080]), ("https", vec![443])]);
+/// assert_eq!(ports.to_json(), json!({"http": [80, 8080], "https": [443]}));
+/// assert_eq!((1_u8, 'x', None::<bool>, f64::NAN).to_json(), json!([1, "x", null, null]));
+/// ```
+///
+/// # Deriving
+///
+/// With the (default) `derive` feature, both traits can be derived for structs and enums:
+/// - a struct with named fields is an object,
+///   a tuple struct is an array (except with a single field, which is transparent),
+///   a unit struct is `null`;
+/// - an enum variant is represented like a struct, but tagged with its name:
+///   - externally: `"Unit"` or `{"Variant": ...}` (by default),
+///   - internally: `{"tag": "Variant", ...fields}` with `#[json(tag = "tag")]`
+///     (not for tuple variants; a newtype variant must contain an object, see below),
+///   - adjacently: `{"tag": "Variant", "content": ...}` with `#[json(tag = "tag", content = "content")]`,
+///   - or not at all with `#[json(untagged)]` (the first variant that converts wins).
+///
+/// Other attributes:
+/// - on a container: `#[json(rename_all = "...")]`
+///   (for struct fields, or enum variant names) with one of
+///   `lowercase`, `UPPERCASE`, `PascalCase`, `camelCase`,
+///   `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case`, `SCREAMING-KEBAB-CASE`;
+/// - on a variant: `#[json(rename = "...")]`, `#[json(rename_all = "...")]` (for its fields);
+/// - on a named field:
+///   - `#[json(rename = "...")]`;
+///   - `#[json(skip)]` (uses `Default` when converting from JSON);
+///   - `#[json(default)]` (uses `Default` when missing;
+///     otherwise a missing field is converted from `null` if possible, e.g. `Option`);
+///   - `#[json(flatten)]` (merges the members of the field, which must be an object).
+///
+/// Unknown object members are ignored when converting from JSON.
+///
+/// Rather than losing data, the content of an internally tagged newtype variant
+/// or a flattened field must be an object: a type known not to be one
+/// (a primitive, string, `Vec`, array, slice or tuple) is a compilation error,
+/// and converting any other value that is not an object into JSON panics.
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use rustic_json::json;
+/// use rustic_json::{FromJson, FromJsonErrorKind, ToJson};
+///
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// #[json(rename_all = "camelCase")]
+/// struct Server {
+///     host_name: String,
+///     #[json(rename = "port")]
+///     port_number: u16,
+///     #[json(default)]
+///     tags: Vec<String>,
+///     timeout: Option<f64>,
+///     #[json(skip)]
+///     connections: usize,
+///     #[json(flatten)]
+///     limits: Limits,
+/// }
+///
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// struct Limits {
+///     max_body: u32,
+/// }
+///
+/// let server = Server {
+///     host_name: "example.com".into(),
+///     port_number: 443,
+///     tags: vec![],
+///     timeout: None,
+///     connections: 12,
+///     limits: Limits { max_body: 1024 },
+/// };
+/// let value = json!({"hostName": "example.com", "port": 443, "tags": [], "timeout": null, "max_body": 1024});
+/// assert_eq!(server.to_json(), value);
+/// assert_eq!(
+///     Server::from_json(&json!({"hostName": "example.com", "port": 443, "max_body": 1024})),
+///     Ok(Server { connections: 0, ..server })
+/// );
+///
+/// let error = Server::from_json(&json!({"hostName": "x", "max_body": 1})).unwrap_err();
+/// assert_eq!(error.kind, FromJsonErrorKind::MissingField("port".into()));
+/// let error = Server::from_json(&json!({"hostName": "x", "port": (-1), "max_body": 1})).unwrap_err();
+/// assert_eq!(error.to_string(), r#"number out of range at "/port""#);
+///
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// struct Meters(f64);
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// struct Pair<T>(T, T);
+///
+/// assert_eq!(Meters(1.5).to_json(), json!(1.5));
+/// assert_eq!(Pair('a', 'b').to_json(), json!(["a", "b"]));
+/// assert_eq!(Pair::<u8>::from_json(&json!([1, 2])), Ok(Pair(1, 2)));
+/// ```
+///
+/// Enum representations:
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use rustic_json::json;
+/// use rustic_json::{FromJson, FromJsonErrorKind, ToJson};
+///
+/// macro_rules! check {
+///     ($value:expr, $json:tt) => {
+///         assert_eq!($value.to_json(), json!($json));
+///         assert_eq!(FromJson::from_json(&json!($json)), Ok($value));
+///     };
+/// }
+///
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// #[json(rename_all = "snake_case")]
+/// enum External {
+///     Stop,
+///     MoveTo(i32, i32),
+///     Say(String),
+///     #[json(rename = "config", rename_all = "UPPERCASE")]
+///     Configure { level: u8 },
+/// }
+/// check!(External::Stop, "stop");
+/// check!(External::MoveTo(1, 2), {"move_to": [1, 2]});
+/// check!(External::Say("hi".into()), {"say": "hi"});
+/// check!(External::Configure { level: 3 }, {"config": {"LEVEL": 3}});
+/// let error = External::from_json(&json!({"say": 1})).unwrap_err();
+/// assert_eq!(error.path.as_str(), "/say");
+/// let error = External::from_json(&json!("go")).unwrap_err();
+/// assert_eq!(error.kind, FromJsonErrorKind::UnknownVariant("go".into()));
+///
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// #[json(tag = "type")]
+/// enum Internal {
+///     Ping,
+///     Move { x: i32, y: i32 },
+///     Wrapped(Point),
+/// }
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// struct Point {
+///     x: i32,
+/// }
+/// check!(Internal::Ping, {"type": "Ping"});
+/// check!(Internal::Move { x: 1, y: 2 }, {"type": "Move", "x": 1, "y": 2});
+/// check!(Internal::Wrapped(Point { x: 3 }), {"type": "Wrapped", "x": 3});
+///
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// #[json(tag = "t", content = "c")]
+/// enum Adjacent {
+///     Ping,
+///     Pair(u8, u8),
+/// }
+/// check!(Adjacent::Ping, {"t": "Ping"});
+/// check!(Adjacent::Pair(1, 2), {"t": "Pair", "c": [1, 2]});
+///
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// #[json(untagged)]
+/// enum Untagged {
+///     Number(f64),
+///     Text(String),
+///     Point { x: i32 },
+///     Nothing,
+/// }
+/// check!(Untagged::Number(1.5), 1.5);
+/// check!(Untagged::Text("hi".into()), "hi");
+/// check!(Untagged::Point { x: 1 }, {"x": 1});
+/// check!(Untagged::Nothing, null);
+/// assert!(Untagged::from_json(&json!([])).is_err());
+///
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// #[json(rename_all = "camelCase")]
+/// enum Season {
+///     Été,
+///     ÉtéIndien,
+/// }
+/// check!(Season::Été, "été");
+/// check!(Season::ÉtéIndien, "étéIndien");
+///
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// #[json(rename_all = "snake_case")]
+/// enum Saison {
+///     ÉtéIndien,
+/// }
+/// check!(Saison::ÉtéIndien, "été_indien");
+///
+/// #[derive(Debug, PartialEq, ToJson, FromJson)]
+/// #[json(rename_all = "SCREAMING-KEBAB-CASE")]
+/// struct Météo {
+///     été_indien: bool,
+/// }
+/// check!(Météo { été_indien: true }, {"ÉTÉ-INDIEN": true});
+/// ```
+///
+/// Compilation error (attribute without arguments):
+///
+#[cfg_attr(feature = "derive", doc = "```compile_fail")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use rustic_json::ToJson;
+///
+/// #[derive(ToJson)]
+/// struct Server {
+///     #[json]
+///     host: String,
+/// }
+/// ```
+///
+/// Compilation error (flattened field that cannot be an object):
+///
+#[cfg_attr(feature = "derive", doc = "```compile_fail")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use rustic_json::ToJson;
+///
+/// #[derive(ToJson)]
+/// struct Page {
+///     #[json(flatten)]
+///     id: u32,
+/// }
+/// ```
+///
+/// Compilation error (internally tagged newtype variant that cannot contain an object):
+///
+#[cfg_attr(feature = "derive", doc = "```compile_fail")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use rustic_json::ToJson;
+///
+/// #[derive(ToJson)]
+/// #[json(tag = "type")]
+/// enum Message {
+///     Count(Vec<u32>),
+/// }
+/// ```
+///
+/// Panic (internally tagged newtype variant not containing an object):
+///
+#[cfg_attr(feature = "derive", doc = "```should_panic")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use rustic_json::{json, ToJson, Value};
+///
+/// #[derive(ToJson)]
+/// #[json(tag = "type")]
+/// enum Message {
+///     Raw(Value),
+/// }
+///
+/// assert_eq!(Message::Raw(json!({"a": 1})).to_json(), json!({"type": "Raw", "a": 1}));
+/// let _ = Message::Raw(json!([1, 2])).to_json();
+/// ```
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+/// Conversion of a [`Value`] into a Rust value.
+///
+/// Implemented for the same types as [`ToJson`] (except `()`, `&T`, `[T]`, and `&str`):
+/// - integers must be whole numbers within range, `f32` must be within range;
+/// - `char` must be a string of exactly one char;
+/// - `[T; N]` and tuples must be arrays of the right length;
+/// - `Option` is `None` for `null` (only).
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::{FromJson, FromJsonErrorKind};
+/// use std::collections::HashMap;
+///
+/// let value = json!({"a": [1, 2], "b": [3, null]});
+/// let map = HashMap::<String, Vec<Option<u8>>>::from_json(&value).unwrap();
+/// assert_eq!(map["b"], [Some(3), None]);
+///
+/// let error = HashMap::<String, Vec<u8>>::from_json(&value).unwrap_err();
+/// assert_eq!(error.path.as_str(), "/b/1");
+/// assert_eq!(error.kind, FromJsonErrorKind::InvalidType { expected: "number", found: "null" });
+/// assert_eq!(error.to_string(), r#"expected number at "/b/1", found null"#);
+///
+/// let error = <(i8, char)>::from_json(&json!([300, "x"])).unwrap_err();
+/// assert_eq!((error.path.as_str(), error.kind), ("/0", FromJsonErrorKind::OutOfRange));
+/// let error = <(i8, char)>::from_json(&json!([1, "xy"])).unwrap_err();
+/// assert_eq!((error.path.as_str(), error.kind), ("/1", FromJsonErrorKind::InvalidValue));
+/// let error = <[bool; 2]>::from_json(&json!([true])).unwrap_err();
+/// assert_eq!(error.kind, FromJsonErrorKind::InvalidLength { expected: 2, found: 1 });
+/// ```
+pub trait FromJson: Sized {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError>;
+}
+
+/// Error from [`FromJson`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FromJsonError {
+    pub kind: FromJsonErrorKind,
+    /// Location of the mismatch in the converted value.
+    pub path: JsonPointer,
+}
+
+impl FromJsonError {
+    /// A new error at the root.
+    #[must_use]
+    pub fn new(kind: FromJsonErrorKind) -> Self {
+        Self {
+            kind,
+            path: JsonPointer::root(),
+        }
+    }
+
+    /// A new [`FromJsonErrorKind::InvalidType`] error at the root.
+    #[must_use]
+    pub fn invalid_type(expected: &'static str, found: &Value) -> Self {
+        Self::new(FromJsonErrorKind::InvalidType {
+            expected,
+            found: found.type_name(),
+        })
+    }
+
+    /// Moves the error into the object member or array element identified by the token,
+    /// for use by containers converting their contents.
+    #[must_use]
+    pub fn within(mut self, token: impl Into<PointerToken>) -> Self {
+        let mut path = JsonPointer::root().join(token);
+        path.append(&self.path);
+        self.path = path;
+        self
+    }
+}
+
+impl Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            FromJsonErrorKind::InvalidType { expected, found } => {
+                write!(
+                    f,
+                    "expected {} at \"{}\", found {}",
+                    expected, self.path, found
+                )
+            }
+            kind => write!(f, "{} at \"{}\"", kind, self.path),
+        }
+    }
+}
+
+impl Error for FromJsonError {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FromJsonErrorKind {
+    /// With the expected and found types (see [`Value::type_name`]).
+    InvalidType {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// Number not representable in the target type.
+    OutOfRange,
+    /// Right type but otherwise invalid.
+    InvalidValue,
+    /// Array of the wrong length.
+    InvalidLength {
+        expected: usize,
+        found: usize,
+    },
+    MissingField(Str),
+    UnknownField(Str),
+    UnknownVariant(Str),
+    Custom(String),
+}
+
+impl Display for FromJsonErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidType { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            Self::OutOfRange => f.write_str("number out of range"),
+            Self::InvalidValue => f.write_str("invalid value"),
+            Self::InvalidLength { expected, found } => {
+                write!(f, "expected array of length {}, found {}", expected, found)
+            }
+            Self::MissingField(name) => write!(f, "missing field {:?}", name),
+            Self::UnknownField(name) => write!(f, "unknown field {:?}", name),
+            Self::UnknownVariant(name) => write!(f, "unknown variant {:?}", name),
+            Self::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+impl Value {
+    /// One of `"null"`, `"boolean"`, `"number"`, `"string"`, `"array"`, `"object"`.
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Boolean(_) => "boolean",
+            Self::Number(_) => "number",
+            Self::String(_) => "string",
+            Self::Array(_) => "array",
+            Self::Object(_) => "object",
+        }
+    }
+}
+
+impl ToJson for Value {
+    fn to_json(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl FromJson for Value {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for () {
+    fn to_json(&self) -> Value {
+        Value::Null
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Value {
+        Value::Boolean(*self)
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        match value {
+            Value::Boolean(b) => Ok(*b),
+            _ => Err(FromJsonError::invalid_type("boolean", value)),
+        }
+    }
+}
+
+impl ToJson for Num {
+    fn to_json(&self) -> Value {
+        Value::Number(*self)
+    }
+}
+
+impl FromJson for Num {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        match value {
+            Value::Number(num) => Ok(*num),
+            _ => Err(FromJsonError::invalid_type("number", value)),
+        }
+    }
+}
+
+macro_rules! impl_float {
+    ($($typ:ty),+) => {$(
+        impl ToJson for $typ {
+            fn to_json(&self) -> Value {
+                Num::new(f64::from(*self)).map_or(Value::Null, Value::Number)
+            }
+        }
+
+        impl FromJson for $typ {
+            #[allow(clippy::cast_possible_truncation)]
+            fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+                let f = Num::from_json(value)?.get() as $typ;
+                if f.is_finite() {
+                    Ok(f)
+                } else {
+                    Err(FromJsonError::new(FromJsonErrorKind::OutOfRange))
+                }
+            }
+        }
+    )+};
+}
+
+impl_float!(f32, f64);
+
+macro_rules! impl_integer {
+    ($signed:literal: $($typ:ty),+) => {$(
+        impl ToJson for $typ {
+            #[allow(clippy::cast_precision_loss, clippy::cast_lossless)]
+            fn to_json(&self) -> Value {
+                Value::Number(Num::new(*self as f64).expect("finite number"))
+            }
+        }
+
+        impl FromJson for $typ {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_possible_wrap)]
+            fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+                let f = Num::from_json(value)?.get();
+                // exclusive, exact power of two
+                let max = 2_f64.powi(<$typ>::BITS as i32 - i32::from($signed));
+                let min = if $signed { -max } else { 0.0 };
+                if f.fract() != 0.0 || f < min || f >= max {
+                    return Err(FromJsonError::new(FromJsonErrorKind::OutOfRange));
+                }
+                Ok(f as $typ)
+            }
+        }
+    )+};
+}
+
+impl_integer!(true: i8, i16, i32, i64, i128, isize);
+impl_integer!(false: u8, u16, u32, u64, u128, usize);
+
+impl ToJson for str {
+    fn to_json(&self) -> Value {
+        Value::String(Str::from(String::from(self)))
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Value {
+        Value::String(Str::from(self.clone()))
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        Str::from_json(value).map(String::from)
+    }
+}
+
+impl ToJson for Str {
+    fn to_json(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+impl FromJson for Str {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        match value {
+            Value::String(str) => Ok(str.clone()),
+            _ => Err(FromJsonError::invalid_type("string", value)),
+        }
+    }
+}
+
+impl ToJson for char {
+    fn to_json(&self) -> Value {
+        Value::String(Str::from(String::from(*self)))
+    }
+}
+
+impl FromJson for char {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        let str = Str::from_json(value)?;
+        let mut chars = str.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(FromJsonError::new(FromJsonErrorKind::InvalidValue)),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Value {
+        self.as_ref().map_or(Value::Null, T::to_json)
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        match value {
+            Value::Null => Ok(None),
+            _ => T::from_json(value).map(Some),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(T::to_json).collect())
+    }
+}
+
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+    fn to_json(&self) -> Value {
+        self[..].to_json()
+    }
+}
+
+impl<T: FromJson, const N: usize> FromJson for [T; N] {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        let elements = array_of_len(value, N)?;
+        let vec = elements
+            .iter()
+            .enumerate()
+            .map(|(i, element)| T::from_json(element).map_err(|error| error.within(i)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(vec.try_into().ok().expect("length checked"))
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        self[..].to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        let Value::Array(arr) = value else {
+            return Err(FromJsonError::invalid_type("array", value));
+        };
+        arr.iter()
+            .enumerate()
+            .map(|(i, element)| T::from_json(element).map_err(|error| error.within(i)))
+            .collect()
+    }
+}
+
+fn array_of_len(value: &Value, len: usize) -> Result<&Arr, FromJsonError> {
+    match value {
+        Value::Array(arr) if arr.len() == len => Ok(arr),
+        Value::Array(arr) => Err(FromJsonError::new(FromJsonErrorKind::InvalidLength {
+            expected: len,
+            found: arr.len(),
+        })),
+        _ => Err(FromJsonError::invalid_type("array", value)),
+    }
+}
+
+macro_rules! impl_tuple {
+    ($len:literal: $($typ:ident $i:tt),+) => {
+        impl<$($typ: ToJson),+> ToJson for ($($typ,)+) {
+            fn to_json(&self) -> Value {
+                Value::Array(Arr::from([$(self.$i.to_json()),+]))
+            }
+        }
+
+        impl<$($typ: FromJson),+> FromJson for ($($typ,)+) {
+            fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+                let arr = array_of_len(value, $len)?;
+                Ok(($($typ::from_json(&arr[$i]).map_err(|error| error.within($i))?,)+))
+            }
+        }
+    };
+}
+
+impl_tuple!(1: A 0);
+impl_tuple!(2: A 0, B 1);
+impl_tuple!(3: A 0, B 1, C 2);
+impl_tuple!(4: A 0, B 1, C 2, D 3);
+impl_tuple!(5: A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(6: A 0, B 1, C 2, D 3, E 4, F 5);
+impl_tuple!(7: A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_tuple!(8: A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_tuple!(9: A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_tuple!(10: A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_tuple!(11: A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_tuple!(12: A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+fn object_to_json<'a, K: AsRef<str> + 'a, V: ToJson + 'a>(
+    members: impl Iterator<Item = (&'a K, &'a V)>,
+) -> Value {
+    Value::Object(
+        members
+            .map(|(key, value)| (Str::from(String::from(key.as_ref())), value.to_json()))
+            .collect(),
+    )
+}
+
+fn object_from_json<K: From<Str>, V: FromJson, C: FromIterator<(K, V)>>(
+    value: &Value,
+) -> Result<C, FromJsonError> {
+    let Value::Object(obj) = value else {
+        return Err(FromJsonError::invalid_type("object", value));
+    };
+    obj.iter()
+        .map(|(key, value)| {
+            let value = V::from_json(value).map_err(|error| error.within(&**key))?;
+            Ok((K::from(key.clone()), value))
+        })
+        .collect()
+}
+
+impl<K: AsRef<str>, V: ToJson> ToJson for BTreeMap<K, V> {
+    fn to_json(&self) -> Value {
+        object_to_json(self.iter())
+    }
+}
+
+impl<K: From<Str> + Ord, V: FromJson> FromJson for BTreeMap<K, V> {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        object_from_json(value)
+    }
+}
+
+impl<K: AsRef<str>, V: ToJson, S> ToJson for HashMap<K, V, S> {
+    fn to_json(&self) -> Value {
+        object_to_json(self.iter())
+    }
+}
+
+impl<K: From<Str> + Eq + Hash, V: FromJson, S: BuildHasher + Default> FromJson
+    for HashMap<K, V, S>
+{
+    fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+        object_from_json(value)
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Value {
+        (**self).to_json()
+    }
+}
+
+macro_rules! impl_pointer {
+    ($($ptr:ident),+) => {$(
+        impl<T: ToJson + ?Sized> ToJson for $ptr<T> {
+            fn to_json(&self) -> Value {
+                (**self).to_json()
+            }
+        }
+
+        impl<T: FromJson> FromJson for $ptr<T> {
+            fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+                T::from_json(value).map($ptr::new)
+            }
+        }
+    )+};
+}
+
+impl_pointer!(Box, Rc, Arc);
@@ -0,0 +1,251 @@
+use super::*;
+use std::fmt::{self, Display};
+
+/// Structural differences between two values, from `left` to `right`
+/// (empty exactly when they are equal).
+///
+/// Objects are compared key by key, and arrays index by index
+/// (so an insertion shows up as changes to all the following elements).
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::{diff, Change, ChangeKind, JsonPointer};
+///
+/// let left = json!({"name": "Alice", "tags": ["a"], "age": 30});
+/// let right = json!({"name": "Bob", "tags": ["a", "b"], "email": null});
+/// let diff = diff(&left, &right);
+/// assert_eq!(diff.changes, [
+///     Change { pointer: "/age".parse().unwrap(), kind: ChangeKind::Removed(json!(30)) },
+///     Change { pointer: "/email".parse().unwrap(), kind: ChangeKind::Added(json!(null)) },
+///     Change {
+///         pointer: "/name".parse().unwrap(),
+///         kind: ChangeKind::Changed { old: json!("Alice"), new: json!("Bob") },
+///     },
+///     Change { pointer: "/tags/1".parse().unwrap(), kind: ChangeKind::Added(json!("b")) },
+/// ]);
+///
+/// assert!(rustic_json::diff(&left, &left.clone()).is_empty());
+/// ```
+///
+/// It is displayed as a (colourless) unified-style report,
+/// with values pretty-printed:
+///
+/// ```
+/// use rustic_json::{diff, json};
+///
+/// let left = json!({"user": {"name": "Alice", "roles": ["admin"]}, "version": 1});
+/// let right = json!({"user": {"name": "Alice", "roles": []}, "version": 2});
+/// assert_eq!(diff(&left, &right).to_string(), r#"--- left
+/// +++ right
+/// @@ /user/roles/0 @@
+/// -"admin"
+/// @@ /version @@
+/// -1
+/// +2
+/// "#);
+///
+/// assert_eq!(diff(&json!([]), &json!([{"a": [true]}])).to_string(), r#"--- left
+/// +++ right
+/// @@ /0 @@
+/// +{
+/// +    "a": [
+/// +        true
+/// +    ]
+/// +}
+/// "#);
+///
+/// assert_eq!(diff(&left, &left).to_string(), "");
+/// ```
+#[must_use]
+pub fn diff(left: &Value, right: &Value) -> Diff {
+    let mut changes = Vec::new();
+    helper::diff_into(&mut changes, &mut JsonPointer::root(), left, right);
+    Diff { changes }
+}
+
+/// Result of [`diff`].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Diff {
+    /// In document order.
+    pub changes: Vec<Change>,
+}
+
+impl Diff {
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Display for Diff {
+    /// Formats as a unified-style report (nothing if there are no changes).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "--- left")?;
+        writeln!(f, "+++ right")?;
+        for change in &self.changes {
+            write!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// A difference at a given location, from [`diff`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Change {
+    /// Location of the value (in `left` if removed or changed, in `right` if added).
+    pub pointer: JsonPointer,
+    pub kind: ChangeKind,
+}
+
+impl Display for Change {
+    /// Formats as a unified-style hunk.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "@@ {} @@", self.pointer)?;
+        let write_lines = |f: &mut fmt::Formatter<'_>, prefix: char, value: &Value| {
+            format!("{:#}", value)
+                .lines()
+                .try_for_each(|line| writeln!(f, "{}{}", prefix, line))
+        };
+        match &self.kind {
+            ChangeKind::Added(value) => write_lines(f, '+', value),
+            ChangeKind::Removed(value) => write_lines(f, '-', value),
+            ChangeKind::Changed { old, new } => {
+                write_lines(f, '-', old)?;
+                write_lines(f, '+', new)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ChangeKind {
+    /// Only in `right`, with its value.
+    Added(Value),
+    /// Only in `left`, with its value.
+    Removed(Value),
+    /// Different values (of different types, or scalars).
+    Changed { old: Value, new: Value },
+}
+
+/// Asserts that two [`Value`]s are equal, panicking with their [`diff`] otherwise.
+///
+/// Like [`assert_eq!`], it accepts an optional custom message.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::{assert_json_eq, json};
+///
+/// assert_json_eq!(json!({"a": [1, 2]}), "{\"a\": [1.0, 2e0]}".parse().unwrap());
+/// ```
+///
+/// ```should_panic
+/// use rustic_json::{assert_json_eq, json};
+///
+/// assert_json_eq!(json!({"a": [1, 2]}), json!({"a": [1, 3]}), "for {}", "case 1");
+/// ```
+#[macro_export]
+macro_rules! assert_json_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let diff = $crate::diff(left, right);
+                if !diff.is_empty() {
+                    panic!("assertion failed: `(left == right)` (JSON)\n{}", diff);
+                }
+            }
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let diff = $crate::diff(left, right);
+                if !diff.is_empty() {
+                    panic!(
+                        "assertion failed: `(left == right)` (JSON): {}\n{}",
+                        format_args!($($arg)+),
+                        diff
+                    );
+                }
+            }
+        }
+    };
+}
+
+mod helper {
+    use super::*;
+    use std::cmp::Ordering;
+
+    pub(super) fn diff_into(
+        changes: &mut Vec<Change>,
+        pointer: &mut JsonPointer,
+        left: &Value,
+        right: &Value,
+    ) {
+        match (left, right) {
+            (Value::Array(left), Value::Array(right)) => {
+                for (i, (left, right)) in left.iter().zip(right).enumerate() {
+                    pointer.push_index(i);
+                    diff_into(changes, pointer, left, right);
+                    pointer.pop();
+                }
+                for (i, value) in left.iter().enumerate().skip(right.len()) {
+                    push(changes, pointer.join(i), ChangeKind::Removed(value.clone()));
+                }
+                for (i, value) in right.iter().enumerate().skip(left.len()) {
+                    push(changes, pointer.join(i), ChangeKind::Added(value.clone()));
+                }
+            }
+            (Value::Object(left), Value::Object(right)) => {
+                let mut left = left.iter().peekable();
+                let mut right = right.iter().peekable();
+                loop {
+                    // merge of the (sorted) keys
+                    let order = match (left.peek(), right.peek()) {
+                        (None, None) => break,
+                        (Some(_), None) => Ordering::Less,
+                        (None, Some(_)) => Ordering::Greater,
+                        (Some((left_key, _)), Some((right_key, _))) => left_key.cmp(right_key),
+                    };
+                    match order {
+                        Ordering::Less => {
+                            let (key, value) = left.next().expect("peeked");
+                            let kind = ChangeKind::Removed(value.clone());
+                            push(changes, pointer.join(&**key), kind);
+                        }
+                        Ordering::Greater => {
+                            let (key, value) = right.next().expect("peeked");
+                            let kind = ChangeKind::Added(value.clone());
+                            push(changes, pointer.join(&**key), kind);
+                        }
+                        Ordering::Equal => {
+                            let ((key, left), (_, right)) =
+                                (left.next().expect("peeked"), right.next().expect("peeked"));
+                            pointer.push_key(key);
+                            diff_into(changes, pointer, left, right);
+                            pointer.pop();
+                        }
+                    }
+                }
+            }
+            _ if left != right => {
+                let kind = ChangeKind::Changed {
+                    old: left.clone(),
+                    new: right.clone(),
+                };
+                push(changes, pointer.clone(), kind);
+            }
+            _ => {}
+        }
+    }
+
+    fn push(changes: &mut Vec<Change>, pointer: JsonPointer, kind: ChangeKind) {
+        changes.push(Change { pointer, kind });
+    }
+}
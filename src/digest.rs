@@ -0,0 +1,127 @@
+use super::*;
+use helper::{Sha256, BLOCK_LEN};
+
+impl Value {
+    /// SHA-256 hash of the [canonical form](Value::to_canonical_string),
+    /// so that equal values have the same digest however they were formatted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::{sha256, Value};
+    ///
+    /// let a: Value = r#"{"b": [1.0, "é"], "a": null}"#.parse().unwrap();
+    /// let b: Value = r#"{"a":null,"b":[1,"é"]}"#.parse().unwrap();
+    /// assert_eq!(a.digest(), b.digest());
+    /// assert_eq!(a.digest(), sha256(r#"{"a":null,"b":[1,"é"]}"#.as_bytes()));
+    /// ```
+    #[must_use]
+    pub fn digest(&self) -> [u8; 32] {
+        sha256(self.to_canonical_string().as_bytes())
+    }
+
+    /// HMAC-SHA256 of the [canonical form](Value::to_canonical_string) with the given key,
+    /// as a detached signature (see [`Value::verify`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::{hmac_sha256, json};
+    ///
+    /// let payload = json!({"event": "push", "id": 42});
+    /// let signature = payload.sign(b"secret");
+    /// assert!(payload.verify(b"secret", &signature));
+    /// assert_eq!(signature, hmac_sha256(b"secret", br#"{"event":"push","id":42}"#));
+    ///
+    /// assert!(!payload.verify(b"other secret", &signature));
+    /// assert!(!json!({"event": "push", "id": 43}).verify(b"secret", &signature));
+    /// ```
+    #[must_use]
+    pub fn sign(&self, key: &[u8]) -> [u8; 32] {
+        hmac_sha256(key, self.to_canonical_string().as_bytes())
+    }
+
+    /// Whether the signature is the one from [`Value::sign`] with the given key
+    /// (compared in constant time, to not leak how much of it matches).
+    #[must_use]
+    pub fn verify(&self, key: &[u8], signature: &[u8]) -> bool {
+        let expected = self.sign(key);
+        signature.len() == expected.len()
+            && signature
+                .iter()
+                .zip(expected)
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+/// SHA-256 hash (FIPS 180-4) of the bytes.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::sha256;
+///
+/// let hex = |bytes: [u8; 32]| bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+/// assert_eq!(hex(sha256(b"")), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+/// assert_eq!(hex(sha256(b"abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+/// assert_eq!(
+///     hex(sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+///     "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+/// );
+/// assert_eq!(
+///     hex(sha256(&[b'a'; 1_000_000])),
+///     "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+/// );
+/// ```
+#[must_use]
+pub fn sha256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hasher.finish()
+}
+
+/// HMAC (RFC 2104) with SHA-256 of the message with the key.
+///
+/// # Examples
+///
+/// From RFC 4231:
+///
+/// ```
+/// use rustic_json::hmac_sha256;
+///
+/// let hex = |bytes: [u8; 32]| bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+/// assert_eq!(
+///     hex(hmac_sha256(&[0x0b; 20], b"Hi There")),
+///     "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+/// );
+/// assert_eq!(
+///     hex(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+///     "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+/// );
+/// // key longer than a block
+/// assert_eq!(
+///     hex(hmac_sha256(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+///     "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+/// );
+/// ```
+#[must_use]
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const IPAD: u8 = 0x36;
+    const OPAD: u8 = 0x5c;
+    let mut block = [0; BLOCK_LEN];
+    if key.len() > BLOCK_LEN {
+        block[..32].copy_from_slice(&sha256(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(&block.map(|byte| byte ^ IPAD));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(&block.map(|byte| byte ^ OPAD));
+    outer.update(&inner.finish());
+    outer.finish()
+}
+
+mod helper;
@@ -0,0 +1,185 @@
+//! SHA-256 (FIPS 180-4).
+
+pub(super) const BLOCK_LEN: usize = 64;
+
+const INITIAL_STATE: [u32; 8] = [
+    0x6a09_e667,
+    0xbb67_ae85,
+    0x3c6e_f372,
+    0xa54f_f53a,
+    0x510e_527f,
+    0x9b05_688c,
+    0x1f83_d9ab,
+    0x5be0_cd19,
+];
+
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a_2f98,
+    0x7137_4491,
+    0xb5c0_fbcf,
+    0xe9b5_dba5,
+    0x3956_c25b,
+    0x59f1_11f1,
+    0x923f_82a4,
+    0xab1c_5ed5,
+    0xd807_aa98,
+    0x1283_5b01,
+    0x2431_85be,
+    0x550c_7dc3,
+    0x72be_5d74,
+    0x80de_b1fe,
+    0x9bdc_06a7,
+    0xc19b_f174,
+    0xe49b_69c1,
+    0xefbe_4786,
+    0x0fc1_9dc6,
+    0x240c_a1cc,
+    0x2de9_2c6f,
+    0x4a74_84aa,
+    0x5cb0_a9dc,
+    0x76f9_88da,
+    0x983e_5152,
+    0xa831_c66d,
+    0xb003_27c8,
+    0xbf59_7fc7,
+    0xc6e0_0bf3,
+    0xd5a7_9147,
+    0x06ca_6351,
+    0x1429_2967,
+    0x27b7_0a85,
+    0x2e1b_2138,
+    0x4d2c_6dfc,
+    0x5338_0d13,
+    0x650a_7354,
+    0x766a_0abb,
+    0x81c2_c92e,
+    0x9272_2c85,
+    0xa2bf_e8a1,
+    0xa81a_664b,
+    0xc24b_8b70,
+    0xc76c_51a3,
+    0xd192_e819,
+    0xd699_0624,
+    0xf40e_3585,
+    0x106a_a070,
+    0x19a4_c116,
+    0x1e37_6c08,
+    0x2748_774c,
+    0x34b0_bcb5,
+    0x391c_0cb3,
+    0x4ed8_aa4a,
+    0x5b9c_ca4f,
+    0x682e_6ff3,
+    0x748f_82ee,
+    0x78a5_636f,
+    0x84c8_7814,
+    0x8cc7_0208,
+    0x90be_fffa,
+    0xa450_6ceb,
+    0xbef9_a3f7,
+    0xc671_78f2,
+];
+
+/// Incremental hasher.
+pub(super) struct Sha256 {
+    state: [u32; 8],
+    /// Pending bytes (less than a block).
+    buffer: [u8; BLOCK_LEN],
+    buffered: usize,
+    /// Total number of bytes hashed.
+    len: u64,
+}
+
+impl Sha256 {
+    pub(super) fn new() -> Self {
+        Self {
+            state: INITIAL_STATE,
+            buffer: [0; BLOCK_LEN],
+            buffered: 0,
+            len: 0,
+        }
+    }
+
+    pub(super) fn update(&mut self, mut bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+        if self.buffered > 0 {
+            let taken = bytes.len().min(BLOCK_LEN - self.buffered);
+            self.buffer[self.buffered..self.buffered + taken].copy_from_slice(&bytes[..taken]);
+            self.buffered += taken;
+            bytes = &bytes[taken..];
+            if self.buffered < BLOCK_LEN {
+                return;
+            }
+            let block = self.buffer;
+            self.compress(&block);
+            self.buffered = 0;
+        }
+        let mut blocks = bytes.chunks_exact(BLOCK_LEN);
+        for block in &mut blocks {
+            self.compress(block.try_into().expect("a block"));
+        }
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    pub(super) fn finish(mut self) -> [u8; 32] {
+        // 0x80, zeros, then the length in bits (big-endian), up to a multiple of the block length
+        let bit_len = self.len * 8;
+        let padding_len = (BLOCK_LEN - (self.buffered + 1 + 8) % BLOCK_LEN) % BLOCK_LEN;
+        let mut padding = [0; BLOCK_LEN + 1 + 8];
+        padding[0] = 0x80;
+        padding[1 + padding_len..1 + padding_len + 8].copy_from_slice(&bit_len.to_be_bytes());
+        self.update(&padding[..1 + padding_len + 8]);
+        debug_assert_eq!(self.buffered, 0);
+        let mut hash = [0; 32];
+        for (bytes, word) in hash.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        hash
+    }
+
+    fn compress(&mut self, block: &[u8; BLOCK_LEN]) {
+        let mut schedule = [0_u32; 64];
+        for (word, bytes) in schedule.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().expect("4 bytes"));
+        }
+        for t in 16..64 {
+            let s0 = schedule[t - 15].rotate_right(7)
+                ^ schedule[t - 15].rotate_right(18)
+                ^ schedule[t - 15] >> 3;
+            let s1 = schedule[t - 2].rotate_right(17)
+                ^ schedule[t - 2].rotate_right(19)
+                ^ schedule[t - 2] >> 10;
+            schedule[t] = schedule[t - 16]
+                .wrapping_add(s0)
+                .wrapping_add(schedule[t - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for (&k, &w) in ROUND_CONSTANTS.iter().zip(&schedule) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(choice)
+                .wrapping_add(k)
+                .wrapping_add(w);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(majority);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word, value) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use super::*;
+use pointer::parse_index;
+
+impl Value {
+    /// Converts the value identified by a JSON Pointer (in string form),
+    /// reporting errors at their full path.
+    ///
+    /// A missing value (including through a missing or `null` parent)
+    /// is converted from `null` if possible (e.g. `Option`),
+    /// otherwise it is a [`FromJsonErrorKind::MissingField`] error.
+    ///
+    /// # Errors
+    ///
+    /// If the pointer is invalid, if a parent is neither an object nor an array,
+    /// or if the conversion fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::FromJsonErrorKind;
+    ///
+    /// let value = json!({"config": {"port": "8080", "hosts": ["ab", "c"]}});
+    /// assert_eq!(value.extract::<String>("/config/hosts/1"), Ok("c".into()));
+    /// assert_eq!(value.extract::<Option<f64>>("/config/timeout"), Ok(None));
+    /// assert_eq!(value.extract::<Option<f64>>("/missing/timeout"), Ok(None));
+    ///
+    /// let error = value.extract::<u16>("/config/port").unwrap_err();
+    /// assert_eq!(error.to_string(), r#"expected number at "/config/port", found string"#);
+    /// let error = value.extract::<u16>("/config/hosts/port").unwrap_err();
+    /// assert_eq!(error.to_string(), r#"missing field "port" at "/config/hosts""#);
+    /// let error = value.extract::<u16>("/config/port/number").unwrap_err();
+    /// assert_eq!(error.to_string(), r#"expected object at "/config/port", found string"#);
+    /// let error = value.extract::<Vec<char>>("/config/hosts").unwrap_err();
+    /// assert_eq!(error.path.as_str(), "/config/hosts/0");
+    /// assert_eq!(error.kind, FromJsonErrorKind::InvalidValue);
+    /// ```
+    pub fn extract<T: FromJson>(&self, pointer: &str) -> Result<T, FromJsonError> {
+        let pointer: JsonPointer = pointer.parse().map_err(|error: InvalidJsonPointer| {
+            FromJsonError::new(FromJsonErrorKind::Custom(error.to_string()))
+        })?;
+        let mut path = JsonPointer::root();
+        let mut value = Some(self);
+        for token in pointer.tokens() {
+            value = match value {
+                Some(Self::Object(obj)) => obj.get(token.as_str()),
+                Some(Self::Array(arr)) => parse_index(&token).and_then(|index| arr.get(index)),
+                Some(Self::Null) | None => None,
+                Some(other) => {
+                    return Err(prefixed(
+                        FromJsonError::invalid_type("object", other),
+                        &path,
+                    ))
+                }
+            };
+            if value.is_none() {
+                return T::from_json(&Self::Null).map_err(|_| {
+                    prefixed(
+                        FromJsonError::new(FromJsonErrorKind::MissingField(Str::from(token))),
+                        &path,
+                    )
+                });
+            }
+            path.push_key(&token);
+        }
+        T::from_json(value.unwrap_or(&Self::Null)).map_err(|error| prefixed(error, &path))
+    }
+
+    /// Starts reading the fields of an object.
+    ///
+    /// # Errors
+    ///
+    /// If the value is not an object.
+    pub fn fields(&self) -> Result<FieldReader<'_>, FromJsonError> {
+        match self {
+            Self::Object(obj) => Ok(FieldReader {
+                obj,
+                path: JsonPointer::root(),
+            }),
+            other => Err(FromJsonError::invalid_type("object", other)),
+        }
+    }
+}
+
+/// Reader of required and optional object fields,
+/// reporting errors at their full path (see [`Value::fields`]).
+///
+/// Unknown fields are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::FromJsonError;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Request {
+///     user: String,
+///     limit: Option<u32>,
+///     tags: Vec<String>,
+///     port: u16,
+/// }
+///
+/// fn read(value: &rustic_json::Value) -> Result<Request, FromJsonError> {
+///     let fields = value.fields()?;
+///     let config = fields.nested("config")?;
+///     Ok(Request {
+///         user: fields.required("user")?,
+///         limit: fields.optional("limit")?,
+///         tags: fields.optional("tags")?.unwrap_or_default(),
+///         port: config.required("port")?,
+///     })
+/// }
+///
+/// let value = json!({"user": "ann", "limit": null, "config": {"port": 80}});
+/// assert_eq!(
+///     read(&value),
+///     Ok(Request { user: "ann".into(), limit: None, tags: vec![], port: 80 })
+/// );
+///
+/// let value = json!({"user": "ann", "config": {"port": "80"}});
+/// let error = read(&value).unwrap_err();
+/// assert_eq!(error.to_string(), r#"expected number at "/config/port", found string"#);
+/// let value = json!({"user": "ann", "config": {}});
+/// let error = read(&value).unwrap_err();
+/// assert_eq!(error.to_string(), r#"missing field "port" at "/config""#);
+/// let value = json!({"user": "ann", "tags": ["a", 1], "config": {"port": 80}});
+/// let error = read(&value).unwrap_err();
+/// assert_eq!(error.to_string(), r#"expected string at "/tags/1", found number"#);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FieldReader<'v> {
+    obj: &'v Obj,
+    path: JsonPointer,
+}
+
+impl<'v> FieldReader<'v> {
+    /// Location of the object being read.
+    #[must_use]
+    pub fn path(&self) -> &JsonPointer {
+        &self.path
+    }
+
+    /// Converts a field, which must be present (even if `null`).
+    ///
+    /// # Errors
+    ///
+    /// If the field is missing or its conversion fails.
+    pub fn required<T: FromJson>(&self, key: &str) -> Result<T, FromJsonError> {
+        match self.obj.get(key) {
+            Some(value) => self.convert(key, value),
+            None => Err(prefixed(
+                FromJsonError::new(FromJsonErrorKind::MissingField(Str::from(key.to_owned()))),
+                &self.path,
+            )),
+        }
+    }
+
+    /// Converts a field, if present and not `null`.
+    ///
+    /// # Errors
+    ///
+    /// If the conversion fails.
+    pub fn optional<T: FromJson>(&self, key: &str) -> Result<Option<T>, FromJsonError> {
+        match self.obj.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => self.convert(key, value).map(Some),
+        }
+    }
+
+    /// Starts reading the fields of a nested object, which must be present.
+    ///
+    /// # Errors
+    ///
+    /// If the field is missing or not an object.
+    pub fn nested(&self, key: &str) -> Result<FieldReader<'v>, FromJsonError> {
+        let path = self.path.join(key);
+        match self.obj.get(key) {
+            Some(Value::Object(obj)) => Ok(FieldReader { obj, path }),
+            Some(other) => Err(prefixed(
+                FromJsonError::invalid_type("object", other),
+                &path,
+            )),
+            None => Err(prefixed(
+                FromJsonError::new(FromJsonErrorKind::MissingField(Str::from(key.to_owned()))),
+                &self.path,
+            )),
+        }
+    }
+
+    fn convert<T: FromJson>(&self, key: &str, value: &Value) -> Result<T, FromJsonError> {
+        T::from_json(value).map_err(|error| prefixed(error.within(key), &self.path))
+    }
+}
+
+/// Moves the error under the given path.
+fn prefixed(mut error: FromJsonError, path: &JsonPointer) -> FromJsonError {
+    let mut full = path.clone();
+    full.append(&error.path);
+    error.path = full;
+    error
+}
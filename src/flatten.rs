@@ -0,0 +1,245 @@
+use super::*;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// Flattens a value into a map from dotted paths (like `a.b[0].c`) to scalars,
+/// e.g. for key-value stores (see [`flatten_with`] for other separators).
+///
+/// Empty arrays and objects are kept as values, so that [`unflatten`]
+/// rebuilds any array or object (except with an empty key at the top level,
+/// which could be confused with the value itself). Anything else is at the empty path.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::{flatten, json, unflatten, Value};
+///
+/// let value = json!({"db": {"hosts": ["a", "b"], "port": 5432, "opts": {}}, "a.b": [[true]]});
+/// let flat = flatten(&value);
+/// assert_eq!(flat, [
+///     (String::from(r"a\.b[0][0]"), json!(true)),
+///     (String::from("db.hosts[0]"), json!("a")),
+///     (String::from("db.hosts[1]"), json!("b")),
+///     (String::from("db.opts"), json!({})),
+///     (String::from("db.port"), json!(5432)),
+/// ].into());
+/// assert_eq!(unflatten(&flat), Ok(value));
+///
+/// assert_eq!(flatten(&json!(null)), [(String::new(), Value::Null)].into());
+/// ```
+#[must_use]
+pub fn flatten(value: &Value) -> BTreeMap<String, Value> {
+    flatten_with(value, &FlattenOptions::default())
+}
+
+/// Flattens a value into a map from paths to scalars (see [`flatten`]), with the given options.
+///
+/// # Panics
+///
+/// If the separator is invalid (see [`FlattenOptions::separator`]).
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::{flatten_with, json, unflatten_with, FlattenOptions};
+///
+/// // e.g. for environment variables
+/// let options = FlattenOptions { separator: "__".into(), bracket_indexes: false };
+/// let value = json!({"db": {"hosts": ["a", "b"], "max__size": 10}, "2024": true});
+/// let flat = flatten_with(&value, &options);
+/// assert_eq!(flat, [
+///     (String::from(r"\2024"), json!(true)),
+///     (String::from("db__hosts__0"), json!("a")),
+///     (String::from("db__hosts__1"), json!("b")),
+///     (String::from(r"db__max\__size"), json!(10)),
+/// ].into());
+/// assert_eq!(unflatten_with(&flat, &options), Ok(value));
+/// ```
+#[must_use]
+pub fn flatten_with(value: &Value, options: &FlattenOptions) -> BTreeMap<String, Value> {
+    assert!(helper::is_valid_separator(options), "invalid separator");
+    let mut map = BTreeMap::new();
+    if value != &Value::Object(Obj::new()) {
+        helper::flatten_into(&mut map, value, options);
+    }
+    map
+}
+
+/// Rebuilds a value from a map produced by [`flatten`]
+/// (so from dotted paths like `a.b[0].c`), creating arrays and objects as needed.
+///
+/// # Errors
+///
+/// If a path is invalid, conflicts with another
+/// (e.g. `a` and `a.b`, or `a.b` and `a[0]`), or if an array has a missing index.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::{json, unflatten, UnflattenError, UnflattenErrorKind, Value};
+/// use std::collections::BTreeMap;
+///
+/// let flat = BTreeMap::from([
+///     (String::from("servers[1].port"), json!(8081)),
+///     (String::from("servers[0].port"), json!(8080)),
+///     (String::from("name"), json!("app")),
+/// ]);
+/// assert_eq!(unflatten(&flat), Ok(json!({"name": "app", "servers": [{"port": 8080}, {"port": 8081}]})));
+///
+/// let flat = BTreeMap::from([(String::from("a"), json!(1)), (String::from("a.b"), json!(2))]);
+/// let error = unflatten(&flat).unwrap_err();
+/// assert_eq!(error, UnflattenError { kind: UnflattenErrorKind::Conflict, key: String::from("a.b") });
+/// assert_eq!(error.to_string(), r#"conflicting path at key "a.b""#);
+///
+/// let flat = BTreeMap::from([(String::from("a[1]"), json!(1))]);
+/// assert_eq!(unflatten(&flat).unwrap_err().kind, UnflattenErrorKind::MissingIndex(0));
+///
+/// let flat = BTreeMap::from([(String::from("a[x]"), json!(1))]);
+/// assert_eq!(unflatten(&flat).unwrap_err().kind, UnflattenErrorKind::InvalidKey);
+/// ```
+pub fn unflatten(map: &BTreeMap<String, Value>) -> Result<Value, UnflattenError> {
+    unflatten_with(map, &FlattenOptions::default())
+}
+
+/// Rebuilds a value from a map produced by [`flatten_with`], with the same options.
+///
+/// Without bracketed indexes, containers whose paths all continue with indexes
+/// (`0`, `1`, ... in decimal, unescaped) are rebuilt as arrays.
+///
+/// # Errors
+///
+/// As [`unflatten`], and if the separator is invalid (see [`FlattenOptions::separator`]).
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::{json, unflatten_with, FlattenOptions, UnflattenErrorKind};
+/// use std::collections::BTreeMap;
+///
+/// let flat = BTreeMap::from([(String::from("a_0"), json!(1))]);
+/// let options = FlattenOptions { separator: "_".into(), bracket_indexes: false };
+/// assert_eq!(unflatten_with(&flat, &options), Ok(json!({"a": [1]})));
+///
+/// // a separator that could not be told apart from (escaped) keys or indexes
+/// for (separator, bracket_indexes) in [("", true), (r"\", true), ("][", true), ("_0", false)] {
+///     let options = FlattenOptions { separator: separator.into(), bracket_indexes };
+///     let error = unflatten_with(&flat, &options).unwrap_err();
+///     assert_eq!(error.kind, UnflattenErrorKind::InvalidSeparator);
+/// }
+/// ```
+///
+/// Paths can be arbitrarily deep (both ways):
+///
+/// ```
+/// use rustic_json::{flatten, unflatten, Value};
+/// use std::collections::BTreeMap;
+///
+/// let flat = BTreeMap::from([(vec!["a"; 100_000].join("."), Value::Null)]);
+/// let mut value = unflatten(&flat).unwrap();
+/// assert_eq!(flatten(&value), flat);
+/// let mut depth = 0;
+/// while let Value::Object(mut obj) = value {
+///     value = obj.remove("a").unwrap();
+///     depth += 1;
+/// }
+/// assert_eq!(depth, 100_000);
+///
+/// // (also when failing)
+/// let flat = BTreeMap::from([(vec!["a"; 10_000].join("."), Value::Null), (String::from("b[1]"), Value::Null)]);
+/// assert!(unflatten(&flat).is_err());
+/// ```
+pub fn unflatten_with(
+    map: &BTreeMap<String, Value>,
+    options: &FlattenOptions,
+) -> Result<Value, UnflattenError> {
+    if !helper::is_valid_separator(options) {
+        return Err(UnflattenError {
+            kind: K::InvalidSeparator,
+            key: String::new(),
+        });
+    }
+    let mut root = helper::Node::Object(BTreeMap::new());
+    for (key, value) in map {
+        let error = |kind| UnflattenError {
+            kind,
+            key: key.clone(),
+        };
+        let segments = helper::parse_path(key, options).ok_or_else(|| error(K::InvalidKey))?;
+        root.insert(segments, value.clone())
+            .ok_or_else(|| error(K::Conflict))?;
+    }
+    root.into_value()
+        .map_err(|(kind, segments)| UnflattenError {
+            kind,
+            key: helper::format_path(&segments, options),
+        })
+}
+
+/// Options for [`flatten_with`] and [`unflatten_with`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FlattenOptions {
+    /// Between keys (and indexes, unless in brackets).
+    /// Occurrences in keys are escaped with a backslash before their first character
+    /// (as are backslashes).
+    ///
+    /// It must be non-empty, without a backslash, and without `[` with bracketed indexes
+    /// or without ASCII digits otherwise.
+    pub separator: Str,
+    /// Array indexes in brackets, like `a[0]` (with `[` escaped in keys),
+    /// otherwise separated like keys, like `a.0` (with keys made of digits escaped, like `a.\0`).
+    pub bracket_indexes: bool,
+}
+
+impl Default for FlattenOptions {
+    /// Dotted paths, like `a.b[0].c`.
+    fn default() -> Self {
+        Self {
+            separator: Str::from("."),
+            bracket_indexes: true,
+        }
+    }
+}
+
+/// Error from [`unflatten`] or [`unflatten_with`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UnflattenError {
+    pub kind: UnflattenErrorKind,
+    /// The offending path (for a missing index, of the array; empty for an invalid separator).
+    pub key: String,
+}
+
+impl Display for UnflattenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at key {:?}", self.kind, self.key)
+    }
+}
+
+impl Error for UnflattenError {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum UnflattenErrorKind {
+    /// Malformed path (e.g. an invalid escape or index).
+    InvalidKey,
+    /// A path within a scalar, or an index within an object (or a key within an array).
+    Conflict,
+    /// With the first missing index of the array.
+    MissingIndex(usize),
+    /// See [`FlattenOptions::separator`].
+    InvalidSeparator,
+}
+
+impl Display for UnflattenErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKey => f.write_str("invalid path"),
+            Self::Conflict => f.write_str("conflicting path"),
+            Self::MissingIndex(index) => write!(f, "missing index {}", index),
+            Self::InvalidSeparator => f.write_str("invalid separator"),
+        }
+    }
+}
+
+use UnflattenErrorKind as K;
+
+mod helper;
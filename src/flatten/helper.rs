@@ -0,0 +1,317 @@
+use super::*;
+use crate::visit::helper::{Children, Token};
+use std::collections::btree_map;
+
+pub(super) enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// See [`FlattenOptions::separator`].
+pub(super) fn is_valid_separator(options: &FlattenOptions) -> bool {
+    let separator = &*options.separator;
+    let is_reserved = |c: char| match options.bracket_indexes {
+        true => c == '\\' || c == '[',
+        false => c == '\\' || c.is_ascii_digit(),
+    };
+    !separator.is_empty() && !separator.contains(is_reserved)
+}
+
+/// Without recursion (for deeply nested values), like [`Value::visit`].
+pub(super) fn flatten_into(
+    map: &mut BTreeMap<String, Value>,
+    value: &Value,
+    options: &FlattenOptions,
+) {
+    let mut path = String::new();
+    // the remaining children of each container being flattened, with the length of its path
+    let mut stack = Vec::new();
+    let mut next = Some(value);
+    loop {
+        if let Some(value) = next.take() {
+            let is_leaf = match value {
+                Value::Array(arr) => arr.is_empty(),
+                Value::Object(obj) => obj.is_empty(),
+                _ => true,
+            };
+            if is_leaf {
+                map.insert(path.clone(), value.clone());
+            } else {
+                stack.extend(Children::of(value).map(|children| (children, path.len())));
+            }
+        }
+        let is_first = stack.len() == 1;
+        let Some((children, len)) = stack.last_mut() else {
+            return;
+        };
+        path.truncate(*len);
+        match children.next() {
+            Some((token, child)) => {
+                let segment = match token {
+                    Token::Index(index) => Segment::Index(index),
+                    Token::Key(key) => Segment::Key(String::from(key)),
+                };
+                push_segment(&mut path, is_first, &segment, options);
+                next = Some(child);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+}
+
+/// Appends a segment to a path.
+fn push_segment(path: &mut String, is_first: bool, segment: &Segment, options: &FlattenOptions) {
+    let separator = &*options.separator;
+    match segment {
+        Segment::Index(index) if options.bracket_indexes => {
+            path.push_str(&format!("[{}]", index));
+        }
+        Segment::Index(index) => {
+            if !is_first {
+                path.push_str(separator);
+            }
+            path.push_str(&index.to_string());
+        }
+        Segment::Key(key) => {
+            if !is_first {
+                path.push_str(separator);
+            }
+            if !options.bracket_indexes
+                && !key.is_empty()
+                && key.bytes().all(|b| b.is_ascii_digit())
+            {
+                path.push('\\');
+            }
+            // escaping any character from which a separator would be read
+            // (even overlapping the next one, like `a_` before `__`)
+            for (i, c) in key.char_indices() {
+                let rest = &key[i..];
+                // (i.e. `rest` followed by a separator starts with one)
+                let is_separator = rest.starts_with(separator)
+                    || separator
+                        .strip_prefix(rest)
+                        .is_some_and(|end| separator.starts_with(end));
+                if is_separator || c == '\\' || (c == '[' && options.bracket_indexes) {
+                    path.push('\\');
+                }
+                path.push(c);
+            }
+        }
+    }
+}
+
+pub(super) fn format_path(segments: &[Segment], options: &FlattenOptions) -> String {
+    let mut path = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        push_segment(&mut path, i == 0, segment, options);
+    }
+    path
+}
+
+/// `None` if invalid.
+pub(super) fn parse_path(path: &str, options: &FlattenOptions) -> Option<Vec<Segment>> {
+    let separator = &*options.separator;
+    let mut segments = Vec::new();
+    // the key being read (none right after a bracketed index), and whether it had escapes
+    let mut key = None;
+    let mut is_escaped = false;
+    let finish_key = |segments: &mut Vec<Segment>, key: String, is_escaped: bool| {
+        segments.push(match key.parse() {
+            Ok(index) if !options.bracket_indexes && !is_escaped && is_canonical(&key) => {
+                Segment::Index(index)
+            }
+            _ => Segment::Key(key),
+        });
+    };
+    if path.is_empty() {
+        // the root itself
+        return Some(segments);
+    }
+    let mut rest = path;
+    if !(options.bracket_indexes && rest.starts_with('[')) {
+        key = Some(String::new());
+    }
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with(separator) {
+            if let Some(key) = key.take() {
+                finish_key(&mut segments, key, is_escaped);
+            }
+            key = Some(String::new());
+            is_escaped = false;
+            rest = &rest[separator.len()..];
+        } else if c == '[' && options.bracket_indexes {
+            if let Some(key) = key.take() {
+                finish_key(&mut segments, key, is_escaped);
+            }
+            let (index, after) = rest[1..].split_once(']')?;
+            if !is_canonical(index) {
+                return None;
+            }
+            segments.push(Segment::Index(index.parse().ok()?));
+            rest = after;
+        } else if c == '\\' {
+            let c = rest[1..].chars().next()?;
+            key.as_mut()?.push(c);
+            rest = &rest[1 + c.len_utf8()..];
+            is_escaped = true;
+        } else {
+            key.as_mut()?.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if let Some(key) = key {
+        finish_key(&mut segments, key, is_escaped);
+    }
+    Some(segments)
+}
+
+/// Decimal digits without leading zeros.
+fn is_canonical(index: &str) -> bool {
+    !index.is_empty()
+        && index.bytes().all(|b| b.is_ascii_digit())
+        && (index == "0" || !index.starts_with('0'))
+}
+
+/// Value being rebuilt.
+pub(super) enum Node {
+    Leaf(Value),
+    Object(BTreeMap<String, Node>),
+    Array(BTreeMap<usize, Node>),
+}
+
+impl Node {
+    /// `None` on a conflict.
+    pub(super) fn insert(&mut self, segments: Vec<Segment>, value: Value) -> Option<()> {
+        let mut node = self;
+        let mut segments = segments.into_iter().peekable();
+        if segments.peek().is_none() {
+            // the root itself (e.g. a scalar)
+            return match node {
+                Self::Object(obj) if obj.is_empty() => {
+                    *node = Self::Leaf(value);
+                    Some(())
+                }
+                _ => None,
+            };
+        }
+        // (the root is an array if its first segments are indexes)
+        if let (Self::Object(obj), Some(Segment::Index(_))) = (&*node, segments.peek()) {
+            if obj.is_empty() {
+                *node = Self::Array(BTreeMap::new());
+            }
+        }
+        while let Some(segment) = segments.next() {
+            let child = match segments.peek() {
+                None => Self::Leaf(Value::Null),
+                Some(Segment::Key(_)) => Self::Object(BTreeMap::new()),
+                Some(Segment::Index(_)) => Self::Array(BTreeMap::new()),
+            };
+            let is_leaf = matches!(child, Self::Leaf(_));
+            node = match (node, segment) {
+                (Self::Object(obj), Segment::Key(key)) => match obj.entry(key) {
+                    btree_map::Entry::Vacant(entry) => entry.insert(child),
+                    btree_map::Entry::Occupied(entry) if !is_leaf => entry.into_mut(),
+                    btree_map::Entry::Occupied(_) => return None,
+                },
+                (Self::Array(arr), Segment::Index(index)) => match arr.entry(index) {
+                    btree_map::Entry::Vacant(entry) => entry.insert(child),
+                    btree_map::Entry::Occupied(entry) if !is_leaf => entry.into_mut(),
+                    btree_map::Entry::Occupied(_) => return None,
+                },
+                _ => return None,
+            };
+            // an existing node must be a container of the same kind
+            match (&*node, segments.peek()) {
+                (Self::Leaf(_), Some(_)) => return None,
+                (Self::Object(_), Some(Segment::Index(_)))
+                | (Self::Array(_), Some(Segment::Key(_))) => return None,
+                _ => {}
+            }
+        }
+        *node = Self::Leaf(value);
+        Some(())
+    }
+
+    /// Fails with the first missing index, and the path of its array
+    /// (without recursion, for deeply nested paths).
+    pub(super) fn into_value(mut self) -> Result<Value, (UnflattenErrorKind, Vec<Segment>)> {
+        let mut stack = Vec::new();
+        // the segments to the child being rebuilt in each container of the stack
+        let mut path = Vec::new();
+        let mut finished = self.open(&mut stack);
+        loop {
+            if let Some(value) = finished.take() {
+                match (stack.last_mut(), path.pop()) {
+                    (None, _) => return Ok(value),
+                    (Some(Partial::Object(_, obj)), Some(Segment::Key(key))) => {
+                        obj.insert(Str::from(key), value);
+                    }
+                    (Some(Partial::Array(_, arr)), Some(Segment::Index(_))) => arr.push(value),
+                    _ => unreachable!("segment of the parent's kind"),
+                }
+            }
+            let next = match stack.last_mut().expect("container being rebuilt") {
+                Partial::Object(children, _) => {
+                    children.next().map(|(key, node)| (Segment::Key(key), node))
+                }
+                Partial::Array(children, arr) => match children.next() {
+                    Some((index, _)) if index != arr.len() => {
+                        return Err((UnflattenErrorKind::MissingIndex(arr.len()), path));
+                    }
+                    next => next.map(|(index, node)| (Segment::Index(index), node)),
+                },
+            };
+            finished = match next {
+                Some((segment, mut node)) => {
+                    path.push(segment);
+                    node.open(&mut stack)
+                }
+                None => Some(match stack.pop().expect("container being rebuilt") {
+                    Partial::Object(_, obj) => Value::Object(obj),
+                    Partial::Array(_, arr) => Value::Array(arr),
+                }),
+            };
+        }
+    }
+
+    /// Takes the value of a leaf, or pushes a container to be rebuilt with the children.
+    fn open(&mut self, stack: &mut Vec<Partial>) -> Option<Value> {
+        match self {
+            Self::Leaf(value) => return Some(std::mem::replace(value, Value::Null)),
+            Self::Object(obj) => {
+                stack.push(Partial::Object(std::mem::take(obj).into_iter(), Obj::new()));
+            }
+            Self::Array(arr) => {
+                stack.push(Partial::Array(std::mem::take(arr).into_iter(), Arr::new()));
+            }
+        }
+        None
+    }
+
+    fn take_children(&mut self, into: &mut Vec<Self>) {
+        match self {
+            Self::Leaf(_) => {}
+            Self::Object(obj) => into.extend(std::mem::take(obj).into_values()),
+            Self::Array(arr) => into.extend(std::mem::take(arr).into_values()),
+        }
+    }
+}
+
+impl Drop for Node {
+    /// Without recursion (for deeply nested paths), by emptying the descendants first.
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        self.take_children(&mut stack);
+        while let Some(mut node) = stack.pop() {
+            node.take_children(&mut stack);
+        }
+    }
+}
+
+/// A container being rebuilt, with its remaining children.
+enum Partial {
+    Object(btree_map::IntoIter<String, Node>, Obj),
+    Array(btree_map::IntoIter<usize, Node>, Arr),
+}
@@ -0,0 +1,293 @@
+use super::*;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// Checks that `expected` is structurally included in `actual`, e.g. to test only some fields:
+/// - objects: each key of `expected` is in `actual`, with an including value;
+/// - arrays: each element of `expected` is included in the element of `actual`
+///   at the same index (so `actual` can have more elements at the end),
+///   or in any distinct element if [`IncludeOptions::ignore_array_order`];
+/// - other values: equal (numbers within [`IncludeOptions::numeric_tolerance`]).
+///
+/// # Errors
+///
+/// At the first (in document order) part of `expected` that is not included.
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::{json_include, IncludeError, IncludeErrorKind, IncludeOptions};
+///
+/// let actual = json!({"id": 42, "name": "Alice", "roles": ["admin", "dev"], "score": 0.30000000000000004});
+/// let options = IncludeOptions::default();
+/// assert_eq!(json_include(&actual, &json!({"name": "Alice", "roles": ["admin"]}), &options), Ok(()));
+///
+/// let error = json_include(&actual, &json!({"roles": ["dev"]}), &options).unwrap_err();
+/// assert_eq!(error, IncludeError {
+///     kind: IncludeErrorKind::Mismatch { expected: json!("dev"), actual: json!("admin") },
+///     pointer: "/roles/0".parse().unwrap(),
+/// });
+/// assert_eq!(error.to_string(), r#"expected "dev", got "admin" at "/roles/0""#);
+///
+/// let error = json_include(&actual, &json!({"email": null}), &options).unwrap_err();
+/// assert_eq!(error.kind, IncludeErrorKind::Missing(json!(null)));
+/// assert_eq!(error.to_string(), r#"missing value (expected null) at "/email""#);
+///
+/// let options = IncludeOptions { ignore_array_order: true, numeric_tolerance: 1e-9 };
+/// assert_eq!(json_include(&actual, &json!({"roles": ["dev"], "score": 0.3}), &options), Ok(()));
+/// let error = json_include(&actual, &json!({"roles": ["dev", "dev"]}), &options).unwrap_err();
+/// assert_eq!(error.to_string(), r#"no matching element (expected "dev") at "/roles/1""#);
+///
+/// // when no element includes it, with the closest one (failing the deepest)
+/// let actual = json!([{"id": "a", "spec": {"w": 1}}, {"id": "b", "spec": {"w": 1}}]);
+/// let error = json_include(&actual, &json!([{"id": "b", "spec": {"w": 2}}]), &options).unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     r#"no matching element (expected {"id":"b","spec":{"w":2}}; closest [1]: expected 2, got 1 at "/0/spec/w") at "/0""#
+/// );
+///
+/// // {"a": 1} also matches the first element, but only it can include {"b": 2}
+/// let actual = json!([{"a": 1, "b": 2}, {"a": 1}]);
+/// assert_eq!(json_include(&actual, &json!([{"a": 1}, {"b": 2}]), &options), Ok(()));
+/// ```
+pub fn json_include(
+    actual: &Value,
+    expected: &Value,
+    options: &IncludeOptions,
+) -> Result<(), IncludeError> {
+    helper::include(actual, expected, options, &mut JsonPointer::root())
+}
+
+/// Options for [`json_include`] and [`assert_json_include!`].
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct IncludeOptions {
+    /// Matching each expected array element with any (distinct) actual element,
+    /// otherwise with the one at the same index.
+    pub ignore_array_order: bool,
+    /// Maximum absolute difference between an expected number and the actual one.
+    pub numeric_tolerance: f64,
+}
+
+/// Error from [`json_include`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IncludeError {
+    pub kind: IncludeErrorKind,
+    /// Location in `expected` of the value that is not included.
+    pub pointer: JsonPointer,
+}
+
+impl Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at \"{}\"", self.kind, self.pointer)
+    }
+}
+
+impl Error for IncludeError {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum IncludeErrorKind {
+    /// No such key or index in `actual`, with the expected value.
+    Missing(Value),
+    /// Different scalars, or values of different types.
+    Mismatch { expected: Value, actual: Value },
+    /// No (distinct) element of the `actual` array includes this one, with its value
+    /// (with [`IncludeOptions::ignore_array_order`]), and if none includes it at all,
+    /// the index of the closest one (failing the deepest, the first if several)
+    /// with its failure.
+    Unmatched {
+        expected: Value,
+        closest: Option<(usize, Box<IncludeError>)>,
+    },
+}
+
+impl Display for IncludeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(expected) => write!(f, "missing value (expected {})", expected),
+            Self::Mismatch { expected, actual } => {
+                write!(f, "expected {}, got {}", expected, actual)
+            }
+            Self::Unmatched { expected, closest } => {
+                write!(f, "no matching element (expected {}", expected)?;
+                if let Some((index, error)) = closest {
+                    write!(f, "; closest [{}]: {}", index, error)?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/// Asserts that a [`Value`] structurally includes another (see [`json_include`]),
+/// panicking with the failing path otherwise.
+///
+/// The options default to [`IncludeOptions::default()`].
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::{assert_json_include, json, IncludeOptions};
+///
+/// let response = json!({"id": 42, "items": [{"sku": "a", "qty": 1}, {"sku": "b", "qty": 2.0000001}]});
+/// assert_json_include!(response, json!({"items": [{"sku": "a"}]}));
+///
+/// let options = IncludeOptions { ignore_array_order: true, numeric_tolerance: 1e-6 };
+/// assert_json_include!(response, json!({"items": [{"qty": 2}, {"sku": "a"}]}), options);
+/// ```
+///
+/// ```should_panic
+/// use rustic_json::{assert_json_include, json};
+///
+/// assert_json_include!(json!({"a": [1, 2]}), json!({"a": [1, 3]}));
+/// ```
+#[macro_export]
+macro_rules! assert_json_include {
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::assert_json_include!($actual, $expected, $crate::IncludeOptions::default())
+    };
+    ($actual:expr, $expected:expr, $options:expr $(,)?) => {
+        match (&$actual, &$expected, &$options) {
+            (actual, expected, options) => {
+                if let Err(error) = $crate::json_include(actual, expected, options) {
+                    panic!(
+                        "assertion failed: `actual` includes `expected` (JSON): {}",
+                        error
+                    );
+                }
+            }
+        }
+    };
+}
+
+mod helper {
+    use super::*;
+
+    type IncludeResult = Result<(), IncludeError>;
+
+    pub(super) fn include(
+        actual: &Value,
+        expected: &Value,
+        options: &IncludeOptions,
+        pointer: &mut JsonPointer,
+    ) -> IncludeResult {
+        match (actual, expected) {
+            (Value::Object(actual), Value::Object(expected)) => {
+                for (key, expected) in expected {
+                    pointer.push_key(key);
+                    match actual.get(key) {
+                        Some(actual) => include(actual, expected, options, pointer)?,
+                        None => {
+                            let kind = IncludeErrorKind::Missing(expected.clone());
+                            return Err(error(kind, pointer));
+                        }
+                    }
+                    pointer.pop();
+                }
+                Ok(())
+            }
+            (Value::Array(actual), Value::Array(expected)) if options.ignore_array_order => {
+                include_unordered(actual, expected, options, pointer)
+            }
+            (Value::Array(actual), Value::Array(expected)) => {
+                for (i, expected) in expected.iter().enumerate() {
+                    pointer.push_index(i);
+                    match actual.get(i) {
+                        Some(actual) => include(actual, expected, options, pointer)?,
+                        None => {
+                            let kind = IncludeErrorKind::Missing(expected.clone());
+                            return Err(error(kind, pointer));
+                        }
+                    }
+                    pointer.pop();
+                }
+                Ok(())
+            }
+            (Value::Number(actual), Value::Number(expected))
+                if (actual.get() - expected.get()).abs() <= options.numeric_tolerance =>
+            {
+                Ok(())
+            }
+            _ if actual == expected => Ok(()),
+            _ => {
+                let kind = IncludeErrorKind::Mismatch {
+                    expected: expected.clone(),
+                    actual: actual.clone(),
+                };
+                Err(error(kind, pointer))
+            }
+        }
+    }
+
+    /// Finds distinct including elements for all the expected ones (a bipartite matching,
+    /// by augmenting paths), failing at the first one that cannot have any.
+    fn include_unordered(
+        actual: &[Value],
+        expected: &[Value],
+        options: &IncludeOptions,
+        pointer: &mut JsonPointer,
+    ) -> IncludeResult {
+        // candidates[i]: the actual elements including expected[i],
+        // closest[i]: the one failing the deepest (kept only without candidates)
+        let mut candidates = vec![Vec::new(); expected.len()];
+        let mut closest = vec![None; expected.len()];
+        for (i, expected) in expected.iter().enumerate() {
+            pointer.push_index(i);
+            for (j, actual) in actual.iter().enumerate() {
+                // (failing checks leave their path behind, hence a scratch pointer)
+                match include(actual, expected, options, &mut pointer.clone()) {
+                    Ok(()) => candidates[i].push(j),
+                    Err(error) => {
+                        let depth = error.pointer.tokens().count();
+                        if closest[i].as_ref().is_none_or(|(d, _, _)| depth > *d) {
+                            closest[i] = Some((depth, j, Box::new(error)));
+                        }
+                    }
+                }
+            }
+            pointer.pop();
+        }
+        // matched[j]: the expected element matched to actual[j]
+        let mut matched = vec![None; actual.len()];
+        for (i, expected) in expected.iter().enumerate() {
+            let mut visited = vec![false; actual.len()];
+            if !augment(i, &candidates, &mut matched, &mut visited) {
+                pointer.push_index(i);
+                let kind = IncludeErrorKind::Unmatched {
+                    expected: expected.clone(),
+                    closest: closest[i]
+                        .take()
+                        .filter(|_| candidates[i].is_empty())
+                        .map(|(_, j, error)| (j, error)),
+                };
+                return Err(error(kind, pointer));
+            }
+        }
+        Ok(())
+    }
+
+    fn augment(
+        i: usize,
+        candidates: &[Vec<usize>],
+        matched: &mut [Option<usize>],
+        visited: &mut [bool],
+    ) -> bool {
+        for &j in &candidates[i] {
+            if !visited[j] {
+                visited[j] = true;
+                if matched[j].is_none_or(|other| augment(other, candidates, matched, visited)) {
+                    matched[j] = Some(i);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn error(kind: IncludeErrorKind, pointer: &JsonPointer) -> IncludeError {
+        IncludeError {
+            kind,
+            pointer: pointer.clone(),
+        }
+    }
+}
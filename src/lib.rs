@@ -7,10 +7,31 @@
 //!
 //! The main item is the [`Value`] enum, which can be:
 //! - constructed:
-//!   - by parsing JSON data via [its `FromStr` impl](Value#impl-FromStr-for-Value),
+//!   - by parsing JSON data via [its `FromStr` impl](Value#impl-FromStr-for-Value)
+//!     (or [`Value::parse_recovering`] to collect all errors,
+//!     or [`Value::parse_spanned`] to locate nodes in the source),
 //!   - or manually, optionally via its various \[`Try`\]`From` impls or with the [`json!`] macro;
-//! - modified manually (through pattern matching);
-//! - and formatted into JSON via [its `Display` impl](Value#impl-Display-for-Value).
+//!   - or from Rust values via [`ToJson`] (and back via [`FromJson`]),
+//!     or via `serde` with the optional `serde` feature (`to_value`/`from_value`, `to_string`/`from_str`);
+//! - read through [typed accessors](Value::as_str) and indexing (by key or index),
+//!   or navigated with a [`JsonPointer`] (or [walked](Value::walk) through,
+//!   or traversed by a [visitor](Visit)),
+//!   or converted [at a path](Value::extract) or [field by field](Value::fields)
+//!   with path-aware errors;
+//! - validated against a JSON [`Schema`];
+//! - compared structurally with [`diff`] or [`json_include`]
+//!   (e.g. in tests with [`assert_json_eq!`] or [`assert_json_include!`]);
+//! - modified manually (through pattern matching), or [deep-merged](Value::deep_merge);
+//! - [flattened](flatten) into a map from paths like `a.b[0].c` (and [back](unflatten));
+//! - and formatted into JSON via [its `Display` impl](Value#impl-Display-for-Value)
+//!   (or [with options](Value::to_string_with), e.g. for ECMAScript number formatting,
+//!   or [canonicalized](Value::to_canonical_string), e.g. to [hash](Value::digest)
+//!   or [sign](Value::sign) it).
+//!
+//! It can also be converted to and from [CBOR](Value::to_cbor),
+//! [MessagePack](Value::to_msgpack), [BSON](Value::to_bson) (with Extended JSON),
+//! [UBJSON](Value::to_ubjson) and [Smile](Value::to_smile),
+//! or saved as a compact [binary snapshot](Value::to_snapshot) for fast reloading.
 
 #![forbid(unsafe_code)]
 
@@ -216,7 +237,65 @@ escape_tables! {
 
 const MIN_VALID_STRING_CHAR: u8 = b'\x20';
 
+/// What to do, when decoding a binary format, with a construct that has no JSON equivalent.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NonJsonPolicy {
+    /// Fail with an error.
+    Reject,
+    /// Convert (lossily) to JSON.
+    Convert,
+}
+
+mod access;
+mod base64;
+mod bson;
+mod cbor;
+mod convert;
+mod diff;
+mod digest;
+mod extract;
+mod flatten;
+mod include;
+mod merge;
+mod msgpack;
 mod parse;
+mod pointer;
+mod schema;
+#[cfg(feature = "serde")]
+mod serde_support;
+mod smile;
+mod snapshot;
 mod stringify;
+mod ubjson;
+mod visit;
 
-pub use parse::{ParseError, ParseErrorKind, ParseErrorPosition};
+pub use access::ValueIndex;
+pub use bson::{
+    BsonEncodeError, BsonEncodeErrorKind, BsonError, BsonErrorKind, BsonOptions, ExtendedJsonMode,
+};
+pub use cbor::{CborError, CborErrorKind, CborOptions};
+pub use convert::{FromJson, FromJsonError, FromJsonErrorKind, ToJson};
+pub use diff::{diff, Change, ChangeKind, Diff};
+pub use digest::{hmac_sha256, sha256};
+pub use extract::FieldReader;
+pub use flatten::{
+    flatten, flatten_with, unflatten, unflatten_with, FlattenOptions, UnflattenError,
+    UnflattenErrorKind,
+};
+pub use include::{json_include, IncludeError, IncludeErrorKind, IncludeOptions};
+pub use merge::{ArrayMerge, MergeError, MergeOptions, MergeStrategy, TypeConflict};
+pub use msgpack::{MsgpackError, MsgpackErrorKind, MsgpackOptions};
+pub use parse::{ParseError, ParseErrorKind, ParseErrorPosition, Span, Spans};
+pub use pointer::{InvalidJsonPointer, JsonPointer, PointerToken};
+#[cfg(feature = "derive")]
+pub use rustic_json_derive::{FromJson, ToJson};
+pub use schema::{
+    InferOptions, Schema, SchemaError, SchemaErrorKind, ValidationError, ValidationErrorKind,
+};
+#[cfg(feature = "serde")]
+pub use serde_support::{from_str, from_value, to_string, to_value, SerdeError};
+pub use smile::{SmileError, SmileErrorKind, SmileOptions};
+pub use snapshot::{SnapshotError, SnapshotErrorKind};
+pub use stringify::{NumberStyle, StringifyOptions};
+pub use ubjson::{UbjsonError, UbjsonErrorKind, UbjsonOptions};
+pub use visit::{Visit, VisitMut, Walk};
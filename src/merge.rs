@@ -0,0 +1,254 @@
+use super::*;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+impl Value {
+    /// Merges another value into this one (e.g. layers of configuration), with the default
+    /// [`MergeOptions`]: objects are merged recursively, and anything else is overridden
+    /// by `other` (including arrays, and values of different types).
+    ///
+    /// Returns the locations of the values that were overridden (by a different value).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    ///
+    /// let mut config = json!({"server": {"host": "localhost", "port": 80}, "tags": ["a"]});
+    /// let overridden = config.deep_merge(json!({"server": {"port": 8080, "tls": true}, "tags": ["b"]}));
+    /// assert_eq!(config, json!({"server": {"host": "localhost", "port": 8080, "tls": true}, "tags": ["b"]}));
+    /// assert_eq!(overridden, ["/server/port".parse().unwrap(), "/tags".parse().unwrap()]);
+    /// ```
+    pub fn deep_merge(&mut self, other: Value) -> Vec<JsonPointer> {
+        self.deep_merge_with(other, &MergeOptions::default())
+            .expect("no errors when overriding type conflicts")
+    }
+
+    /// Merges another value into this one, with the given options.
+    ///
+    /// Returns the locations of the values that were overridden (by a different value).
+    ///
+    /// # Errors
+    ///
+    /// On a type conflict, with [`TypeConflict::Error`]
+    /// (in which case `self` is left partially merged).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{ArrayMerge, MergeError, MergeOptions, MergeStrategy, TypeConflict};
+    ///
+    /// let defaults = json!({
+    ///     "paths": ["/usr/bin"],
+    ///     "features": ["a", "b"],
+    ///     "servers": [{"name": "main", "port": 80}, {"name": "backup", "port": 81}],
+    ///     "log": {"level": "info"}
+    /// });
+    /// let user = json!({
+    ///     "paths": ["/opt/bin"],
+    ///     "features": ["b", "c"],
+    ///     "servers": [{"name": "main", "port": 8080}, {"name": "extra", "port": 82}],
+    ///     "log": {"level": "debug"}
+    /// });
+    /// let options = MergeOptions {
+    ///     strategy: MergeStrategy { arrays: ArrayMerge::Append, ..MergeStrategy::default() },
+    ///     path_strategies: [
+    ///         ("/features".parse().unwrap(), MergeStrategy { arrays: ArrayMerge::Union, ..MergeStrategy::default() }),
+    ///         ("/servers".parse().unwrap(), MergeStrategy { arrays: ArrayMerge::MergeByKey("name".into()), ..MergeStrategy::default() }),
+    ///     ].into(),
+    /// };
+    /// let mut config = defaults.clone();
+    /// let overridden = config.deep_merge_with(user, &options).unwrap();
+    /// assert_eq!(config, json!({
+    ///     "paths": ["/usr/bin", "/opt/bin"],
+    ///     "features": ["a", "b", "c"],
+    ///     "servers": [{"name": "main", "port": 8080}, {"name": "backup", "port": 81}, {"name": "extra", "port": 82}],
+    ///     "log": {"level": "debug"}
+    /// }));
+    /// assert_eq!(overridden, ["/log/level".parse().unwrap(), "/servers/0/port".parse().unwrap()]);
+    ///
+    /// // type conflicts (other than with null)
+    /// let options = MergeOptions {
+    ///     strategy: MergeStrategy { type_conflicts: TypeConflict::Error, ..MergeStrategy::default() },
+    ///     ..MergeOptions::default()
+    /// };
+    /// let mut config = defaults.clone();
+    /// let error = config.deep_merge_with(json!({"log": "verbose"}), &options).unwrap_err();
+    /// assert_eq!(error, MergeError { pointer: "/log".parse().unwrap(), existing: "object", incoming: "string" });
+    /// assert_eq!(error.to_string(), r#"cannot merge string into object at "/log""#);
+    /// let mut config = defaults;
+    /// assert!(config.deep_merge_with(json!({"log": null}), &options).is_ok());
+    /// ```
+    pub fn deep_merge_with(
+        &mut self,
+        other: Value,
+        options: &MergeOptions,
+    ) -> Result<Vec<JsonPointer>, MergeError> {
+        let mut merger = helper::Merger {
+            options,
+            pointer: JsonPointer::root(),
+            overridden: Vec::new(),
+        };
+        merger.merge(self, other, &options.strategy)?;
+        Ok(merger.overridden)
+    }
+}
+
+/// Options for [`Value::deep_merge_with`].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct MergeOptions {
+    /// Used everywhere, except under the locations of `path_strategies`.
+    pub strategy: MergeStrategy,
+    /// Used at and under these locations (of the merged value),
+    /// except under more specific ones.
+    ///
+    /// Array elements merged by key are at their index in the merged value.
+    pub path_strategies: BTreeMap<JsonPointer, MergeStrategy>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct MergeStrategy {
+    pub arrays: ArrayMerge,
+    pub type_conflicts: TypeConflict,
+}
+
+/// How to merge an array into another.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub enum ArrayMerge {
+    /// Replacing it.
+    #[default]
+    Replace,
+    /// Appending all the elements.
+    Append,
+    /// Appending the elements not already present.
+    Union,
+    /// Merging objects with the same value for the given field into the existing one
+    /// (recursively), and appending the others.
+    MergeByKey(Str),
+}
+
+/// What to do when merging a value into one of another type (other than `null`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum TypeConflict {
+    /// Replacing the existing value.
+    #[default]
+    Override,
+    /// Failing with a [`MergeError`].
+    Error,
+}
+
+/// Error from [`Value::deep_merge_with`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MergeError {
+    /// Location of the conflict.
+    pub pointer: JsonPointer,
+    /// Types (see [`Value::type_name`]) of the existing and incoming values.
+    pub existing: &'static str,
+    pub incoming: &'static str,
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot merge {} into {} at \"{}\"",
+            self.incoming, self.existing, self.pointer
+        )
+    }
+}
+
+impl Error for MergeError {}
+
+mod helper {
+    use super::*;
+
+    pub(super) struct Merger<'o> {
+        pub(super) options: &'o MergeOptions,
+        pub(super) pointer: JsonPointer,
+        pub(super) overridden: Vec<JsonPointer>,
+    }
+
+    impl<'o> Merger<'o> {
+        pub(super) fn merge(
+            &mut self,
+            target: &mut Value,
+            other: Value,
+            strategy: &'o MergeStrategy,
+        ) -> Result<(), MergeError> {
+            let strategy = self
+                .options
+                .path_strategies
+                .get(&self.pointer)
+                .unwrap_or(strategy);
+            match (target, other) {
+                (Value::Object(target), Value::Object(other)) => {
+                    for (key, value) in other {
+                        match target.get_mut(&key) {
+                            Some(existing) => {
+                                self.pointer.push_key(&key);
+                                self.merge(existing, value, strategy)?;
+                                self.pointer.pop();
+                            }
+                            None => {
+                                target.insert(key, value);
+                            }
+                        }
+                    }
+                }
+                (Value::Array(target), Value::Array(other)) => match &strategy.arrays {
+                    ArrayMerge::Replace => self.replace(target, other),
+                    ArrayMerge::Append => target.extend(other),
+                    ArrayMerge::Union => {
+                        for value in other {
+                            if !target.contains(&value) {
+                                target.push(value);
+                            }
+                        }
+                    }
+                    ArrayMerge::MergeByKey(field) => {
+                        for value in other {
+                            let id = value.as_object().and_then(|obj| obj.get(field));
+                            let existing = id.and_then(|id| {
+                                target.iter().position(|existing| {
+                                    existing.as_object().and_then(|obj| obj.get(field)) == Some(id)
+                                })
+                            });
+                            match existing {
+                                Some(i) => {
+                                    self.pointer.push_index(i);
+                                    self.merge(&mut target[i], value, strategy)?;
+                                    self.pointer.pop();
+                                }
+                                None => target.push(value),
+                            }
+                        }
+                    }
+                },
+                (target, other) => {
+                    let is_conflict = std::mem::discriminant(target)
+                        != std::mem::discriminant(&other)
+                        && *target != Value::Null
+                        && other != Value::Null;
+                    if is_conflict && strategy.type_conflicts == TypeConflict::Error {
+                        return Err(MergeError {
+                            pointer: self.pointer.clone(),
+                            existing: target.type_name(),
+                            incoming: other.type_name(),
+                        });
+                    }
+                    self.replace(target, other);
+                }
+            }
+            Ok(())
+        }
+
+        fn replace<T: PartialEq>(&mut self, target: &mut T, other: T) {
+            if *target != other {
+                *target = other;
+                self.overridden.push(self.pointer.clone());
+            }
+        }
+    }
+}
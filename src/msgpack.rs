@@ -0,0 +1,260 @@
+use super::*;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io::{self, Read};
+
+impl Value {
+    /// Encodes into [MessagePack], choosing the smallest encoding for each number:
+    /// whole numbers within the 64-bit integer ranges as (the smallest) integers,
+    /// other numbers as single-precision floats when that preserves them, otherwise as doubles.
+    ///
+    /// [MessagePack]: https://github.com/msgpack/msgpack/blob/master/spec.md
+    ///
+    /// # Panics
+    ///
+    /// If a string, array or object is longer than 2<sup>32</sup> - 1 (bytes or elements).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    ///
+    /// let mut expected = vec![0x82, 0xa7];
+    /// expected.extend_from_slice(b"compact");
+    /// expected.extend_from_slice(&[0xc3, 0xa6]);
+    /// expected.extend_from_slice(b"schema");
+    /// expected.push(0x00);
+    /// assert_eq!(json!({"compact": true, "schema": 0}).to_msgpack(), expected);
+    ///
+    /// assert_eq!(json!([1, (-1), 200, (-200), 70000, 1.5, 0.1]).to_msgpack(), [
+    ///     0x97, 0x01, 0xff, 0xcc, 0xc8, 0xd1, 0xff, 0x38, 0xce, 0x00, 0x01, 0x11, 0x70,
+    ///     0xca, 0x3f, 0xc0, 0x00, 0x00, 0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a,
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        helper::encode(self, &mut out);
+        out
+    }
+
+    /// Decodes a single [MessagePack] value, with default options
+    /// (rejecting any type without a JSON equivalent).
+    ///
+    /// Integers are converted to `f64` (so possibly rounded beyond ±2<sup>53</sup>).
+    ///
+    /// [MessagePack]: https://github.com/msgpack/msgpack/blob/master/spec.md
+    ///
+    /// # Errors
+    ///
+    /// If the data is invalid, not completely consumed,
+    /// or contains a value rejected by the options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{MsgpackError, MsgpackErrorKind, Value};
+    ///
+    /// let value = json!({"a": [1, 0.5, "x", (-123456789)], "b": null});
+    /// assert_eq!(Value::from_msgpack(&value.to_msgpack()), Ok(value));
+    ///
+    /// let error = Value::from_msgpack(&[0x92, 0x01]).unwrap_err();
+    /// assert_eq!(error, MsgpackError { kind: MsgpackErrorKind::PrematureEnd, offset: 2 });
+    /// assert_eq!(error.to_string(), "premature end of data at byte 2");
+    /// // bin 8
+    /// let error = Value::from_msgpack(&[0x91, 0xc4, 0x01, 0xff]).unwrap_err();
+    /// assert_eq!(error, MsgpackError { kind: MsgpackErrorKind::Binary, offset: 1 });
+    /// ```
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, MsgpackError> {
+        Self::from_msgpack_with(bytes, &MsgpackOptions::default())
+    }
+
+    /// Decodes a single [MessagePack] value, mapping types without
+    /// a JSON equivalent as configured (see [`MsgpackOptions`]).
+    ///
+    /// [MessagePack]: https://github.com/msgpack/msgpack/blob/master/spec.md
+    ///
+    /// # Errors
+    ///
+    /// See [`Value::from_msgpack`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{MsgpackErrorKind, MsgpackOptions, NonJsonPolicy, Value};
+    ///
+    /// // {1: bin(01 ff), "t": timestamp(1.5 s), "x": ext(7, 00), "n": NaN as float 32}
+    /// let bytes = [
+    ///     0x84, 0x01, 0xc4, 0x02, 0x01, 0xff,
+    ///     0xa1, b't', 0xd7, 0xff, 0x77, 0x35, 0x94, 0x00, 0x00, 0x00, 0x00, 0x01,
+    ///     0xa1, b'x', 0xd4, 0x07, 0x00,
+    ///     0xa1, b'n', 0xca, 0x7f, 0xc0, 0x00, 0x00,
+    /// ];
+    /// assert_eq!(
+    ///     Value::from_msgpack_with(&bytes, &MsgpackOptions::lossy()),
+    ///     Ok(json!({"1": "Af8", "t": 1.5, "x": "AA", "n": null}))
+    /// );
+    ///
+    /// let options = MsgpackOptions { non_string_keys: NonJsonPolicy::Convert, ..MsgpackOptions::default() };
+    /// let error = Value::from_msgpack_with(&bytes, &options).unwrap_err();
+    /// assert_eq!((error.kind, error.offset), (MsgpackErrorKind::Binary, 2));
+    /// let options = MsgpackOptions { binary: NonJsonPolicy::Convert, ..options };
+    /// let error = Value::from_msgpack_with(&bytes, &options).unwrap_err();
+    /// assert_eq!((error.kind, error.offset), (MsgpackErrorKind::Extension(-1), 8));
+    /// ```
+    pub fn from_msgpack_with(
+        mut bytes: &[u8],
+        options: &MsgpackOptions,
+    ) -> Result<Self, MsgpackError> {
+        let len = bytes.len();
+        let value = Self::read_msgpack(&mut bytes, options)?;
+        if !bytes.is_empty() {
+            return Err(MsgpackError {
+                kind: MsgpackErrorKind::TrailingData,
+                offset: len - bytes.len(),
+            });
+        }
+        Ok(value)
+    }
+
+    /// Decodes a single [MessagePack] value from a reader, without reading past its end
+    /// (so a stream of values can be decoded by calling this repeatedly).
+    ///
+    /// Reads are small, so an unbuffered reader (e.g. a file or socket)
+    /// should be wrapped in an [`io::BufReader`].
+    ///
+    /// [MessagePack]: https://github.com/msgpack/msgpack/blob/master/spec.md
+    ///
+    /// # Errors
+    ///
+    /// See [`Value::from_msgpack`]; also for an I/O error
+    /// (offsets are then relative to the start of the value).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{MsgpackErrorKind, MsgpackOptions, Value};
+    ///
+    /// let mut stream = json!({"seq": 1}).to_msgpack();
+    /// stream.extend(json!([true]).to_msgpack());
+    /// let mut reader = stream.as_slice();
+    /// let options = MsgpackOptions::default();
+    /// assert_eq!(Value::read_msgpack(&mut reader, &options), Ok(json!({"seq": 1})));
+    /// assert_eq!(Value::read_msgpack(&mut reader, &options), Ok(json!([true])));
+    /// assert!(reader.is_empty());
+    ///
+    /// let options = MsgpackOptions { max_depth: 2, ..options };
+    /// let error = Value::read_msgpack(&[0x91, 0x91, 0x91, 0xc0][..], &options).unwrap_err();
+    /// assert_eq!((error.kind, error.offset), (MsgpackErrorKind::TooDeep, 2));
+    /// ```
+    pub fn read_msgpack<R: Read>(
+        reader: R,
+        options: &MsgpackOptions,
+    ) -> Result<Self, MsgpackError> {
+        helper::decode(reader, options)
+    }
+}
+
+/// Options for [`Value::from_msgpack_with`] and [`Value::read_msgpack`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MsgpackOptions {
+    /// `bin` values: converted to base64url strings (without padding).
+    pub binary: NonJsonPolicy,
+    /// `ext` values: timestamps (type -1) converted to (fractional) seconds
+    /// since the Unix epoch, others to base64url strings of their data (without padding).
+    pub extensions: NonJsonPolicy,
+    /// Map keys other than strings: converted to their JSON text.
+    pub non_string_keys: NonJsonPolicy,
+    /// NaN and infinities: converted to `null`.
+    pub non_finite_floats: NonJsonPolicy,
+    /// Maximum nesting of arrays and maps.
+    pub max_depth: usize,
+}
+
+impl MsgpackOptions {
+    /// Converting every type without a JSON equivalent.
+    #[must_use]
+    pub fn lossy() -> Self {
+        Self {
+            binary: NonJsonPolicy::Convert,
+            extensions: NonJsonPolicy::Convert,
+            non_string_keys: NonJsonPolicy::Convert,
+            non_finite_floats: NonJsonPolicy::Convert,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for MsgpackOptions {
+    /// Rejecting every type without a JSON equivalent.
+    fn default() -> Self {
+        Self {
+            binary: NonJsonPolicy::Reject,
+            extensions: NonJsonPolicy::Reject,
+            non_string_keys: NonJsonPolicy::Reject,
+            non_finite_floats: NonJsonPolicy::Reject,
+            max_depth: 512,
+        }
+    }
+}
+
+/// Error from [`Value::from_msgpack`] or [`Value::read_msgpack`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MsgpackError {
+    pub kind: MsgpackErrorKind,
+    /// Of the start of the offending value (or of the end of data).
+    pub offset: usize,
+}
+
+impl Display for MsgpackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl Error for MsgpackError {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MsgpackErrorKind {
+    PrematureEnd,
+    /// Data after the (first) value.
+    TrailingData,
+    /// The "never used" format byte `0xc1`.
+    InvalidMarker,
+    InvalidUtf8,
+    DuplicateKey(Str),
+    TooDeep,
+    /// An invalid timestamp extension.
+    InvalidTimestamp,
+    Binary,
+    /// With the extension type.
+    Extension(i8),
+    NonStringKey,
+    NonFiniteFloat,
+    /// From the reader.
+    Io(io::ErrorKind),
+}
+
+impl Display for MsgpackErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PrematureEnd => f.write_str("premature end of data"),
+            Self::TrailingData => f.write_str("trailing data"),
+            Self::InvalidMarker => f.write_str("invalid marker"),
+            Self::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            Self::DuplicateKey(key) => write!(f, "duplicate key {:?}", key),
+            Self::TooDeep => f.write_str("too deep nesting"),
+            Self::InvalidTimestamp => f.write_str("invalid timestamp"),
+            Self::Binary => f.write_str("unsupported binary"),
+            Self::Extension(typ) => write!(f, "unsupported extension type {}", typ),
+            Self::NonStringKey => f.write_str("unsupported non-string key"),
+            Self::NonFiniteFloat => f.write_str("unsupported non-finite float"),
+            Self::Io(kind) => write!(f, "I/O error ({})", kind),
+        }
+    }
+}
+
+mod helper;
@@ -0,0 +1,334 @@
+use super::*;
+
+type DecodeResult<T> = Result<T, MsgpackError>;
+
+use MsgpackErrorKind as K;
+
+const TIMESTAMP: i8 = -1;
+
+pub(super) fn encode(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.push(0xc0),
+        Value::Boolean(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+        Value::Number(num) => encode_number(*num, out),
+        Value::String(str) => encode_str(str, out),
+        Value::Array(arr) => {
+            write_len(arr.len(), (0x90, 16), 0xdc, 0xdd, out);
+            for element in arr {
+                encode(element, out);
+            }
+        }
+        Value::Object(obj) => {
+            write_len(obj.len(), (0x80, 16), 0xde, 0xdf, out);
+            for (key, value) in obj {
+                encode_str(key, out);
+                encode(value, out);
+            }
+        }
+    }
+}
+
+fn encode_str(str: &str, out: &mut Vec<u8>) {
+    if let Ok(len) = u8::try_from(str.len()) {
+        if len >= 32 {
+            out.extend_from_slice(&[0xd9, len]);
+        } else {
+            out.push(0xa0 | len);
+        }
+    } else {
+        write_len(str.len(), (0xa0, 32), 0xda, 0xdb, out);
+    }
+    out.extend_from_slice(str.as_bytes());
+}
+
+/// With the "fix" marker and bound, and the 16-bit and 32-bit markers.
+#[allow(clippy::cast_possible_truncation)]
+fn write_len(
+    len: usize,
+    (fix, fix_bound): (u8, usize),
+    marker16: u8,
+    marker32: u8,
+    out: &mut Vec<u8>,
+) {
+    if len < fix_bound {
+        out.push(fix | len as u8);
+    } else if let Ok(len) = u16::try_from(len) {
+        out.push(marker16);
+        out.extend_from_slice(&len.to_be_bytes());
+    } else {
+        let len = u32::try_from(len).expect("length within 32 bits");
+        out.push(marker32);
+        out.extend_from_slice(&len.to_be_bytes());
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn encode_number(num: Num, out: &mut Vec<u8>) {
+    let f = num.get();
+    if let Some(u) = num.as_u64() {
+        if u < 0x80 {
+            out.push(u as u8);
+        } else if let Ok(u) = u8::try_from(u) {
+            out.extend_from_slice(&[0xcc, u]);
+        } else if let Ok(u) = u16::try_from(u) {
+            out.push(0xcd);
+            out.extend_from_slice(&u.to_be_bytes());
+        } else if let Ok(u) = u32::try_from(u) {
+            out.push(0xce);
+            out.extend_from_slice(&u.to_be_bytes());
+        } else {
+            out.push(0xcf);
+            out.extend_from_slice(&u.to_be_bytes());
+        }
+    } else if let Some(i) = num.as_i64() {
+        // (negative)
+        if i >= -32 {
+            out.push(i as u8);
+        } else if let Ok(i) = i8::try_from(i) {
+            out.extend_from_slice(&[0xd0, i as u8]);
+        } else if let Ok(i) = i16::try_from(i) {
+            out.push(0xd1);
+            out.extend_from_slice(&i.to_be_bytes());
+        } else if let Ok(i) = i32::try_from(i) {
+            out.push(0xd2);
+            out.extend_from_slice(&i.to_be_bytes());
+        } else {
+            out.push(0xd3);
+            out.extend_from_slice(&i.to_be_bytes());
+        }
+    } else if f64::from(f as f32) == f {
+        out.push(0xca);
+        out.extend_from_slice(&(f as f32).to_be_bytes());
+    } else {
+        out.push(0xcb);
+        out.extend_from_slice(&f.to_be_bytes());
+    }
+}
+
+pub(super) fn decode<R: Read>(reader: R, options: &MsgpackOptions) -> DecodeResult<Value> {
+    Decoder {
+        reader,
+        offset: 0,
+        options,
+        depth: 0,
+    }
+    .decode_item()
+}
+
+struct Decoder<'o, R> {
+    reader: R,
+    offset: usize,
+    options: &'o MsgpackOptions,
+    depth: usize,
+}
+
+impl<R: Read> Decoder<'_, R> {
+    fn error_at(&self, kind: MsgpackErrorKind, offset: usize) -> MsgpackError {
+        MsgpackError { kind, offset }
+    }
+
+    /// Fails with the error unless the options convert the type.
+    fn check(
+        &self,
+        policy: NonJsonPolicy,
+        kind: MsgpackErrorKind,
+        offset: usize,
+    ) -> DecodeResult<()> {
+        match policy {
+            NonJsonPolicy::Reject => Err(self.error_at(kind, offset)),
+            NonJsonPolicy::Convert => Ok(()),
+        }
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> DecodeResult<()> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) => return Err(self.error_at(K::PrematureEnd, self.offset)),
+                Ok(n) => {
+                    filled += n;
+                    self.offset += n;
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(self.error_at(K::Io(error.kind()), self.offset)),
+            }
+        }
+        Ok(())
+    }
+
+    fn read_array<const N: usize>(&mut self) -> DecodeResult<[u8; N]> {
+        let mut buf = [0; N];
+        self.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    fn read_u8(&mut self) -> DecodeResult<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    fn read_len(&mut self, bytes: usize) -> DecodeResult<usize> {
+        let len = match bytes {
+            1 => u32::from(self.read_u8()?),
+            2 => u32::from(u16::from_be_bytes(self.read_array()?)),
+            _ => u32::from_be_bytes(self.read_array()?),
+        };
+        Ok(usize::try_from(len).expect("32-bit length"))
+    }
+
+    /// Without allocating up front for the announced length (which may be bogus).
+    fn read_vec(&mut self, len: usize) -> DecodeResult<Vec<u8>> {
+        let mut vec = Vec::new();
+        let read = (&mut self.reader).take(len as u64).read_to_end(&mut vec);
+        self.offset += vec.len();
+        match read {
+            Ok(_) if vec.len() == len => Ok(vec),
+            Ok(_) => Err(self.error_at(K::PrematureEnd, self.offset)),
+            Err(error) => Err(self.error_at(K::Io(error.kind()), self.offset)),
+        }
+    }
+
+    fn read_string(&mut self, len: usize, start: usize) -> DecodeResult<Str> {
+        let bytes = self.read_vec(len)?;
+        String::from_utf8(bytes)
+            .map(Str::from)
+            .map_err(|_| self.error_at(K::InvalidUtf8, start))
+    }
+
+    fn enter(&mut self, start: usize) -> DecodeResult<()> {
+        if self.depth >= self.options.max_depth {
+            return Err(self.error_at(K::TooDeep, start));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn decode_item(&mut self) -> DecodeResult<Value> {
+        let start = self.offset;
+        let marker = self.read_u8()?;
+        self.decode_marked(marker, start)
+    }
+
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
+    fn decode_marked(&mut self, marker: u8, start: usize) -> DecodeResult<Value> {
+        let f = match marker {
+            0x00..=0x7f => f64::from(marker),
+            0xe0..=0xff => f64::from(marker as i8),
+            0xcc => f64::from(self.read_u8()?),
+            0xcd => f64::from(u16::from_be_bytes(self.read_array()?)),
+            0xce => f64::from(u32::from_be_bytes(self.read_array()?)),
+            0xcf => u64::from_be_bytes(self.read_array()?) as f64,
+            0xd0 => f64::from(i8::from_be_bytes(self.read_array()?)),
+            0xd1 => f64::from(i16::from_be_bytes(self.read_array()?)),
+            0xd2 => f64::from(i32::from_be_bytes(self.read_array()?)),
+            0xd3 => i64::from_be_bytes(self.read_array()?) as f64,
+            0xca => f64::from(f32::from_be_bytes(self.read_array()?)),
+            0xcb => f64::from_be_bytes(self.read_array()?),
+            0xc0 => return Ok(Value::Null),
+            0xc1 => return Err(self.error_at(K::InvalidMarker, start)),
+            0xc2 => return Ok(Value::Boolean(false)),
+            0xc3 => return Ok(Value::Boolean(true)),
+            0xa0..=0xbf => {
+                return self
+                    .read_string(usize::from(marker & 0x1f), start)
+                    .map(Value::String)
+            }
+            0xd9..=0xdb => {
+                let len = self.read_len(1 << (marker - 0xd9))?;
+                return self.read_string(len, start).map(Value::String);
+            }
+            0xc4..=0xc6 => {
+                self.check(self.options.binary, K::Binary, start)?;
+                let len = self.read_len(1 << (marker - 0xc4))?;
+                let bytes = self.read_vec(len)?;
+                return Ok(Value::from(base64::encode(&bytes, base64::URL_SAFE, false)));
+            }
+            0xc7..=0xc9 | 0xd4..=0xd8 => {
+                let len = match marker {
+                    0xc7..=0xc9 => self.read_len(1 << (marker - 0xc7))?,
+                    _ => 1 << (marker - 0xd4),
+                };
+                let typ = self.read_u8()? as i8;
+                self.check(self.options.extensions, K::Extension(typ), start)?;
+                let data = self.read_vec(len)?;
+                if typ != TIMESTAMP {
+                    return Ok(Value::from(base64::encode(&data, base64::URL_SAFE, false)));
+                }
+                timestamp(&data).ok_or_else(|| self.error_at(K::InvalidTimestamp, start))?
+            }
+            0x90..=0x9f | 0xdc | 0xdd => {
+                let len = match marker {
+                    0xdc => self.read_len(2)?,
+                    0xdd => self.read_len(4)?,
+                    _ => usize::from(marker & 0x0f),
+                };
+                self.enter(start)?;
+                let mut arr = Arr::new();
+                for _ in 0..len {
+                    arr.push(self.decode_item()?);
+                }
+                self.depth -= 1;
+                return Ok(Value::Array(arr));
+            }
+            0x80..=0x8f | 0xde | 0xdf => {
+                let len = match marker {
+                    0xde => self.read_len(2)?,
+                    0xdf => self.read_len(4)?,
+                    _ => usize::from(marker & 0x0f),
+                };
+                self.enter(start)?;
+                let mut obj = Obj::new();
+                for _ in 0..len {
+                    self.decode_member(&mut obj)?;
+                }
+                self.depth -= 1;
+                return Ok(Value::Object(obj));
+            }
+        };
+        match Num::new(f) {
+            Some(num) => Ok(Value::Number(num)),
+            None => {
+                self.check(self.options.non_finite_floats, K::NonFiniteFloat, start)?;
+                Ok(Value::Null)
+            }
+        }
+    }
+
+    fn decode_member(&mut self, obj: &mut Obj) -> DecodeResult<()> {
+        let start = self.offset;
+        let marker = self.read_u8()?;
+        let key = match marker {
+            0xa0..=0xbf | 0xd9..=0xdb => match self.decode_marked(marker, start)? {
+                Value::String(str) => str,
+                _ => unreachable!("string marker"),
+            },
+            _ => {
+                self.check(self.options.non_string_keys, K::NonStringKey, start)?;
+                Str::from(self.decode_marked(marker, start)?.to_string())
+            }
+        };
+        if obj.contains_key(&key) {
+            return Err(self.error_at(K::DuplicateKey(key), start));
+        }
+        let value = self.decode_item()?;
+        obj.insert(key, value);
+        Ok(())
+    }
+}
+
+/// Seconds since the Unix epoch, from timestamp 32, 64 or 96.
+#[allow(clippy::cast_precision_loss)]
+fn timestamp(data: &[u8]) -> Option<f64> {
+    let (seconds, nanoseconds) = match data.len() {
+        4 => (f64::from(u32::from_be_bytes(data.try_into().ok()?)), 0),
+        8 => {
+            let n = u64::from_be_bytes(data.try_into().ok()?);
+            ((n & 0x3_ffff_ffff) as f64, u32::try_from(n >> 34).ok()?)
+        }
+        12 => (
+            i64::from_be_bytes(data[4..].try_into().ok()?) as f64,
+            u32::from_be_bytes(data[..4].try_into().ok()?),
+        ),
+        _ => return None,
+    };
+    (nanoseconds < 1_000_000_000).then(|| seconds + f64::from(nanoseconds) / 1e9)
+}
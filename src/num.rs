@@ -25,8 +25,59 @@ impl Num {
     pub fn get(self) -> f64 {
         self.0
     }
+
+    /// The number as an `i64`, if it is a whole number within its range
+    /// (but not `-0`, which would lose its sign), e.g. for binary formats.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::Num;
+    ///
+    /// let num = |f| Num::new(f).unwrap();
+    /// assert_eq!(num(-42.0).as_i64(), Some(-42));
+    /// assert_eq!(num(-9223372036854775808.0).as_i64(), Some(i64::MIN));
+    /// assert_eq!(num(9223372036854775808.0).as_i64(), None);
+    /// assert_eq!(num(0.5).as_i64(), None);
+    /// assert_eq!(num(0.0).as_i64(), Some(0));
+    /// assert_eq!(num(-0.0).as_i64(), None);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn as_i64(self) -> Option<i64> {
+        (self.is_whole() && (-I64_BOUND..I64_BOUND).contains(&self.0)).then_some(self.0 as i64)
+    }
+
+    /// The number as a `u64`, if it is a whole number within its range
+    /// (but not `-0`, which would lose its sign).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::Num;
+    ///
+    /// let num = |f| Num::new(f).unwrap();
+    /// assert_eq!(num(18446744073709549568.0).as_u64(), Some(18446744073709549568));
+    /// assert_eq!(num(18446744073709551616.0).as_u64(), None);
+    /// assert_eq!(num(-1.0).as_u64(), None);
+    /// assert_eq!(num(-0.0).as_u64(), None);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn as_u64(self) -> Option<u64> {
+        (self.is_whole() && (0.0..U64_BOUND).contains(&self.0)).then_some(self.0 as u64)
+    }
+
+    fn is_whole(self) -> bool {
+        self.0.fract() == 0.0 && !(self.0 == 0.0 && self.0.is_sign_negative())
+    }
 }
 
+/// 2<sup>63</sup> (exactly, unlike `i64::MAX as f64`).
+const I64_BOUND: f64 = 9_223_372_036_854_775_808.0;
+/// 2<sup>64</sup>.
+const U64_BOUND: f64 = 18_446_744_073_709_551_616.0;
+
 macro_rules! num_impl_from {
     ($param:ident: $typ:ty) => {
         impl From<$typ> for Num {
@@ -1,6 +1,8 @@
 use super::*;
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fmt::{self, Display};
+use std::ops::Range;
 use std::str::FromStr;
 
 impl FromStr for Value {
@@ -56,6 +58,35 @@ impl FromStr for Value {
     /// );
     /// ```
     ///
+    /// Numbers are converted to the closest `f64` (rounding ties to even), however many digits they have:
+    ///
+    /// ```
+    /// use rustic_json::Value;
+    ///
+    /// let number = |text: &str| match text.parse::<Value>() {
+    ///     Ok(Value::Number(num)) => num.get(),
+    ///     other => panic!("{:?}", other),
+    /// };
+    /// assert_eq!(number("0.1"), 0.1);
+    /// assert_eq!(number("9007199254740993"), 9007199254740992.0); // halfway: to even
+    /// assert_eq!(number("9007199254740993.000000000000000000001"), 9007199254740994.0);
+    /// assert_eq!(number("2.4703282292062327e-324"), 0.0);
+    /// assert_eq!(number("2.4703282292062328e-324"), 5e-324);
+    /// assert_eq!(number(&format!("0.{}1e1001", "0".repeat(1000))), 1.0);
+    /// assert_eq!(number("-0").to_bits(), (-0.0_f64).to_bits());
+    ///
+    /// // the same as the standard library
+    /// let mut seed = 0x5eed_u64;
+    /// for _ in 0..100_000 {
+    ///     seed ^= seed << 13;
+    ///     seed ^= seed >> 7;
+    ///     seed ^= seed << 17;
+    ///     let text = format!("{}.{:0>18}e{}", seed >> 40, seed % 1_000_000_000_000_000_000, seed % 300);
+    ///     let text = if seed % 2 == 0 { text } else { text.replace('e', "e-") };
+    ///     assert_eq!(number(&text), text.parse::<f64>().unwrap(), "{}", text);
+    /// }
+    /// ```
+    ///
     /// Invalid JSON:
     ///
     /// ```
@@ -117,6 +148,130 @@ impl FromStr for Value {
     }
 }
 
+impl Value {
+    /// Parses JSON data into a `Value`, recovering from errors instead of stopping at the first one.
+    ///
+    /// After an error inside an array or object, parsing resumes at the next
+    /// `,`, `]` or `}` of the same nesting level.
+    /// An element that could not be parsed is replaced by a [`Value::Null`] placeholder,
+    /// and a member whose key could not be parsed is dropped.
+    ///
+    /// Returns the (partial) value along with every error encountered, in order
+    /// (empty if and only if the data is valid JSON).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::Value;
+    /// use rustic_json::{ParseError, ParseErrorKind, ParseErrorPosition};
+    ///
+    /// let (value, errors) = Value::parse_recovering(r#"{"a": [1, tru, 3], "b": x, c: 4, "d": 5"#);
+    /// assert_eq!(value, json!({"a": [1, null, 3], "b": null, "d": 5}));
+    /// assert_eq!(
+    ///     errors
+    ///         .iter()
+    ///         .map(|ParseError { kind, position: ParseErrorPosition { line, column } }| (*kind, *line, *column))
+    ///         .collect::<Vec<_>>(),
+    ///     [
+    ///         (ParseErrorKind::UnexpectedChar, 1, 14),
+    ///         (ParseErrorKind::UnexpectedChar, 1, 25),
+    ///         (ParseErrorKind::UnexpectedChar, 1, 28),
+    ///         (ParseErrorKind::PrematureEof, 1, 40),
+    ///     ]
+    /// );
+    ///
+    /// // mismatched brackets, trailing comma, trailing data
+    /// let (value, errors) = Value::parse_recovering("[{\"a\": [1}, 2,] 3");
+    /// assert_eq!(value, json!([{"a": [1]}, 2, null]));
+    /// assert_eq!(errors.len(), 3);
+    ///
+    /// // valid JSON
+    /// assert_eq!(Value::parse_recovering("[1, 2]"), (json!([1, 2]), Vec::new()));
+    /// ```
+    #[must_use]
+    pub fn parse_recovering(s: &str) -> (Self, Vec<ParseError>) {
+        helper::parse_recovering(s)
+    }
+
+    /// Parses JSON data into a `Value`, along with the source [`Spans`] of all its nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{JsonPointer, Value};
+    /// use rustic_json::{ParseErrorPosition, Span};
+    ///
+    /// let data = "{\n  \"café\": [true, \"ok\"]\n}";
+    /// let (value, spans) = Value::parse_spanned(data).unwrap();
+    /// assert_eq!(value, json!({"café": [true, "ok"]}));
+    ///
+    /// let pointer: JsonPointer = "/café/1".parse().unwrap();
+    /// assert_eq!(
+    ///     spans.value(&pointer),
+    ///     Some(&Span {
+    ///         start: ParseErrorPosition { line: 2, column: 18 },
+    ///         end: ParseErrorPosition { line: 2, column: 22 },
+    ///         bytes: 20..24,
+    ///     })
+    /// );
+    /// assert_eq!(&data[spans.value(&pointer).unwrap().bytes.clone()], "\"ok\"");
+    ///
+    /// let key_span = spans.key(&"/café".parse().unwrap()).unwrap();
+    /// assert_eq!(&data[key_span.bytes.clone()], "\"café\"");
+    /// assert_eq!(key_span.start, ParseErrorPosition { line: 2, column: 3 });
+    ///
+    /// assert_eq!(spans.value(&JsonPointer::root()).unwrap().bytes, 0..data.len());
+    /// assert_eq!(spans.key(&JsonPointer::root()), None);
+    /// ```
+    pub fn parse_spanned(s: &str) -> Result<(Self, Spans), ParseError> {
+        helper::parse_spanned(s)
+    }
+}
+
+/// Location of a node in the source data.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Span {
+    /// Of the first char.
+    pub start: ParseErrorPosition,
+    /// Just past the last char.
+    pub end: ParseErrorPosition,
+    /// Byte offsets.
+    pub bytes: Range<usize>,
+}
+
+/// Side table of [`Span`]s, from [`Value::parse_spanned`].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Spans {
+    values: BTreeMap<JsonPointer, Span>,
+    keys: BTreeMap<JsonPointer, Span>,
+}
+
+impl Spans {
+    /// Span of the value (excluding surrounding whitespace) at the given pointer.
+    #[must_use]
+    pub fn value(&self, pointer: &JsonPointer) -> Option<&Span> {
+        self.values.get(pointer)
+    }
+
+    /// Span of the object key (including quotes) of the member at the given pointer.
+    #[must_use]
+    pub fn key(&self, pointer: &JsonPointer) -> Option<&Span> {
+        self.keys.get(pointer)
+    }
+
+    /// Iterates over the spans of all values.
+    pub fn values(&self) -> impl Iterator<Item = (&JsonPointer, &Span)> {
+        self.values.iter()
+    }
+
+    /// Iterates over the spans of all object keys.
+    pub fn keys(&self) -> impl Iterator<Item = (&JsonPointer, &Span)> {
+        self.keys.iter()
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct ParseError {
     pub kind: ParseErrorKind,
@@ -165,3 +320,4 @@ impl Display for ParseErrorPosition {
 }
 
 mod helper;
+mod number;
@@ -14,24 +14,125 @@ pub(super) fn parse(str: &str) -> ParseResult<Value> {
     Ok(element)
 }
 
+pub(super) fn parse_recovering(str: &str) -> (Value, Vec<ParseError>) {
+    let mut parser = Parser::new(str);
+    parser.errors = Some(Vec::new());
+    let element = parser.parse_element().unwrap_or_else(|error| {
+        parser.recover(error).expect("recovering");
+        Value::Null
+    });
+    if parser.state.peek_char().is_ok() {
+        let error = parser.state.error(K::UnexpectedChar);
+        parser.recover(error).expect("recovering");
+    }
+    (element, parser.errors.expect("recovering"))
+}
+
+pub(super) fn parse_spanned(str: &str) -> ParseResult<(Value, Spans)> {
+    let mut parser = Parser::new(str);
+    parser.spans = Some(Spans::default());
+    let element = parser.parse_element()?;
+    if parser.state.peek_char().is_ok() {
+        return Err(parser.state.error(K::UnexpectedChar));
+    }
+    Ok((element, parser.spans.expect("spanned")))
+}
+
 struct Parser<'s> {
     state: State<'s>,
+    /// `None` when strict (stop at the first error).
+    errors: Option<Vec<ParseError>>,
+    /// `None` when not tracking spans.
+    spans: Option<Spans>,
+    /// Only maintained when tracking spans.
+    path: JsonPointer,
 }
 
 impl Parser<'_> {
     fn new(str: &str) -> Parser<'_> {
         Parser {
             state: State::new(str),
+            errors: None,
+            spans: None,
+            path: JsonPointer::root(),
+        }
+    }
+
+    /// Records the error and returns `Ok` when recovering, otherwise returns it as `Err`.
+    fn recover(&mut self, error: ParseError) -> ParseResult<()> {
+        match &mut self.errors {
+            Some(errors) => {
+                // e.g. premature end of data seen by both a string and its enclosing array
+                if errors.last() != Some(&error) {
+                    errors.push(error);
+                }
+                Ok(())
+            }
+            None => Err(error),
+        }
+    }
+
+    /// Skips to the next `,`, `]` or `}` at the current nesting level (or to the end of data).
+    fn skip_to_sync(&mut self) {
+        let mut depth = 0_usize;
+        while let Ok(peeked) = self.state.peek_char() {
+            match peeked {
+                ',' if depth == 0 => break,
+                ']' | '}' if depth == 0 => break,
+                ']' | '}' => depth -= 1,
+                '[' | '{' => depth += 1,
+                '"' => {
+                    self.skip_junk_string();
+                    continue;
+                }
+                _ => {}
+            }
+            self.state.skip_char(peeked);
+        }
+    }
+
+    /// Skips a (possibly unterminated) string, stopping at the end of the line.
+    fn skip_junk_string(&mut self) {
+        self.state.skip_char('"');
+        while let Ok(peeked) = self.state.peek_char() {
+            self.state.skip_char(peeked);
+            match peeked {
+                '"' | '\n' => break,
+                '\\' => {
+                    if let Ok(escaped @ ('"' | '\\')) = self.state.peek_char() {
+                        self.state.skip_char(escaped);
+                    }
+                }
+                _ => {}
+            }
         }
     }
 
     fn parse_element(&mut self) -> ParseResult<Value> {
         self.skip_ws();
+        let start = self.spans.as_ref().map(|_| self.state.location());
         let value = self.parse_value()?;
+        if let (Some(spans), Some(start)) = (&mut self.spans, start) {
+            spans
+                .values
+                .insert(self.path.clone(), self.state.span_from(start));
+        }
         self.skip_ws();
         Ok(value)
     }
 
+    fn enter(&mut self, token: impl FnOnce(&mut JsonPointer)) {
+        if self.spans.is_some() {
+            token(&mut self.path);
+        }
+    }
+
+    fn leave(&mut self) {
+        if self.spans.is_some() {
+            self.path.pop();
+        }
+    }
+
     fn parse_value(&mut self) -> ParseResult<Value> {
         let peeked = self.state.peek_char()?;
         match peeked {
@@ -63,89 +164,70 @@ impl Parser<'_> {
     }
 
     fn parse_number(&mut self) -> ParseResult<Num> {
-        let num_error = self.state.error(K::TooBigNumber);
-        let mut buf = String::new();
-        macro_rules! consume_char {
-            ($buf:ident, $peeked:ident) => {{
-                $buf.push($peeked);
-                self.state.skip_char($peeked);
-            }};
-        }
-        macro_rules! accept_digits {
-            ($buf:ident) => {
-                while let Ok(digit @ '0'..='9') = self.state.peek_char() {
-                    consume_char!($buf, digit);
-                }
-            };
-        }
+        let start = self.state.offset();
+        let mut decimal = number::Decimal::default();
         macro_rules! require_digits {
-            ($buf:ident) => {{
-                let peeked = self.state.peek_char()?;
-                if !matches!(peeked, '0'..='9') {
+            () => {{
+                if !matches!(self.state.peek_char()?, '0'..='9') {
                     return Err(self.state.error(K::UnexpectedChar));
                 }
-                consume_char!($buf, peeked);
-                accept_digits!($buf);
+                self.state.take_digits()
             }};
         }
 
         // integer: /[-]?(0|[1-9][0-9]*)/
-        if let Ok(minus @ '-') = self.state.peek_char() {
-            consume_char!(buf, minus);
+        if let Some(b'-') = self.state.peek_byte() {
+            decimal.is_negative = true;
+            self.state.skip_byte();
         }
-        let peeked = self.state.peek_char()?;
-        match peeked {
-            '0' => consume_char!(buf, peeked),
-            '1'..='9' => {
-                consume_char!(buf, peeked);
-                accept_digits!(buf);
-            }
+        match self.state.peek_char()? {
+            '0' => self.state.skip_byte(),
+            '1'..='9' => decimal.push_integer_digits(self.state.take_digits()),
             _ => return Err(self.state.error(K::UnexpectedChar)),
         }
 
         // fraction: /([.][0-9]+)?/
-        if let Ok(dot @ '.') = self.state.peek_char() {
-            consume_char!(buf, dot);
-            require_digits!(buf);
+        if let Some(b'.') = self.state.peek_byte() {
+            self.state.skip_byte();
+            decimal.push_fraction_digits(require_digits!());
         }
 
         // exponent: /([Ee][+-]?[0-9]+)?/
-        if let Ok(e @ ('E' | 'e')) = self.state.peek_char() {
-            consume_char!(buf, e);
-            if let Ok(sign @ ('+' | '-')) = self.state.peek_char() {
-                consume_char!(buf, sign);
+        if let Some(b'E' | b'e') = self.state.peek_byte() {
+            self.state.skip_byte();
+            let is_negative = self.state.peek_byte() == Some(b'-');
+            if let Some(b'+' | b'-') = self.state.peek_byte() {
+                self.state.skip_byte();
             }
-            require_digits!(buf);
+            decimal.push_exponent_digits(require_digits!(), is_negative);
         }
 
-        let f: f64 = buf.parse().expect("valid f64 grammar");
+        let f = decimal.to_f64(self.state.slice_from(start));
         debug_assert!(!f.is_nan()); // only finite or infinite (too big)
-        Num::new(f).ok_or(num_error)
+        Num::new(f).ok_or_else(|| self.state.error_at(K::TooBigNumber, start))
     }
 
     fn parse_string(&mut self) -> ParseResult<Str> {
         self.expect_char('"')?;
         let mut buf = String::new();
         loop {
-            let peeked = self.state.peek_char()?;
-            if peeked == '"' {
-                self.state.skip_char(peeked);
-                break;
-            }
-            if peeked == '\\' {
-                buf.push(self.parse_escape()?);
-            } else if peeked >= char::from(MIN_VALID_STRING_CHAR) {
-                buf.push(peeked);
-                self.state.skip_char(peeked);
-            } else {
-                return Err(self.state.error(K::UnexpectedChar));
+            buf.push_str(self.state.take_string_run());
+            match self.state.peek_byte() {
+                Some(b'"') => {
+                    self.state.skip_byte();
+                    break;
+                }
+                Some(b'\\') => buf.push(self.parse_escape()?),
+                // a control char
+                Some(_) => return Err(self.state.error(K::UnexpectedChar)),
+                None => return Err(self.state.error(K::PrematureEof)),
             }
         }
         Ok(Str::from(buf))
     }
 
     fn parse_escape(&mut self) -> ParseResult<char> {
-        let utf16_decode_error = self.state.error(K::InvalidUtf16SurrogatePair);
+        let start = self.state.offset();
         self.expect_char('\\')?;
         let peeked = self.state.peek_char()?;
         if let Ok(byte) = u8::try_from(peeked) {
@@ -167,7 +249,7 @@ impl Parser<'_> {
             let result = char::decode_utf16([unit, unit_2])
                 .next()
                 .expect("not empty");
-            return result.or(Err(utf16_decode_error));
+            return result.map_err(|_| self.state.error_at(K::InvalidUtf16SurrogatePair, start));
         }
         Err(self.state.error(K::UnexpectedChar))
     }
@@ -192,15 +274,41 @@ impl Parser<'_> {
         self.skip_ws();
         let mut buf = Vec::new();
         loop {
-            let peeked = self.state.peek_char()?;
+            let peeked = match self.state.peek_char() {
+                Ok(peeked) => peeked,
+                Err(error) => {
+                    self.recover(error)?;
+                    break;
+                }
+            };
             if peeked == ']' {
                 self.state.skip_char(peeked);
                 break;
             }
+            if peeked == '}' {
+                // mismatched: leave it to an enclosing object (if any)
+                self.recover(self.state.error(K::UnexpectedChar))?;
+                break;
+            }
             if !buf.is_empty() {
-                self.expect_char(',')?;
+                if let Err(error) = self.expect_char(',') {
+                    self.recover(error)?;
+                    self.skip_to_sync();
+                    continue;
+                }
+            }
+            let index = buf.len();
+            self.enter(|path| path.push_index(index));
+            let element = self.parse_element();
+            self.leave();
+            match element {
+                Ok(element) => buf.push(element),
+                Err(error) => {
+                    self.recover(error)?;
+                    self.skip_to_sync();
+                    buf.push(Value::Null);
+                }
             }
-            buf.push(self.parse_element()?);
         }
         Ok(Arr::from(buf))
     }
@@ -209,81 +317,254 @@ impl Parser<'_> {
         self.expect_char('{')?;
         self.skip_ws();
         let mut buf = Vec::new();
+        // (not `buf.is_empty()`, as a failed member is not pushed)
+        let mut is_first = true;
         loop {
-            let peeked = self.state.peek_char()?;
+            let peeked = match self.state.peek_char() {
+                Ok(peeked) => peeked,
+                Err(error) => {
+                    self.recover(error)?;
+                    break;
+                }
+            };
             if peeked == '}' {
                 self.state.skip_char(peeked);
                 break;
             }
-            if !buf.is_empty() {
-                self.expect_char(',')?;
+            if peeked == ']' {
+                // mismatched: leave it to an enclosing array (if any)
+                self.recover(self.state.error(K::UnexpectedChar))?;
+                break;
+            }
+            if !is_first {
+                if let Err(error) = self.expect_char(',') {
+                    self.recover(error)?;
+                    self.skip_to_sync();
+                    continue;
+                }
+            }
+            is_first = false;
+            match self.parse_member() {
+                Ok(member) => buf.push(member),
+                Err(error) => {
+                    self.recover(error)?;
+                    self.skip_to_sync();
+                }
             }
-            buf.push(self.parse_member()?);
         }
         Ok(Obj::from_iter(buf))
     }
 
     fn parse_member(&mut self) -> ParseResult<(Str, Value)> {
         self.skip_ws();
+        let start = self.spans.as_ref().map(|_| self.state.location());
         let key = self.parse_string()?;
+        self.enter(|path| path.push_key(&key));
+        if let (Some(spans), Some(start)) = (&mut self.spans, start) {
+            spans
+                .keys
+                .insert(self.path.clone(), self.state.span_from(start));
+        }
         self.skip_ws();
-        self.expect_char(':')?;
-        let value = self.parse_element()?;
+        let value = match self.expect_char(':').and_then(|()| self.parse_element()) {
+            Ok(value) => value,
+            Err(error) => {
+                self.recover(error)?;
+                self.skip_to_sync();
+                Value::Null
+            }
+        };
+        self.leave();
         Ok((key, value))
     }
 
     fn skip_ws(&mut self) {
-        while let Ok(ws @ (' ' | '\n' | '\r' | '\t')) = self.state.peek_char() {
-            self.state.skip_char(ws);
-        }
+        self.state.skip_ws();
     }
 }
 
 mod internal {
     use super::*;
-    use std::iter::Peekable;
-    use std::str::Chars;
+    use std::cell::Cell;
 
+    /// Byte-oriented cursor; positions (line and column) are only computed
+    /// when needed (for errors and spans), from the byte offset.
     pub(super) struct State<'s> {
-        chars: Peekable<Chars<'s>>,
+        str: &'s str,
+        offset: usize,
+        /// Last computed position, to resume from (usually moving forward).
+        known: Cell<(usize, ParseErrorPosition)>,
+    }
+
+    #[derive(Clone, Copy)]
+    pub(super) struct Location {
         position: ParseErrorPosition,
+        offset: usize,
+    }
+
+    /// Bytes per chunk in fast scans.
+    const CHUNK: usize = 8;
+    const ONES: u64 = u64::from_ne_bytes([0x01; CHUNK]);
+    const HIGHS: u64 = u64::from_ne_bytes([0x80; CHUNK]);
+
+    /// The given byte repeated in each byte of a chunk.
+    const fn splat(byte: u8) -> u64 {
+        u64::from_ne_bytes([byte; CHUNK])
+    }
+
+    /// Whether any byte of the chunk is less than `n` (at most 0x80).
+    const fn has_less(chunk: u64, n: u8) -> bool {
+        chunk.wrapping_sub(splat(n)) & !chunk & HIGHS != 0
     }
 
-    impl State<'_> {
+    /// Whether any byte of the chunk is zero.
+    const fn has_zero(chunk: u64) -> bool {
+        chunk.wrapping_sub(ONES) & !chunk & HIGHS != 0
+    }
+
+    impl<'s> State<'s> {
         const ONE: usize = 1;
+        const START: ParseErrorPosition = ParseErrorPosition {
+            line: Self::ONE,
+            column: Self::ONE,
+        };
 
         pub(super) fn new(str: &str) -> State<'_> {
             State {
-                chars: str.chars().peekable(),
-                position: ParseErrorPosition {
-                    line: Self::ONE,
-                    column: Self::ONE,
-                },
+                str,
+                offset: 0,
+                known: Cell::new((0, Self::START)),
+            }
+        }
+
+        pub(super) fn offset(&self) -> usize {
+            self.offset
+        }
+
+        pub(super) fn location(&self) -> Location {
+            Location {
+                position: self.position_at(self.offset),
+                offset: self.offset,
             }
         }
 
-        pub(super) fn peek_char(&mut self) -> ParseResult<char> {
-            match self.chars.peek() {
-                Some(&peeked) => Ok(peeked),
+        pub(super) fn span_from(&self, start: Location) -> Span {
+            Span {
+                start: start.position,
+                end: self.position_at(self.offset),
+                bytes: start.offset..self.offset,
+            }
+        }
+
+        /// Counts lines and columns (in chars) up to the given offset (a char boundary).
+        fn position_at(&self, offset: usize) -> ParseErrorPosition {
+            let (mut from, mut position) = self.known.get();
+            if offset < from {
+                (from, position) = (0, Self::START);
+            }
+            let bytes = &self.str.as_bytes()[from..offset];
+            let count_chars = |bytes: &[u8]| bytes.iter().filter(|&&b| b & 0xc0 != 0x80).count();
+            match bytes.iter().rposition(|&b| b == b'\n') {
+                Some(last) => {
+                    position.line += bytes.iter().filter(|&&b| b == b'\n').count();
+                    position.column = Self::ONE + count_chars(&bytes[last + 1..]);
+                }
+                None => position.column += count_chars(bytes),
+            }
+            self.known.set((offset, position));
+            position
+        }
+
+        pub(super) fn peek_byte(&self) -> Option<u8> {
+            self.str.as_bytes().get(self.offset).copied()
+        }
+
+        pub(super) fn peek_char(&self) -> ParseResult<char> {
+            match self.peek_byte() {
+                Some(byte) if byte.is_ascii() => Ok(char::from(byte)),
+                Some(_) => Ok(self.str[self.offset..].chars().next().expect("not empty")),
                 None => Err(self.error(K::PrematureEof)),
             }
         }
 
         pub(super) fn skip_char(&mut self, peeked: char) {
-            let next = self.chars.next().expect("should have just peeked");
-            debug_assert_eq!(next, peeked);
-            if next == '\n' {
-                self.position.line += 1;
-                self.position.column = Self::ONE;
-            } else {
-                self.position.column += 1;
+            debug_assert_eq!(self.peek_char(), Ok(peeked));
+            self.offset += peeked.len_utf8();
+        }
+
+        /// Skips a peeked ASCII byte.
+        pub(super) fn skip_byte(&mut self) {
+            debug_assert!(self.peek_byte().is_some_and(|byte| byte.is_ascii()));
+            self.offset += 1;
+        }
+
+        /// Skips (and returns) the longest run of ASCII digits.
+        pub(super) fn take_digits(&mut self) -> &'s [u8] {
+            let bytes = self.str.as_bytes();
+            let start = self.offset;
+            let len = bytes[start..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count();
+            self.offset += len;
+            &bytes[start..self.offset]
+        }
+
+        /// Since the given offset (a char boundary).
+        pub(super) fn slice_from(&self, start: usize) -> &'s str {
+            &self.str[start..self.offset]
+        }
+
+        /// Reads the next chunk, if there are enough bytes left.
+        fn chunk(&self) -> Option<u64> {
+            let bytes = self.str.as_bytes().get(self.offset..self.offset + CHUNK)?;
+            Some(u64::from_le_bytes(bytes.try_into().expect("CHUNK bytes")))
+        }
+
+        pub(super) fn skip_ws(&mut self) {
+            // e.g. indentation
+            while self.chunk() == Some(splat(b' ')) {
+                self.offset += CHUNK;
+            }
+            while let Some(b' ' | b'\n' | b'\r' | b'\t') = self.peek_byte() {
+                self.offset += 1;
+            }
+        }
+
+        /// Skips (and returns) the longest run of string chars not needing any processing
+        /// (i.e. up to the next `"`, `\\` or control char).
+        pub(super) fn take_string_run(&mut self) -> &'s str {
+            let start = self.offset;
+            for chunk in self.str.as_bytes()[start..].chunks_exact(CHUNK) {
+                let chunk = u64::from_le_bytes(chunk.try_into().expect("CHUNK bytes"));
+                // (not short-circuiting: cheaper than branching)
+                if has_less(chunk, MIN_VALID_STRING_CHAR)
+                    | has_zero(chunk ^ splat(b'"'))
+                    | has_zero(chunk ^ splat(b'\\'))
+                {
+                    break;
+                }
+                self.offset += CHUNK;
             }
+            while let Some(byte) = self.peek_byte() {
+                if byte == b'"' || byte == b'\\' || byte < MIN_VALID_STRING_CHAR {
+                    break;
+                }
+                self.offset += 1;
+            }
+            // only stopped at an ASCII byte (or the end), so at a char boundary
+            &self.str[start..self.offset]
         }
 
         pub(super) fn error(&self, kind: K) -> ParseError {
+            self.error_at(kind, self.offset)
+        }
+
+        /// For an error detected after the offending item.
+        pub(super) fn error_at(&self, kind: K, offset: usize) -> ParseError {
             ParseError {
                 kind,
-                position: self.position,
+                position: self.position_at(offset),
             }
         }
     }
@@ -0,0 +1,311 @@
+//! Correctly rounded conversion of JSON numbers to `f64`, after the Eisel-Lemire algorithm
+//! (Daniel Lemire, "Number Parsing at a Gigabyte per Second", 2021).
+
+/// Significant digits that always fit into a `u64`.
+const MAX_DIGITS: u32 = 19;
+
+/// Decimal exponents (of 19-digit mantissas) below which all numbers round to zero,
+/// and above which all numbers are infinite.
+const SMALLEST_POWER_OF_TEN: i64 = -342;
+const LARGEST_POWER_OF_TEN: i64 = 308;
+
+const MANTISSA_BITS: i32 = 52;
+const MINIMUM_EXPONENT: i32 = -1023;
+const INFINITE_POWER: i32 = 0x7ff;
+/// Exponents for which `5^q` is exact enough to detect halfway cases.
+const MIN_EXPONENT_ROUND_TO_EVEN: i64 = -4;
+const MAX_EXPONENT_ROUND_TO_EVEN: i64 = 23;
+
+/// Powers of ten exactly representable as `f64`.
+const EXACT_POWERS_OF_TEN: [f64; 23] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+];
+
+/// `5^q` for `q` in [-342, 308], normalized to 128 bits (most significant bit set):
+/// truncated for `q >= 0`, and rounded up for `q < 0`.
+static POWERS_OF_FIVE: [u128; 651] = powers_of_five();
+
+/// Enough 64-bit limbs for the (exact) big integers used to compute the table.
+const LIMBS: usize = 28;
+type Big = [u64; LIMBS];
+
+const fn bit_length(n: &Big) -> usize {
+    let mut i = LIMBS;
+    while i > 0 {
+        i -= 1;
+        if n[i] != 0 {
+            return i * 64 + 64 - n[i].leading_zeros() as usize;
+        }
+    }
+    0
+}
+
+/// The 128 bits of `n` from bit `shift` (inclusive).
+const fn bits_from(n: &Big, shift: usize) -> u128 {
+    let (limb, offset) = (shift / 64, shift % 64);
+    let mut result = 0;
+    let mut k = 0;
+    while k < 3 && limb + k < LIMBS {
+        let bits = n[limb + k] as u128;
+        if k == 0 {
+            result |= bits >> offset;
+        } else if k * 64 - offset < 128 {
+            result |= bits << (k * 64 - offset);
+        }
+        k += 1;
+    }
+    result
+}
+
+/// The 128 most significant bits of `n` (shifted left if shorter).
+const fn top_bits(n: &Big) -> u128 {
+    let len = bit_length(n);
+    if len <= 128 {
+        bits_from(n, 0) << (128 - len)
+    } else {
+        bits_from(n, len - 128)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn powers_of_five() -> [u128; 651] {
+    const TOP: usize = LIMBS * 64 - 1;
+    const NEGATIVE: usize = -SMALLEST_POWER_OF_TEN as usize;
+    let mut table = [0; 651];
+
+    // q >= 0: 5^q
+    let mut pow: Big = [0; LIMBS];
+    pow[0] = 1;
+    let mut q = 0;
+    while NEGATIVE + q < table.len() {
+        table[NEGATIVE + q] = top_bits(&pow);
+        let mut carry = 0;
+        let mut k = 0;
+        while k < LIMBS {
+            let product = pow[k] as u128 * 5 + carry;
+            pow[k] = product as u64;
+            carry = product >> 64;
+            k += 1;
+        }
+        q += 1;
+    }
+
+    // q < 0: floor(2^b / 5^-q) + 1, from floor(2^TOP / 5^-q)
+    let mut quotient: Big = [0; LIMBS];
+    quotient[LIMBS - 1] = 1 << 63;
+    let mut k = 1;
+    while k <= NEGATIVE {
+        let mut remainder = 0;
+        let mut i = LIMBS;
+        while i > 0 {
+            i -= 1;
+            let dividend = remainder << 64 | quotient[i] as u128;
+            quotient[i] = (dividend / 5) as u64;
+            remainder = dividend % 5;
+        }
+        // z = ceil(log2(5^k))
+        let z = ((k as u32 * 1_217_359) >> 19) as usize + 1;
+        let b = if k <= 27 { z + 127 } else { 2 * z + 128 };
+        // quotient >> (TOP - b), plus 1
+        let shift = TOP - b;
+        let mut inverse: Big = [0; LIMBS];
+        let mut i = 0;
+        while i < LIMBS {
+            let from = shift + i * 64;
+            if from < LIMBS * 64 {
+                inverse[i] = bits_from(&quotient, from) as u64;
+            }
+            i += 1;
+        }
+        let mut i = 0;
+        while i < LIMBS {
+            inverse[i] = inverse[i].wrapping_add(1);
+            if inverse[i] != 0 {
+                break;
+            }
+            i += 1;
+        }
+        table[NEGATIVE - k] = top_bits(&inverse);
+        k += 1;
+    }
+    table
+}
+
+/// A JSON number being read (in a single pass), as `mantissa × 10^exponent`,
+/// keeping its first 19 significant digits.
+#[derive(Default)]
+pub(super) struct Decimal {
+    pub(super) is_negative: bool,
+    mantissa: u64,
+    digits: u32,
+    /// Non-zero digits were dropped.
+    is_truncated: bool,
+    exponent: i64,
+}
+
+impl Decimal {
+    pub(super) fn push_integer_digits(&mut self, digits: &[u8]) {
+        let kept = self.push_digits(digits);
+        self.exponent += (digits.len() - kept) as i64;
+    }
+
+    pub(super) fn push_fraction_digits(&mut self, digits: &[u8]) {
+        let kept = self.push_digits(digits);
+        self.exponent -= kept as i64;
+    }
+
+    /// Returns how many digits were kept (all but those after the first 19 significant ones).
+    fn push_digits(&mut self, digits: &[u8]) -> usize {
+        // (not counting leading zeros)
+        let leading_zeros = if self.mantissa == 0 {
+            digits.iter().take_while(|&&digit| digit == b'0').count()
+        } else {
+            0
+        };
+        let kept = digits
+            .len()
+            .min(leading_zeros + (MAX_DIGITS - self.digits) as usize);
+        for &digit in &digits[..kept] {
+            self.mantissa = self.mantissa * 10 + u64::from(digit - b'0');
+        }
+        self.digits += (kept - leading_zeros) as u32;
+        self.is_truncated |= digits[kept..].iter().any(|&digit| digit != b'0');
+        kept
+    }
+
+    pub(super) fn push_exponent_digits(&mut self, digits: &[u8], is_negative: bool) {
+        // (saturating: way beyond the exponents of finite non-zero numbers)
+        let exponent = digits.iter().fold(0, |exponent: i64, &digit| {
+            (exponent * 10 + i64::from(digit - b'0')).min(1 << 32)
+        });
+        self.exponent += if is_negative { -exponent } else { exponent };
+    }
+
+    /// The closest `f64` (infinite if too big), given the number's text (for the rare fallback).
+    pub(super) fn to_f64(&self, text: &str) -> f64 {
+        let (mantissa, exponent, is_truncated) = (self.mantissa, self.exponent, self.is_truncated);
+        let f = if mantissa == 0 {
+            0.0
+        } else if let Some(f) = fast_path(mantissa, exponent, is_truncated) {
+            f
+        } else {
+            let f = eisel_lemire(mantissa, exponent);
+            // exact if rounding the truncated digits up gives the same
+            match f.filter(|&f| !is_truncated || eisel_lemire(mantissa + 1, exponent) == Some(f)) {
+                Some(f) => f,
+                None => return text.parse().expect("valid f64 grammar"),
+            }
+        };
+        if self.is_negative {
+            -f
+        } else {
+            f
+        }
+    }
+}
+
+/// When both the mantissa and the power of ten are exact `f64`s, so a single operation rounds.
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn fast_path(mantissa: u64, exponent: i64, is_truncated: bool) -> Option<f64> {
+    if is_truncated {
+        return None;
+    }
+    if exponent == 0 {
+        // integer (rounded by the conversion)
+        return Some(mantissa as f64);
+    }
+    if mantissa > 1 << 53 || !(-22..=22).contains(&exponent) {
+        return None;
+    }
+    let power = EXACT_POWERS_OF_TEN[exponent.unsigned_abs() as usize];
+    Some(if exponent > 0 {
+        mantissa as f64 * power
+    } else {
+        mantissa as f64 / power
+    })
+}
+
+/// `None` when the 128-bit approximation of `5^q` is not enough to decide the rounding.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+fn eisel_lemire(mut w: u64, q: i64) -> Option<f64> {
+    if q < SMALLEST_POWER_OF_TEN {
+        return Some(0.0);
+    }
+    if q > LARGEST_POWER_OF_TEN {
+        return Some(f64::INFINITY);
+    }
+    // normalized, with the most significant bit set
+    let lz = w.leading_zeros();
+    w <<= lz;
+    let (lo, hi) = product_approx(q, w);
+    if lo == u64::MAX && !(-27..=55).contains(&q) {
+        return None;
+    }
+    let upper_bit = (hi >> 63) as i32;
+    let shift = upper_bit + 64 - MANTISSA_BITS - 3;
+    let mut mantissa = hi >> shift;
+    // binary exponent (biased): floor(log2(10^q)) + 63 + ...
+    let mut power2 = ((q as i32).wrapping_mul(152_170 + 65536) >> 16) + 63 + upper_bit
+        - lz as i32
+        - MINIMUM_EXPONENT;
+    if power2 <= 0 {
+        // subnormal (or zero)
+        if -power2 + 1 >= 64 {
+            return Some(0.0);
+        }
+        mantissa >>= -power2 + 1;
+        mantissa += mantissa & 1;
+        mantissa >>= 1;
+        power2 = i32::from(mantissa >= 1 << MANTISSA_BITS);
+        return Some(from_parts(mantissa, power2));
+    }
+    // exactly halfway between two floats: round down to even instead of up
+    if lo <= 1
+        && (MIN_EXPONENT_ROUND_TO_EVEN..=MAX_EXPONENT_ROUND_TO_EVEN).contains(&q)
+        && mantissa & 3 == 1
+        && mantissa << shift == hi
+    {
+        mantissa &= !1;
+    }
+    mantissa += mantissa & 1;
+    mantissa >>= 1;
+    if mantissa >= 2 << MANTISSA_BITS {
+        // rounding up overflowed
+        mantissa = 1 << MANTISSA_BITS;
+        power2 += 1;
+    }
+    if power2 >= INFINITE_POWER {
+        return Some(f64::INFINITY);
+    }
+    Some(from_parts(mantissa, power2))
+}
+
+/// The 128 most significant bits of `w × 5^q` (to the precision needed), as `(low, high)`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn product_approx(q: i64, w: u64) -> (u64, u64) {
+    const MASK: u64 = u64::MAX >> (MANTISSA_BITS + 3);
+    let power = POWERS_OF_FIVE[(q - SMALLEST_POWER_OF_TEN) as usize];
+    let (power_lo, power_hi) = (power as u64, (power >> 64) as u64);
+    let first = u128::from(w) * u128::from(power_hi);
+    let (mut lo, mut hi) = (first as u64, (first >> 64) as u64);
+    if hi & MASK == MASK {
+        // more precision needed from the lower half of the power
+        let second_hi = ((u128::from(w) * u128::from(power_lo)) >> 64) as u64;
+        lo = lo.wrapping_add(second_hi);
+        if second_hi > lo {
+            hi += 1;
+        }
+    }
+    (lo, hi)
+}
+
+/// From the mantissa (possibly with the hidden bit) and the biased exponent.
+#[allow(clippy::cast_sign_loss)]
+fn from_parts(mantissa: u64, power2: i32) -> f64 {
+    f64::from_bits((power2 as u64) << MANTISSA_BITS | mantissa & ((1 << MANTISSA_BITS) - 1))
+}
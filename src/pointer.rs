@@ -0,0 +1,204 @@
+use super::*;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A [JSON Pointer] (RFC 6901), identifying a specific value within a JSON document.
+///
+/// It is stored in its string form (e.g. `/foo/0/a~1b`), so it can be used as a map key.
+///
+/// [JSON Pointer]: https://www.rfc-editor.org/rfc/rfc6901
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::JsonPointer;
+///
+/// let mut pointer = JsonPointer::root();
+/// pointer.push_key("a/b");
+/// pointer.push_index(1);
+/// assert_eq!(pointer.as_str(), "/a~1b/1");
+/// assert_eq!(pointer.tokens().collect::<Vec<_>>(), ["a/b", "1"]);
+/// assert_eq!("/a~1b/1".parse(), Ok(pointer.clone()));
+///
+/// let value = json!({"a/b": [false, true]});
+/// assert_eq!(value.pointer(&pointer), Some(&json!(true)));
+/// pointer.pop();
+/// assert_eq!(value.pointer(&pointer), Some(&json!([false, true])));
+/// pointer.pop();
+/// assert_eq!(value.pointer(&pointer), Some(&value));
+/// assert_eq!(value.pointer(&"/nope".parse().unwrap()), None);
+/// ```
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Default)]
+pub struct JsonPointer(String);
+
+impl JsonPointer {
+    /// The empty pointer, identifying the whole document.
+    #[must_use]
+    #[inline]
+    pub fn root() -> Self {
+        Self(String::new())
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Appends an object key (escaping `~` and `/` as needed).
+    pub fn push_key(&mut self, key: &str) {
+        self.0.push('/');
+        for c in key.chars() {
+            match c {
+                '~' => self.0.push_str("~0"),
+                '/' => self.0.push_str("~1"),
+                _ => self.0.push(c),
+            }
+        }
+    }
+
+    /// Appends an array index.
+    pub fn push_index(&mut self, index: usize) {
+        use std::fmt::Write;
+        write!(self.0, "/{}", index).expect("infallible");
+    }
+
+    /// Appends all the reference tokens of another pointer.
+    pub fn append(&mut self, other: &JsonPointer) {
+        self.0.push_str(&other.0);
+    }
+
+    /// Removes the last reference token, returning `false` if already at the root.
+    pub fn pop(&mut self) -> bool {
+        match self.0.rfind('/') {
+            Some(slash) => {
+                self.0.truncate(slash);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns a new pointer with the object key or array index appended.
+    #[must_use]
+    pub fn join(&self, token: impl Into<PointerToken>) -> Self {
+        let mut pointer = self.clone();
+        match token.into() {
+            PointerToken::Key(key) => pointer.push_key(&key),
+            PointerToken::Index(index) => pointer.push_index(index),
+        }
+        pointer
+    }
+
+    /// Iterates over the (unescaped) reference tokens.
+    pub fn tokens(&self) -> impl Iterator<Item = String> + '_ {
+        self.0
+            .split('/')
+            .skip(1)
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+    }
+}
+
+/// A reference token for [`JsonPointer::join`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PointerToken {
+    Key(String),
+    Index(usize),
+}
+
+impl From<&str> for PointerToken {
+    #[inline]
+    fn from(key: &str) -> Self {
+        Self::Key(String::from(key))
+    }
+}
+
+impl From<usize> for PointerToken {
+    #[inline]
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl Display for JsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for JsonPointer {
+    type Err = InvalidJsonPointer;
+
+    /// Parses the string form of a JSON Pointer (not the URI fragment form).
+    ///
+    /// ```
+    /// use rustic_json::JsonPointer;
+    ///
+    /// assert!("".parse::<JsonPointer>().is_ok());
+    /// assert!("/a~0b/~1".parse::<JsonPointer>().is_ok());
+    /// assert!("a".parse::<JsonPointer>().is_err());
+    /// assert!("/a~2".parse::<JsonPointer>().is_err());
+    /// assert!("/a~".parse::<JsonPointer>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !(s.is_empty() || s.starts_with('/')) {
+            return Err(InvalidJsonPointer);
+        }
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c == '~' && !matches!(chars.next(), Some('0' | '1')) {
+                return Err(InvalidJsonPointer);
+            }
+        }
+        Ok(Self(String::from(s)))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct InvalidJsonPointer;
+
+impl Display for InvalidJsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid JSON pointer")
+    }
+}
+
+impl Error for InvalidJsonPointer {}
+
+impl Value {
+    /// Looks up the value identified by a JSON Pointer.
+    #[must_use]
+    pub fn pointer(&self, pointer: &JsonPointer) -> Option<&Self> {
+        pointer.tokens().try_fold(self, |value, token| match value {
+            Self::Array(arr) => arr.get(parse_index(&token)?),
+            Self::Object(obj) => obj.get(token.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Looks up the value identified by a JSON Pointer, mutably.
+    #[must_use]
+    pub fn pointer_mut(&mut self, pointer: &JsonPointer) -> Option<&mut Self> {
+        pointer.tokens().try_fold(self, |value, token| match value {
+            Self::Array(arr) => arr.get_mut(parse_index(&token)?),
+            Self::Object(obj) => obj.get_mut(token.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// Array index token: `0` or no leading zero (per RFC 6901).
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    if token.len() > 1 && token.starts_with('0') || !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
@@ -0,0 +1,323 @@
+use super::*;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// A compiled [JSON Schema] (draft 2020-12), reusable to validate any number of instances.
+///
+/// Supported keywords:
+/// - `type`, `enum`, `const`;
+/// - `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `multipleOf`;
+/// - `minLength`, `maxLength` (counted in chars);
+/// - `prefixItems`, `items`, `contains` (with `minContains`/`maxContains`),
+///   `minItems`, `maxItems`, `uniqueItems`;
+/// - `properties`, `additionalProperties`, `propertyNames`, `required`,
+///   `dependentRequired`, `minProperties`, `maxProperties`;
+/// - `allOf`, `anyOf`, `oneOf`, `not`, `if`/`then`/`else`;
+/// - `$ref` (to a JSON Pointer fragment or an `$anchor` within the same document), `$defs`.
+///
+/// Annotation-only keywords (`title`, `description`, `$comment`, `format`, ...)
+/// and unknown keywords are ignored,
+/// but keywords that would require a regex engine (`pattern`, `patternProperties`)
+/// or dynamic scoping (`$dynamicRef`) are rejected at compilation.
+///
+/// [JSON Schema]: https://json-schema.org/draft/2020-12/json-schema-validation
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::{Schema, ValidationErrorKind};
+///
+/// let schema = Schema::compile(&json!({
+///     "type": "object",
+///     "properties": {
+///         "name": {"type": "string", "minLength": 1},
+///         "port": {"$ref": "#/$defs/port"},
+///         "tags": {"type": "array", "items": {"enum": ["a", "b"]}, "uniqueItems": true}
+///     },
+///     "required": ["name", "port"],
+///     "$defs": {
+///         "port": {"type": "integer", "minimum": 1, "maximum": 65535}
+///     }
+/// }))
+/// .unwrap();
+///
+/// assert!(schema.is_valid(&json!({"name": "web", "port": 8080, "tags": ["a"]})));
+///
+/// let errors = schema.validate(&json!({"name": "", "port": 0.5, "tags": ["a", "c", "a"]})).unwrap_err();
+/// assert_eq!(
+///     errors
+///         .iter()
+///         .map(|error| (error.kind.clone(), error.instance_path.as_str(), error.schema_path.as_str()))
+///         .collect::<Vec<_>>(),
+///     [
+///         (ValidationErrorKind::MinLength, "/name", "/properties/name/minLength"),
+///         (ValidationErrorKind::Type, "/port", "/$defs/port/type"),
+///         (ValidationErrorKind::Minimum, "/port", "/$defs/port/minimum"),
+///         (ValidationErrorKind::Enum, "/tags/1", "/properties/tags/items/enum"),
+///         (ValidationErrorKind::UniqueItems, "/tags", "/properties/tags/uniqueItems"),
+///     ]
+/// );
+/// assert_eq!(
+///     errors[1].to_string(),
+///     r#"value does not match "type" at "/port" (schema: "/$defs/port/type")"#
+/// );
+///
+/// let errors = schema.validate(&json!({"port": 80})).unwrap_err();
+/// assert_eq!(errors[0].kind, ValidationErrorKind::Required("name".into()));
+/// assert_eq!(errors[0].instance_path.as_str(), "");
+/// ```
+///
+/// Combinators and conditionals:
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::Schema;
+///
+/// let schema = Schema::compile(&json!({
+///     "oneOf": [{"minimum": 10}, {"multipleOf": 3}],
+///     "not": {"const": 0},
+///     "if": {"minimum": 100}, "then": {"multipleOf": 10}, "else": {"maximum": 50}
+/// }))
+/// .unwrap();
+/// assert!(schema.is_valid(&json!(11)));
+/// assert!(schema.is_valid(&json!(3)));
+/// assert!(schema.is_valid(&json!(110)));
+/// assert!(!schema.is_valid(&json!(12))); // both `oneOf`
+/// assert!(!schema.is_valid(&json!(7))); // neither
+/// assert!(!schema.is_valid(&json!(0))); // `not`
+/// assert!(!schema.is_valid(&json!(105))); // `then`
+/// assert!(!schema.is_valid(&json!(70))); // `else`
+/// ```
+///
+/// Recursive schemas:
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::Schema;
+///
+/// let tree = Schema::compile(&json!({
+///     "$anchor": "node",
+///     "type": "object",
+///     "properties": {"children": {"type": "array", "items": {"$ref": "#node"}}},
+///     "additionalProperties": false
+/// }))
+/// .unwrap();
+/// assert!(tree.is_valid(&json!({"children": [{}, {"children": []}]})));
+/// let errors = tree.validate(&json!({"children": [{"x": 1}]})).unwrap_err();
+/// assert_eq!(errors[0].instance_path.as_str(), "/children/0/x");
+/// assert_eq!(errors[0].schema_path.as_str(), "/additionalProperties");
+///
+/// // only the schema's recursion is checked, not the instance's depth
+/// let nested = Schema::compile(&json!({"type": "array", "items": {"$ref": "#"}})).unwrap();
+/// let deep = (0..600).fold(json!([]), |value, _| json!([value]));
+/// assert!(nested.is_valid(&deep));
+/// ```
+///
+/// A cycle of `$ref`s not consuming the instance is reported as an error,
+/// and each subschema is evaluated once per location in the instance
+/// (so combinators over recursive references take linear time):
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::{Schema, ValidationErrorKind};
+///
+/// let schema = Schema::compile(&json!({"anyOf": [{"$ref": "#"}, {"$ref": "#"}]})).unwrap();
+/// let errors = schema.validate(&json!([1, [2, [3]]])).unwrap_err();
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].kind, ValidationErrorKind::AnyOf);
+///
+/// let schema = Schema::compile(&json!({"$ref": "#"})).unwrap();
+/// let errors = schema.validate(&json!(1)).unwrap_err();
+/// assert_eq!(errors[0].kind, ValidationErrorKind::RefCycle);
+///
+/// // 2^100 paths through the `anyOf`s
+/// let mut defs = json!({"d100": {"type": "string"}});
+/// for i in 0..100 {
+///     let next = json!({"$ref": (format!("#/$defs/d{}", i + 1))});
+///     defs[format!("d{}", i)] = json!({"anyOf": [(next.clone()), {"not": next, "type": "null"}]});
+/// }
+/// let schema = Schema::compile(&json!({"$ref": "#/$defs/d0", "$defs": defs})).unwrap();
+/// assert!(schema.is_valid(&json!("a")));
+/// assert!(!schema.is_valid(&json!(1)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Schema {
+    nodes: Vec<helper::Node>,
+}
+
+impl Schema {
+    /// Compiles a schema document (an object or a boolean).
+    ///
+    /// # Errors
+    ///
+    /// If the document is not a valid schema, or uses an unsupported keyword.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{Schema, SchemaError, SchemaErrorKind};
+    ///
+    /// let error = Schema::compile(&json!({"items": {"type": []}})).unwrap_err();
+    /// assert_eq!(error, SchemaError { kind: SchemaErrorKind::InvalidKeyword, schema_path: "/items/type".parse().unwrap() });
+    /// assert_eq!(error.to_string(), r#"invalid keyword value at "/items/type""#);
+    ///
+    /// // values of `enum`, `const`, ... are not schemas (but properties can have any name)
+    /// let error = Schema::compile(&json!({"$ref": "#a", "const": {"$anchor": "a"}})).unwrap_err();
+    /// assert_eq!(error.kind, SchemaErrorKind::UnresolvedRef);
+    /// assert!(Schema::compile(&json!({"$ref": "#a", "properties": {"const": {"$anchor": "a"}}})).is_ok());
+    /// ```
+    pub fn compile(schema: &Value) -> Result<Self, SchemaError> {
+        helper::compile(schema).map(|nodes| Self { nodes })
+    }
+
+    /// Validates an instance, returning all errors if invalid
+    /// (in the order of the keywords that failed, depth first).
+    pub fn validate(&self, instance: &Value) -> Result<(), Vec<ValidationError>> {
+        let errors = helper::validate(&self.nodes, instance);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    #[must_use]
+    pub fn is_valid(&self, instance: &Value) -> bool {
+        self.validate(instance).is_ok()
+    }
+}
+
+/// Error from [`Schema::compile`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SchemaError {
+    pub kind: SchemaErrorKind,
+    /// Location in the schema document.
+    pub schema_path: JsonPointer,
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at \"{}\"", self.kind, self.schema_path)
+    }
+}
+
+impl Error for SchemaError {}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SchemaErrorKind {
+    /// Neither an object nor a boolean.
+    NotASchema,
+    /// Keyword with a value of the wrong type (or out of range).
+    InvalidKeyword,
+    /// `$ref` to a missing location, or outside of the document.
+    UnresolvedRef,
+    UnsupportedKeyword,
+}
+
+impl Display for SchemaErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NotASchema => "not a schema",
+            Self::InvalidKeyword => "invalid keyword value",
+            Self::UnresolvedRef => "unresolved reference",
+            Self::UnsupportedKeyword => "unsupported keyword",
+        })
+    }
+}
+
+/// Error from [`Schema::validate`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ValidationError {
+    pub kind: ValidationErrorKind,
+    /// Location of the offending value in the instance.
+    pub instance_path: JsonPointer,
+    /// Location of the failing keyword in the schema document
+    /// (after following any `$ref`).
+    pub schema_path: JsonPointer,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at \"{}\" (schema: \"{}\")",
+            self.kind, self.instance_path, self.schema_path
+        )
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Mostly named after the failing keyword.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ValidationErrorKind {
+    /// The `false` schema.
+    False,
+    Type,
+    Enum,
+    Const,
+    Minimum,
+    Maximum,
+    ExclusiveMinimum,
+    ExclusiveMaximum,
+    MultipleOf,
+    MinLength,
+    MaxLength,
+    MinItems,
+    MaxItems,
+    UniqueItems,
+    Contains,
+    MinProperties,
+    MaxProperties,
+    /// With the missing property.
+    Required(Str),
+    /// With the missing property.
+    DependentRequired(Str),
+    AnyOf,
+    OneOf,
+    Not,
+    /// A cycle of `$ref`s not consuming the instance (e.g. `{"$ref": "#"}`).
+    RefCycle,
+}
+
+impl Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keyword = match self {
+            Self::False => return f.write_str("value not allowed"),
+            Self::Required(property) => {
+                return write!(f, "missing required property {:?}", property)
+            }
+            Self::DependentRequired(property) => {
+                return write!(f, "missing dependent required property {:?}", property)
+            }
+            Self::RefCycle => return f.write_str("value matched by a cycle of \"$ref\"s"),
+            Self::Type => "type",
+            Self::Enum => "enum",
+            Self::Const => "const",
+            Self::Minimum => "minimum",
+            Self::Maximum => "maximum",
+            Self::ExclusiveMinimum => "exclusiveMinimum",
+            Self::ExclusiveMaximum => "exclusiveMaximum",
+            Self::MultipleOf => "multipleOf",
+            Self::MinLength => "minLength",
+            Self::MaxLength => "maxLength",
+            Self::MinItems => "minItems",
+            Self::MaxItems => "maxItems",
+            Self::UniqueItems => "uniqueItems",
+            Self::Contains => "contains",
+            Self::MinProperties => "minProperties",
+            Self::MaxProperties => "maxProperties",
+            Self::AnyOf => "anyOf",
+            Self::OneOf => "oneOf",
+            Self::Not => "not",
+        };
+        write!(f, "value does not match {:?}", keyword)
+    }
+}
+
+mod helper;
+mod infer;
+
+pub use infer::InferOptions;
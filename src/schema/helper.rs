@@ -0,0 +1,772 @@
+use super::*;
+use std::collections::{BTreeMap, HashMap};
+
+type CompileResult<T> = Result<T, SchemaError>;
+
+use SchemaErrorKind as SK;
+use ValidationErrorKind as VK;
+
+#[derive(Debug, Clone)]
+pub(super) struct Node {
+    path: JsonPointer,
+    /// `Some` for a boolean schema.
+    always: Option<bool>,
+    keywords: Vec<Keyword>,
+}
+
+/// Subschemas are indices into the nodes.
+#[derive(Debug, Clone)]
+enum Keyword {
+    Ref(usize),
+    Type(Vec<JsonType>),
+    Enum(Vec<Value>),
+    Const(Value),
+    Minimum(f64),
+    Maximum(f64),
+    ExclusiveMinimum(f64),
+    ExclusiveMaximum(f64),
+    MultipleOf(f64),
+    MinLength(usize),
+    MaxLength(usize),
+    PrefixItems(Vec<usize>),
+    Items {
+        prefix_len: usize,
+        schema: usize,
+    },
+    Contains {
+        schema: usize,
+        min: usize,
+        max: Option<usize>,
+    },
+    MinItems(usize),
+    MaxItems(usize),
+    UniqueItems,
+    Properties(Vec<(Str, usize)>),
+    AdditionalProperties {
+        known: Vec<Str>,
+        schema: usize,
+    },
+    PropertyNames(usize),
+    Required(Vec<Str>),
+    DependentRequired(Vec<(Str, Vec<Str>)>),
+    DependentSchemas(Vec<(Str, usize)>),
+    MinProperties(usize),
+    MaxProperties(usize),
+    AllOf(Vec<usize>),
+    AnyOf(Vec<usize>),
+    OneOf(Vec<usize>),
+    Not(usize),
+    If {
+        condition: usize,
+        then: Option<usize>,
+        otherwise: Option<usize>,
+    },
+}
+
+impl Keyword {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Ref(_) => "$ref",
+            Self::Type(_) => "type",
+            Self::Enum(_) => "enum",
+            Self::Const(_) => "const",
+            Self::Minimum(_) => "minimum",
+            Self::Maximum(_) => "maximum",
+            Self::ExclusiveMinimum(_) => "exclusiveMinimum",
+            Self::ExclusiveMaximum(_) => "exclusiveMaximum",
+            Self::MultipleOf(_) => "multipleOf",
+            Self::MinLength(_) => "minLength",
+            Self::MaxLength(_) => "maxLength",
+            Self::PrefixItems(_) => "prefixItems",
+            Self::Items { .. } => "items",
+            Self::Contains { .. } => "contains",
+            Self::MinItems(_) => "minItems",
+            Self::MaxItems(_) => "maxItems",
+            Self::UniqueItems => "uniqueItems",
+            Self::Properties(_) => "properties",
+            Self::AdditionalProperties { .. } => "additionalProperties",
+            Self::PropertyNames(_) => "propertyNames",
+            Self::Required(_) => "required",
+            Self::DependentRequired(_) => "dependentRequired",
+            Self::DependentSchemas(_) => "dependentSchemas",
+            Self::MinProperties(_) => "minProperties",
+            Self::MaxProperties(_) => "maxProperties",
+            Self::AllOf(_) => "allOf",
+            Self::AnyOf(_) => "anyOf",
+            Self::OneOf(_) => "oneOf",
+            Self::Not(_) => "not",
+            Self::If { .. } => "if",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum JsonType {
+    Null,
+    Boolean,
+    Object,
+    Array,
+    Number,
+    String,
+    Integer,
+}
+
+impl JsonType {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "null" => Self::Null,
+            "boolean" => Self::Boolean,
+            "object" => Self::Object,
+            "array" => Self::Array,
+            "number" => Self::Number,
+            "string" => Self::String,
+            "integer" => Self::Integer,
+            _ => return None,
+        })
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match (self, value) {
+            (Self::Null, Value::Null)
+            | (Self::Boolean, Value::Boolean(_))
+            | (Self::Object, Value::Object(_))
+            | (Self::Array, Value::Array(_))
+            | (Self::Number, Value::Number(_))
+            | (Self::String, Value::String(_)) => true,
+            (Self::Integer, Value::Number(num)) => num.get().fract() == 0.0,
+            _ => false,
+        }
+    }
+}
+
+const UNSUPPORTED_KEYWORDS: [&str; 6] = [
+    "pattern",
+    "patternProperties",
+    "$dynamicRef",
+    "$recursiveRef",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+
+pub(super) fn compile(schema: &Value) -> CompileResult<Vec<Node>> {
+    let mut compiler = Compiler {
+        root: schema,
+        nodes: Vec::new(),
+        compiled: BTreeMap::new(),
+        anchors: BTreeMap::new(),
+    };
+    collect_anchors(
+        schema,
+        &mut JsonPointer::root(),
+        false,
+        &mut compiler.anchors,
+    );
+    let root = compiler.compile_at(&JsonPointer::root())?;
+    debug_assert_eq!(root, 0);
+    Ok(compiler.nodes)
+}
+
+/// Keywords whose values are instances, not schemas (so without anchors).
+const INSTANCE_KEYWORDS: [&str; 4] = ["enum", "const", "default", "examples"];
+
+/// Keywords whose values are objects of schemas (with any keys, e.g. `enum`).
+const SCHEMA_MAP_KEYWORDS: [&str; 5] = [
+    "properties",
+    "patternProperties",
+    "dependentSchemas",
+    "$defs",
+    "definitions",
+];
+
+/// `is_schema_map` for the value of one of the `SCHEMA_MAP_KEYWORDS`.
+fn collect_anchors(
+    value: &Value,
+    path: &mut JsonPointer,
+    is_schema_map: bool,
+    anchors: &mut BTreeMap<Str, JsonPointer>,
+) {
+    match value {
+        Value::Object(obj) => {
+            if let (false, Some(Value::String(anchor))) = (is_schema_map, obj.get("$anchor")) {
+                anchors.insert(anchor.clone(), path.clone());
+            }
+            for (key, value) in obj {
+                if !is_schema_map && INSTANCE_KEYWORDS.contains(&&**key) {
+                    continue;
+                }
+                let is_schema_map = !is_schema_map && SCHEMA_MAP_KEYWORDS.contains(&&**key);
+                path.push_key(key);
+                collect_anchors(value, path, is_schema_map, anchors);
+                path.pop();
+            }
+        }
+        Value::Array(arr) => {
+            for (i, element) in arr.iter().enumerate() {
+                path.push_index(i);
+                collect_anchors(element, path, false, anchors);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+struct Compiler<'s> {
+    root: &'s Value,
+    nodes: Vec<Node>,
+    /// Each location is compiled once, which also makes recursive `$ref`s work.
+    compiled: BTreeMap<JsonPointer, usize>,
+    anchors: BTreeMap<Str, JsonPointer>,
+}
+
+impl Compiler<'_> {
+    fn compile_at(&mut self, path: &JsonPointer) -> CompileResult<usize> {
+        if let Some(&index) = self.compiled.get(path) {
+            return Ok(index);
+        }
+        let root = self.root;
+        let Some(value) = root.pointer(path) else {
+            return Err(error(path, SK::UnresolvedRef));
+        };
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            path: path.clone(),
+            always: None,
+            keywords: Vec::new(),
+        });
+        self.compiled.insert(path.clone(), index);
+        match value {
+            Value::Boolean(b) => self.nodes[index].always = Some(*b),
+            Value::Object(obj) => self.nodes[index].keywords = self.compile_keywords(path, obj)?,
+            _ => return Err(error(path, SK::NotASchema)),
+        }
+        Ok(index)
+    }
+
+    fn compile_keywords(&mut self, path: &JsonPointer, obj: &Obj) -> CompileResult<Vec<Keyword>> {
+        if let Some(&unsupported) = UNSUPPORTED_KEYWORDS.iter().find(|&&k| obj.contains_key(k)) {
+            return Err(error(&path.join(unsupported), SK::UnsupportedKeyword));
+        }
+        let mut keywords = Vec::new();
+        let at = |name: &str| path.join(name);
+
+        if let Some(value) = obj.get("$ref") {
+            let Value::String(reference) = value else {
+                return Err(error(&at("$ref"), SK::InvalidKeyword));
+            };
+            let target = self
+                .resolve_ref(reference)
+                .ok_or_else(|| error(&at("$ref"), SK::UnresolvedRef))?;
+            keywords.push(Keyword::Ref(self.compile_at(&target)?));
+        }
+        if let Some(value) = obj.get("type") {
+            let names = match value {
+                Value::String(name) => vec![name.clone()],
+                _ => strings(value, &at("type"))?,
+            };
+            if names.is_empty() {
+                return Err(error(&at("type"), SK::InvalidKeyword));
+            }
+            let types = names
+                .iter()
+                .map(|name| JsonType::from_name(name))
+                .collect::<Option<_>>()
+                .ok_or_else(|| error(&at("type"), SK::InvalidKeyword))?;
+            keywords.push(Keyword::Type(types));
+        }
+        if let Some(value) = obj.get("enum") {
+            let Value::Array(values) = value else {
+                return Err(error(&at("enum"), SK::InvalidKeyword));
+            };
+            keywords.push(Keyword::Enum(values.clone()));
+        }
+        if let Some(value) = obj.get("const") {
+            keywords.push(Keyword::Const(value.clone()));
+        }
+        macro_rules! simple {
+            ($($name:literal => $variant:ident($parse:ident),)+) => {$(
+                if let Some(value) = obj.get($name) {
+                    keywords.push(Keyword::$variant($parse(value, &at($name))?));
+                }
+            )+};
+        }
+        simple! {
+            "minimum" => Minimum(number),
+            "maximum" => Maximum(number),
+            "exclusiveMinimum" => ExclusiveMinimum(number),
+            "exclusiveMaximum" => ExclusiveMaximum(number),
+            "multipleOf" => MultipleOf(positive_number),
+            "minLength" => MinLength(count),
+            "maxLength" => MaxLength(count),
+        }
+        let mut prefix_len = 0;
+        if let Some(value) = obj.get("prefixItems") {
+            let schemas = self.compile_all(&at("prefixItems"), value)?;
+            prefix_len = schemas.len();
+            keywords.push(Keyword::PrefixItems(schemas));
+        }
+        if obj.contains_key("items") {
+            let schema = self.compile_at(&at("items"))?;
+            keywords.push(Keyword::Items { prefix_len, schema });
+        }
+        if obj.contains_key("contains") {
+            let schema = self.compile_at(&at("contains"))?;
+            let min = obj
+                .get("minContains")
+                .map(|value| count(value, &at("minContains")))
+                .transpose()?
+                .unwrap_or(1);
+            let max = obj
+                .get("maxContains")
+                .map(|value| count(value, &at("maxContains")))
+                .transpose()?;
+            keywords.push(Keyword::Contains { schema, min, max });
+        }
+        simple! {
+            "minItems" => MinItems(count),
+            "maxItems" => MaxItems(count),
+        }
+        match obj.get("uniqueItems") {
+            None | Some(Value::Boolean(false)) => {}
+            Some(Value::Boolean(true)) => keywords.push(Keyword::UniqueItems),
+            Some(_) => return Err(error(&at("uniqueItems"), SK::InvalidKeyword)),
+        }
+        let mut known = Vec::new();
+        if let Some(value) = obj.get("properties") {
+            let properties = self.compile_each(&at("properties"), value)?;
+            known = properties.iter().map(|(key, _)| key.clone()).collect();
+            keywords.push(Keyword::Properties(properties));
+        }
+        if obj.contains_key("additionalProperties") {
+            let schema = self.compile_at(&at("additionalProperties"))?;
+            keywords.push(Keyword::AdditionalProperties { known, schema });
+        }
+        if obj.contains_key("propertyNames") {
+            let schema = self.compile_at(&at("propertyNames"))?;
+            keywords.push(Keyword::PropertyNames(schema));
+        }
+        simple! {
+            "required" => Required(strings),
+        }
+        if let Some(value) = obj.get("dependentRequired") {
+            let Value::Object(dependencies) = value else {
+                return Err(error(&at("dependentRequired"), SK::InvalidKeyword));
+            };
+            let dependencies = dependencies
+                .iter()
+                .map(|(key, value)| {
+                    Ok((
+                        key.clone(),
+                        strings(value, &at("dependentRequired").join(&**key))?,
+                    ))
+                })
+                .collect::<CompileResult<_>>()?;
+            keywords.push(Keyword::DependentRequired(dependencies));
+        }
+        if let Some(value) = obj.get("dependentSchemas") {
+            let dependencies = self.compile_each(&at("dependentSchemas"), value)?;
+            keywords.push(Keyword::DependentSchemas(dependencies));
+        }
+        simple! {
+            "minProperties" => MinProperties(count),
+            "maxProperties" => MaxProperties(count),
+        }
+        for (name, variant) in [
+            ("allOf", Keyword::AllOf as fn(_) -> _),
+            ("anyOf", Keyword::AnyOf),
+            ("oneOf", Keyword::OneOf),
+        ] {
+            if let Some(value) = obj.get(name) {
+                let schemas = self.compile_all(&at(name), value)?;
+                if schemas.is_empty() {
+                    return Err(error(&at(name), SK::InvalidKeyword));
+                }
+                keywords.push(variant(schemas));
+            }
+        }
+        if obj.contains_key("not") {
+            keywords.push(Keyword::Not(self.compile_at(&at("not"))?));
+        }
+        if obj.contains_key("if") {
+            let condition = self.compile_at(&at("if"))?;
+            let mut compile_if_present = |name| {
+                obj.contains_key(name)
+                    .then(|| self.compile_at(&at(name)))
+                    .transpose()
+            };
+            let then = compile_if_present("then")?;
+            let otherwise = compile_if_present("else")?;
+            keywords.push(Keyword::If {
+                condition,
+                then,
+                otherwise,
+            });
+        }
+        Ok(keywords)
+    }
+
+    /// Compiles an array of subschemas.
+    fn compile_all(&mut self, path: &JsonPointer, value: &Value) -> CompileResult<Vec<usize>> {
+        let Value::Array(arr) = value else {
+            return Err(error(path, SK::InvalidKeyword));
+        };
+        (0..arr.len())
+            .map(|i| self.compile_at(&path.join(i)))
+            .collect()
+    }
+
+    /// Compiles an object of subschemas.
+    fn compile_each(
+        &mut self,
+        path: &JsonPointer,
+        value: &Value,
+    ) -> CompileResult<Vec<(Str, usize)>> {
+        let Value::Object(obj) = value else {
+            return Err(error(path, SK::InvalidKeyword));
+        };
+        obj.keys()
+            .map(|key| Ok((key.clone(), self.compile_at(&path.join(&**key))?)))
+            .collect()
+    }
+
+    /// Supports a JSON Pointer fragment or an `$anchor`, optionally prefixed with the root `$id`.
+    fn resolve_ref(&self, reference: &str) -> Option<JsonPointer> {
+        let (base, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        if !base.is_empty() {
+            let Value::Object(root) = self.root else {
+                return None;
+            };
+            let Some(Value::String(id)) = root.get("$id") else {
+                return None;
+            };
+            if base != id.trim_end_matches('#') {
+                return None;
+            }
+        }
+        let fragment = percent_decode(fragment)?;
+        if fragment.is_empty() || fragment.starts_with('/') {
+            fragment.parse().ok()
+        } else {
+            self.anchors.get(fragment.as_str()).cloned()
+        }
+    }
+}
+
+fn percent_decode(str: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(str.len());
+    let mut iter = str.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let high = char::from(iter.next()?).to_digit(16)?;
+            let low = char::from(iter.next()?).to_digit(16)?;
+            bytes.push(u8::try_from(high << 4 | low).expect("fits in"));
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn error(path: &JsonPointer, kind: SK) -> SchemaError {
+    SchemaError {
+        kind,
+        schema_path: path.clone(),
+    }
+}
+
+fn number(value: &Value, path: &JsonPointer) -> CompileResult<f64> {
+    match value {
+        Value::Number(num) => Ok(num.get()),
+        _ => Err(error(path, SK::InvalidKeyword)),
+    }
+}
+
+fn positive_number(value: &Value, path: &JsonPointer) -> CompileResult<f64> {
+    match number(value, path)? {
+        f if f > 0.0 => Ok(f),
+        _ => Err(error(path, SK::InvalidKeyword)),
+    }
+}
+
+/// Non-negative integer.
+fn count(value: &Value, path: &JsonPointer) -> CompileResult<usize> {
+    match number(value, path)? {
+        // (saturating cast)
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        f if f >= 0.0 && f.fract() == 0.0 => Ok(f as usize),
+        _ => Err(error(path, SK::InvalidKeyword)),
+    }
+}
+
+fn strings(value: &Value, path: &JsonPointer) -> CompileResult<Vec<Str>> {
+    let Value::Array(arr) = value else {
+        return Err(error(path, SK::InvalidKeyword));
+    };
+    arr.iter()
+        .map(|element| match element {
+            Value::String(str) => Ok(str.clone()),
+            _ => Err(error(path, SK::InvalidKeyword)),
+        })
+        .collect()
+}
+
+pub(super) fn validate(nodes: &[Node], instance: &Value) -> Vec<ValidationError> {
+    let mut validator = Validator {
+        nodes,
+        path: JsonPointer::root(),
+        errors: Vec::new(),
+        active: Vec::new(),
+        validity: HashMap::new(),
+        property_name: None,
+    };
+    validator.validate(0, instance);
+    validator.errors
+}
+
+struct Validator<'n> {
+    nodes: &'n [Node],
+    /// Current location in the instance.
+    path: JsonPointer,
+    errors: Vec<ValidationError>,
+    /// Nodes being evaluated at the current location, to detect `$ref` cycles.
+    active: Vec<usize>,
+    /// Whether each node was valid at each location (see `location`), so that combinators
+    /// over the same subschemas (e.g. `anyOf` with recursive `$ref`s) are evaluated once.
+    validity: HashMap<(usize, usize), bool>,
+    /// Location standing for a property name (see `location`).
+    property_name: Option<usize>,
+}
+
+impl Validator<'_> {
+    fn validate(&mut self, index: usize, instance: &Value) {
+        let nodes = self.nodes;
+        let node = &nodes[index];
+        match node.always {
+            Some(true) => return,
+            Some(false) => return self.error(node.path.clone(), VK::False),
+            None => {}
+        }
+        let key = (index, self.location(instance));
+        if self.validity.get(&key) == Some(&true) {
+            return;
+        }
+        if self.active.contains(&index) {
+            return self.error(node.path.clone(), VK::RefCycle);
+        }
+        self.active.push(index);
+        let len = self.errors.len();
+        for keyword in &node.keywords {
+            self.validate_keyword(node, keyword, instance);
+        }
+        self.active.pop();
+        self.validity.insert(key, self.errors.len() == len);
+    }
+
+    /// Like `validate` but only reports whether there were errors, discarding them.
+    fn is_valid(&mut self, index: usize, instance: &Value) -> bool {
+        if let Some(&is_valid) = self.validity.get(&(index, self.location(instance))) {
+            return is_valid;
+        }
+        let len = self.errors.len();
+        self.validate(index, instance);
+        let is_valid = self.errors.len() == len;
+        self.errors.truncate(len);
+        is_valid
+    }
+
+    /// Identifies the instance by its address (as it is borrowed for the whole validation),
+    /// except for a property name, which is a temporary string (identified by its key).
+    fn location(&self, instance: &Value) -> usize {
+        self.property_name
+            .unwrap_or(instance as *const Value as usize)
+    }
+
+    fn validate_child(&mut self, index: usize, token: impl Into<PointerToken>, instance: &Value) {
+        match token.into() {
+            PointerToken::Key(key) => self.path.push_key(&key),
+            PointerToken::Index(i) => self.path.push_index(i),
+        }
+        // (cycles are only within a location)
+        let active = std::mem::take(&mut self.active);
+        self.validate(index, instance);
+        self.active = active;
+        self.path.pop();
+    }
+
+    fn error(&mut self, schema_path: JsonPointer, kind: VK) {
+        self.errors.push(ValidationError {
+            kind,
+            instance_path: self.path.clone(),
+            schema_path,
+        });
+    }
+
+    fn validate_keyword(&mut self, node: &Node, keyword: &Keyword, instance: &Value) {
+        let fail =
+            |validator: &mut Self, kind| validator.error(node.path.join(keyword.name()), kind);
+        let num = match instance {
+            Value::Number(num) => Some(num.get()),
+            _ => None,
+        };
+        let len = match instance {
+            Value::String(str) => Some(str.chars().count()),
+            _ => None,
+        };
+        let arr = match instance {
+            Value::Array(arr) => Some(arr),
+            _ => None,
+        };
+        let obj = match instance {
+            Value::Object(obj) => Some(obj),
+            _ => None,
+        };
+        match (keyword, num, len, arr, obj) {
+            (Keyword::Ref(schema), ..) => self.validate(*schema, instance),
+            (Keyword::Type(types), ..) if !types.iter().any(|typ| typ.matches(instance)) => {
+                fail(self, VK::Type);
+            }
+            (Keyword::Enum(values), ..) if !values.contains(instance) => fail(self, VK::Enum),
+            (Keyword::Const(value), ..) if instance != value => fail(self, VK::Const),
+            (Keyword::Minimum(min), Some(f), ..) if f < *min => fail(self, VK::Minimum),
+            (Keyword::Maximum(max), Some(f), ..) if f > *max => fail(self, VK::Maximum),
+            (Keyword::ExclusiveMinimum(min), Some(f), ..) if f <= *min => {
+                fail(self, VK::ExclusiveMinimum);
+            }
+            (Keyword::ExclusiveMaximum(max), Some(f), ..) if f >= *max => {
+                fail(self, VK::ExclusiveMaximum);
+            }
+            (Keyword::MultipleOf(divisor), Some(f), ..) => {
+                // tolerate rounding errors (e.g. 0.3 / 0.1)
+                let quotient = f / divisor;
+                if (quotient - quotient.round()).abs() > quotient.abs() * 4.0 * f64::EPSILON {
+                    fail(self, VK::MultipleOf);
+                }
+            }
+            (Keyword::MinLength(min), _, Some(len), ..) if len < *min => fail(self, VK::MinLength),
+            (Keyword::MaxLength(max), _, Some(len), ..) if len > *max => fail(self, VK::MaxLength),
+            (Keyword::PrefixItems(schemas), _, _, Some(arr), _) => {
+                for (i, (&schema, element)) in schemas.iter().zip(arr).enumerate() {
+                    self.validate_child(schema, i, element);
+                }
+            }
+            (Keyword::Items { prefix_len, schema }, _, _, Some(arr), _) => {
+                for (i, element) in arr.iter().enumerate().skip(*prefix_len) {
+                    self.validate_child(*schema, i, element);
+                }
+            }
+            (Keyword::Contains { schema, min, max }, _, _, Some(arr), _) => {
+                let matches = arr
+                    .iter()
+                    .filter(|element| self.is_valid(*schema, element))
+                    .count();
+                if matches < *min || max.is_some_and(|max| matches > max) {
+                    fail(self, VK::Contains);
+                }
+            }
+            (Keyword::MinItems(min), _, _, Some(arr), _) if arr.len() < *min => {
+                fail(self, VK::MinItems)
+            }
+            (Keyword::MaxItems(max), _, _, Some(arr), _) if arr.len() > *max => {
+                fail(self, VK::MaxItems)
+            }
+            (Keyword::UniqueItems, _, _, Some(arr), _)
+                if arr.iter().enumerate().any(|(i, a)| arr[..i].contains(a)) =>
+            {
+                fail(self, VK::UniqueItems);
+            }
+            (Keyword::Properties(properties), .., Some(obj)) => {
+                for (key, schema) in properties {
+                    if let Some(value) = obj.get(key) {
+                        self.validate_child(*schema, &**key, value);
+                    }
+                }
+            }
+            (Keyword::AdditionalProperties { known, schema }, .., Some(obj)) => {
+                for (key, value) in obj {
+                    if !known.contains(key) {
+                        self.validate_child(*schema, &**key, value);
+                    }
+                }
+            }
+            (Keyword::PropertyNames(schema), .., Some(obj)) => {
+                for key in obj.keys() {
+                    self.property_name = Some(key as *const Str as usize);
+                    self.validate_child(*schema, &**key, &Value::String(key.clone()));
+                    self.property_name = None;
+                }
+            }
+            (Keyword::Required(names), .., Some(obj)) => {
+                for name in names {
+                    if !obj.contains_key(name) {
+                        fail(self, VK::Required(name.clone()));
+                    }
+                }
+            }
+            (Keyword::DependentRequired(dependencies), .., Some(obj)) => {
+                for (trigger, names) in dependencies {
+                    if obj.contains_key(trigger) {
+                        for name in names {
+                            if !obj.contains_key(name) {
+                                fail(self, VK::DependentRequired(name.clone()));
+                            }
+                        }
+                    }
+                }
+            }
+            (Keyword::DependentSchemas(dependencies), .., Some(obj)) => {
+                for (trigger, schema) in dependencies {
+                    if obj.contains_key(trigger) {
+                        self.validate(*schema, instance);
+                    }
+                }
+            }
+            (Keyword::MinProperties(min), .., Some(obj)) if obj.len() < *min => {
+                fail(self, VK::MinProperties);
+            }
+            (Keyword::MaxProperties(max), .., Some(obj)) if obj.len() > *max => {
+                fail(self, VK::MaxProperties);
+            }
+            (Keyword::AllOf(schemas), ..) => {
+                for &schema in schemas {
+                    self.validate(schema, instance);
+                }
+            }
+            (Keyword::AnyOf(schemas), ..)
+                if !schemas
+                    .iter()
+                    .any(|&schema| self.is_valid(schema, instance)) =>
+            {
+                fail(self, VK::AnyOf);
+            }
+            (Keyword::OneOf(schemas), ..) => {
+                let matches = schemas
+                    .iter()
+                    .filter(|&&schema| self.is_valid(schema, instance))
+                    .count();
+                if matches != 1 {
+                    fail(self, VK::OneOf);
+                }
+            }
+            (Keyword::Not(schema), ..) if self.is_valid(*schema, instance) => fail(self, VK::Not),
+            (
+                Keyword::If {
+                    condition,
+                    then,
+                    otherwise,
+                },
+                ..,
+            ) => {
+                let branch = if self.is_valid(*condition, instance) {
+                    then
+                } else {
+                    otherwise
+                };
+                if let Some(schema) = branch {
+                    self.validate(*schema, instance);
+                }
+            }
+            // not applicable to this type of instance, or passing
+            _ => {}
+        }
+    }
+}
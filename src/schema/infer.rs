@@ -0,0 +1,216 @@
+use super::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Options for [`Schema::infer`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InferOptions {
+    /// Maximum number of distinct strings for an `enum` (0 to disable).
+    pub max_enum_values: usize,
+    /// Minimum number of string samples (at a given location) for an `enum`.
+    pub min_enum_samples: usize,
+}
+
+impl Default for InferOptions {
+    fn default() -> Self {
+        Self {
+            max_enum_values: 5,
+            min_enum_samples: 10,
+        }
+    }
+}
+
+impl Schema {
+    /// Generates a schema document (draft 2020-12) describing all the given samples.
+    ///
+    /// - Types seen at the same location are unioned
+    ///   (`integer` if all numbers are whole, `number` otherwise).
+    /// - Object keys present in every sample object (at a given location) are `required`.
+    /// - All elements of all sample arrays (at a given location) make up the `items`.
+    /// - Strings with few distinct values (see [`InferOptions`]) become an `enum`,
+    ///   provided no types other than `null` were seen.
+    ///
+    /// Without samples, this returns an empty schema (accepting anything).
+    /// The result can be passed to [`Schema::compile`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{InferOptions, Schema};
+    ///
+    /// let samples = [
+    ///     json!({"id": 1, "status": "open", "tags": ["a"], "owner": null}),
+    ///     json!({"id": 2, "status": "closed", "tags": [], "score": 0.5}),
+    ///     json!({"id": 3, "status": "open", "tags": ["b", 7], "owner": {"name": "x"}}),
+    /// ];
+    /// let options = InferOptions { min_enum_samples: 3, ..InferOptions::default() };
+    /// let schema = Schema::infer(&samples, &options);
+    /// assert_eq!(
+    ///     schema,
+    ///     json!({
+    ///         "$schema": "https://json-schema.org/draft/2020-12/schema",
+    ///         "type": "object",
+    ///         "properties": {
+    ///             "id": {"type": "integer"},
+    ///             "owner": {
+    ///                 "type": ["null", "object"],
+    ///                 "properties": {"name": {"type": "string"}},
+    ///                 "required": ["name"]
+    ///             },
+    ///             "score": {"type": "number"},
+    ///             "status": {"enum": ["closed", "open"]},
+    ///             "tags": {"type": "array", "items": {"type": ["integer", "string"]}}
+    ///         },
+    ///         "required": ["id", "status", "tags"]
+    ///     })
+    /// );
+    ///
+    /// let validator = Schema::compile(&schema).unwrap();
+    /// assert!(samples.iter().all(|sample| validator.is_valid(sample)));
+    /// assert!(!validator.is_valid(&json!({"id": 4, "status": "pending", "tags": []})));
+    /// ```
+    #[must_use]
+    pub fn infer<'a>(
+        samples: impl IntoIterator<Item = &'a Value>,
+        options: &InferOptions,
+    ) -> Value {
+        let mut shape = Shape::default();
+        for sample in samples {
+            shape.add(sample, options);
+        }
+        let mut schema = shape.to_schema(options);
+        schema.insert(
+            Str::from("$schema"),
+            Value::from("https://json-schema.org/draft/2020-12/schema"),
+        );
+        Value::Object(schema)
+    }
+}
+
+/// Accumulated knowledge about the values seen at a given location.
+#[derive(Default)]
+struct Shape {
+    /// Number of values seen.
+    count: usize,
+    null: bool,
+    boolean: bool,
+    /// Whether all numbers were whole, if any number.
+    integer: Option<bool>,
+    strings: usize,
+    /// `None` once too many to make an `enum`.
+    distinct_strings: Option<BTreeSet<Str>>,
+    /// Shape of all elements, if any array.
+    array: Option<Box<Shape>>,
+    /// Number of objects, and shape of each member, if any object.
+    object: Option<(usize, BTreeMap<Str, Shape>)>,
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value, options: &InferOptions) {
+        self.count += 1;
+        match value {
+            Value::Null => self.null = true,
+            Value::Boolean(_) => self.boolean = true,
+            Value::Number(num) => {
+                let is_whole = num.get().fract() == 0.0;
+                self.integer = Some(self.integer.unwrap_or(true) && is_whole);
+            }
+            Value::String(str) => {
+                if self.strings == 0 {
+                    self.distinct_strings = Some(BTreeSet::new());
+                }
+                self.strings += 1;
+                if let Some(distinct) = &mut self.distinct_strings {
+                    distinct.insert(str.clone());
+                    if distinct.len() > options.max_enum_values {
+                        self.distinct_strings = None;
+                    }
+                }
+            }
+            Value::Array(arr) => {
+                let items = self.array.get_or_insert_with(Box::default);
+                for element in arr {
+                    items.add(element, options);
+                }
+            }
+            Value::Object(obj) => {
+                let (count, properties) = self.object.get_or_insert_with(Default::default);
+                *count += 1;
+                for (key, value) in obj {
+                    properties
+                        .entry(key.clone())
+                        .or_default()
+                        .add(value, options);
+                }
+            }
+        }
+    }
+
+    fn to_schema(&self, options: &InferOptions) -> Obj {
+        let mut schema = Obj::new();
+        let is_enum = self.strings >= options.min_enum_samples
+            && !self.boolean
+            && self.integer.is_none()
+            && self.array.is_none()
+            && self.object.is_none();
+        if let (true, Some(distinct)) = (is_enum, &self.distinct_strings) {
+            let mut values: Arr = distinct.iter().cloned().map(Value::String).collect();
+            if self.null {
+                values.push(Value::Null);
+            }
+            schema.insert(Str::from("enum"), Value::Array(values));
+            return schema;
+        }
+
+        let mut types = Vec::new();
+        if self.null {
+            types.push("null");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        match self.integer {
+            Some(true) => types.push("integer"),
+            Some(false) => types.push("number"),
+            None => {}
+        }
+        if self.strings > 0 {
+            types.push("string");
+        }
+        if let Some(items) = &self.array {
+            types.push("array");
+            if items.count != 0 {
+                schema.insert(Str::from("items"), Value::Object(items.to_schema(options)));
+            }
+        }
+        if let Some((count, properties)) = &self.object {
+            types.push("object");
+            if !properties.is_empty() {
+                let required: Arr = properties
+                    .iter()
+                    .filter(|(_, shape)| shape.count == *count)
+                    .map(|(key, _)| Value::String(key.clone()))
+                    .collect();
+                let properties = properties
+                    .iter()
+                    .map(|(key, shape)| (key.clone(), Value::Object(shape.to_schema(options))))
+                    .collect();
+                schema.insert(Str::from("properties"), Value::Object(properties));
+                if !required.is_empty() {
+                    schema.insert(Str::from("required"), Value::Array(required));
+                }
+            }
+        }
+        match types[..] {
+            [] => {}
+            [typ] => {
+                schema.insert(Str::from("type"), Value::from(typ));
+            }
+            _ => {
+                let types = types.into_iter().map(Value::from).collect();
+                schema.insert(Str::from("type"), Value::Array(types));
+            }
+        }
+        schema
+    }
+}
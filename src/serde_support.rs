@@ -0,0 +1,222 @@
+use super::*;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer};
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// Serializes any `serde` type into JSON text (compact), via a [`Value`].
+///
+/// Non-finite floats become `null`, and integers are converted to `f64`
+/// (so possibly rounded beyond ±2<sup>53</sup>).
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::{from_str, to_string};
+/// use std::collections::BTreeMap;
+///
+/// let data: BTreeMap<String, (Option<u8>, Vec<f64>)> =
+///     BTreeMap::from([("a".into(), (Some(1), vec![0.5])), ("b".into(), (None, vec![]))]);
+/// let json = to_string(&data).unwrap();
+/// assert_eq!(json, r#"{"a":[1,[0.5]],"b":[null,[]]}"#);
+/// assert_eq!(from_str::<BTreeMap<String, (Option<u8>, Vec<f64>)>>(&json).unwrap(), data);
+///
+/// // enums are externally tagged
+/// let results: Vec<Result<u8, String>> = vec![Ok(1), Err("nope".into())];
+/// let json = to_string(&results).unwrap();
+/// assert_eq!(json, r#"[{"Ok":1},{"Err":"nope"}]"#);
+/// assert_eq!(from_str::<Vec<Result<u8, String>>>(&json).unwrap(), results);
+///
+/// let error = from_str::<Vec<u8>>("[1, 2.5]").unwrap_err();
+/// assert_eq!(error.to_string(), "invalid type: floating point `2.5`, expected u8");
+/// let error = from_str::<Vec<u8>>("[1, 2").unwrap_err();
+/// assert_eq!(error.to_string(), "premature end of data at line 1 column 6");
+///
+/// // map keys are strings, converted from and to numbers or bools
+/// let data = BTreeMap::from([(1_u32, "a"), (20, "b")]);
+/// let json = to_string(&data).unwrap();
+/// assert_eq!(json, r#"{"1":"a","20":"b"}"#);
+/// assert_eq!(from_str::<BTreeMap<u32, String>>(&json).unwrap(), BTreeMap::from([(1, "a".into()), (20, "b".into())]));
+/// assert_eq!(from_str::<BTreeMap<bool, i8>>(r#"{"true":-1}"#).unwrap(), BTreeMap::from([(true, -1)]));
+/// let error = from_str::<BTreeMap<u8, bool>>(r#"{"x":true}"#).unwrap_err();
+/// assert_eq!(error.to_string(), r#"invalid value: string "x", expected u8"#);
+///
+/// // integers are exact up to ±2^53, and within the range of u64 or i64
+/// assert_eq!(from_str::<u64>("18446744073709549568").unwrap(), 18446744073709549568);
+/// assert_eq!(from_str::<i64>("-9223372036854775808").unwrap(), i64::MIN);
+/// assert!(from_str::<u64>("18446744073709551616").is_err());
+/// ```
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, SerdeError> {
+    to_value(value).map(|value| value.to_string())
+}
+
+/// Deserializes any `serde` type from JSON text, via a [`Value`].
+pub fn from_str<T: de::DeserializeOwned>(s: &str) -> Result<T, SerdeError> {
+    from_value(s.parse()?)
+}
+
+/// Serializes any `serde` type into a [`Value`].
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, SerdeError> {
+    value.serialize(serializer::ValueSerializer)
+}
+
+/// Deserializes any `serde` type from a [`Value`].
+pub fn from_value<T: de::DeserializeOwned>(value: Value) -> Result<T, SerdeError> {
+    T::deserialize(value)
+}
+
+/// Error from `serde` (de)serialization.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SerdeError {
+    Parse(ParseError),
+    Custom(String),
+}
+
+impl Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(error) => Display::fmt(error, f),
+            Self::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+impl Error for SerdeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Parse(error) => Some(error),
+            Self::Custom(_) => None,
+        }
+    }
+}
+
+impl From<ParseError> for SerdeError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+impl ser::Error for SerdeError {
+    fn custom<T: Display>(message: T) -> Self {
+        Self::Custom(message.to_string())
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: Display>(message: T) -> Self {
+        Self::Custom(message.to_string())
+    }
+}
+
+impl Serialize for Num {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.get())
+    }
+}
+
+impl<'de> Deserialize<'de> for Num {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let f = f64::deserialize(deserializer)?;
+        Num::new(f).ok_or_else(|| de::Error::custom("non-finite number"))
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Null => serializer.serialize_unit(),
+            Self::Boolean(b) => serializer.serialize_bool(*b),
+            Self::Number(num) => num.serialize(serializer),
+            Self::String(str) => serializer.serialize_str(str),
+            Self::Array(arr) => {
+                let mut seq = serializer.serialize_seq(Some(arr.len()))?;
+                for element in arr {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            Self::Object(obj) => {
+                let mut map = serializer.serialize_map(Some(obj.len()))?;
+                for (key, value) in obj {
+                    map.serialize_entry(&**key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<Value, E> {
+        Ok(Value::Boolean(b))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn visit_i64<E: de::Error>(self, i: i64) -> Result<Value, E> {
+        self.visit_f64(i as f64)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn visit_u64<E: de::Error>(self, u: u64) -> Result<Value, E> {
+        self.visit_f64(u as f64)
+    }
+
+    fn visit_f64<E: de::Error>(self, f: f64) -> Result<Value, E> {
+        Num::new(f)
+            .map(Value::Number)
+            .ok_or_else(|| E::custom("non-finite number"))
+    }
+
+    fn visit_str<E: de::Error>(self, str: &str) -> Result<Value, E> {
+        Ok(Value::from(String::from(str)))
+    }
+
+    fn visit_string<E: de::Error>(self, string: String) -> Result<Value, E> {
+        Ok(Value::from(string))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut arr = Arr::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            arr.push(element);
+        }
+        Ok(Value::Array(arr))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut obj = Obj::new();
+        while let Some((key, value)) = map.next_entry::<String, Value>()? {
+            obj.insert(Str::from(key), value);
+        }
+        Ok(Value::Object(obj))
+    }
+}
+
+mod deserializer;
+mod serializer;
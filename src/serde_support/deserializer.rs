@@ -0,0 +1,263 @@
+use super::*;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{EnumAccess, IntoDeserializer, Unexpected, VariantAccess};
+use serde::forward_to_deserialize_any;
+use std::ops::Range;
+
+type DeResult<T> = Result<T, SerdeError>;
+
+/// Integral numbers in this range (of `i64` and `u64`) are visited as integers.
+const INTEGRAL: Range<f64> = -9_223_372_036_854_775_808.0..18_446_744_073_709_551_616.0; // -2^63..2^64
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = SerdeError;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        match self {
+            Self::Null => visitor.visit_unit(),
+            Self::Boolean(b) => visitor.visit_bool(b),
+            Self::Number(num) => {
+                let f = num.get();
+                if f.fract() != 0.0 || !INTEGRAL.contains(&f) {
+                    visitor.visit_f64(f)
+                } else if f < 0.0 {
+                    visitor.visit_i64(f as i64)
+                } else {
+                    visitor.visit_u64(f as u64)
+                }
+            }
+            Self::String(str) => visitor.visit_string(str.into_owned()),
+            Self::Array(arr) => {
+                let mut seq = SeqDeserializer::new(arr.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Self::Object(obj) => {
+                let mut map = MapDeserializer::new(
+                    obj.into_iter()
+                        .map(|(key, value)| (KeyDeserializer(key.into_owned()), value)),
+                );
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        match self {
+            Self::Null => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Externally tagged: a string for a unit variant, or an object with a
+    /// single entry for a variant with content.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        match self {
+            Self::String(variant) => visitor.visit_enum(Enum {
+                variant: variant.into_owned(),
+                content: None,
+            }),
+            Self::Object(obj) if obj.len() == 1 => {
+                let (variant, content) = obj.into_iter().next().expect("one entry");
+                visitor.visit_enum(Enum {
+                    variant: variant.into_owned(),
+                    content: Some(content),
+                })
+            }
+            value => Err(de::Error::invalid_type(
+                unexpected(&value),
+                &"a string or an object with a single entry",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Deserializer of object keys: strings, parsed for numbers and bools
+/// (as written by the serializer, e.g. for `BTreeMap<u32, _>`).
+struct KeyDeserializer(String);
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident,)+) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+            match self.0.parse() {
+                Ok(v) => visitor.$visit(v),
+                Err(_) => Err(de::Error::invalid_value(Unexpected::Str(&self.0), &visitor)),
+            }
+        }
+    )+};
+}
+
+impl<'de> Deserializer<'de> for KeyDeserializer {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        visitor.visit_string(self.0)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// A unit variant.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        visitor.visit_enum(IntoDeserializer::<SerdeError>::into_deserializer(self.0))
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for KeyDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+fn unexpected(value: &Value) -> Unexpected<'_> {
+    match value {
+        Value::Null => Unexpected::Unit,
+        Value::Boolean(b) => Unexpected::Bool(*b),
+        Value::Number(num) => Unexpected::Float(num.get()),
+        Value::String(str) => Unexpected::Str(str),
+        Value::Array(_) => Unexpected::Seq,
+        Value::Object(_) => Unexpected::Map,
+    }
+}
+
+/// Access to an externally tagged enum variant.
+struct Enum {
+    variant: String,
+    content: Option<Value>,
+}
+
+impl<'de> EnumAccess<'de> for Enum {
+    type Error = SerdeError;
+    type Variant = Variant;
+
+    fn variant_seed<S: de::DeserializeSeed<'de>>(self, seed: S) -> DeResult<(S::Value, Variant)> {
+        let variant = seed.deserialize(IntoDeserializer::<SerdeError>::into_deserializer(
+            self.variant,
+        ))?;
+        Ok((variant, Variant(self.content)))
+    }
+}
+
+/// Content of an enum variant (`None` for a unit variant given as a string).
+struct Variant(Option<Value>);
+
+impl<'de> VariantAccess<'de> for Variant {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> DeResult<()> {
+        match self.0 {
+            None | Some(Value::Null) => Ok(()),
+            Some(value) => Err(de::Error::invalid_type(unexpected(&value), &"unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<S: de::DeserializeSeed<'de>>(self, seed: S) -> DeResult<S::Value> {
+        match self.0 {
+            Some(value) => seed.deserialize(value),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> DeResult<V::Value> {
+        match self.0 {
+            Some(value @ Value::Array(_)) => value.deserialize_any(visitor),
+            Some(value) => Err(de::Error::invalid_type(
+                unexpected(&value),
+                &"tuple variant",
+            )),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        match self.0 {
+            Some(value @ Value::Object(_)) => value.deserialize_any(visitor),
+            Some(value) => Err(de::Error::invalid_type(
+                unexpected(&value),
+                &"struct variant",
+            )),
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
@@ -0,0 +1,420 @@
+use super::*;
+use serde::ser::{
+    SerializeStruct, SerializeStructVariant, SerializeTuple, SerializeTupleStruct,
+    SerializeTupleVariant,
+};
+
+type SerResult<T> = Result<T, SerdeError>;
+
+/// Serializer into a [`Value`] (enums are externally tagged).
+pub(super) struct ValueSerializer;
+
+fn number(f: f64) -> Value {
+    Num::new(f).map_or(Value::Null, Value::Number)
+}
+
+fn tagged(variant: &'static str, content: Value) -> Value {
+    Value::Object(Obj::from([(Str::from(variant), content)]))
+}
+
+macro_rules! serialize_as_f64 {
+    ($($method:ident: $typ:ty),+) => {$(
+        #[allow(clippy::cast_precision_loss, clippy::cast_lossless)]
+        fn $method(self, v: $typ) -> SerResult<Value> {
+            Ok(number(v as f64))
+        }
+    )+};
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = SerdeError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = MapSerializer;
+
+    fn serialize_bool(self, v: bool) -> SerResult<Value> {
+        Ok(Value::Boolean(v))
+    }
+
+    serialize_as_f64! {
+        serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64, serialize_i128: i128,
+        serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64, serialize_u128: u128,
+        serialize_f32: f32, serialize_f64: f64
+    }
+
+    fn serialize_char(self, v: char) -> SerResult<Value> {
+        Ok(Value::from(String::from(v)))
+    }
+
+    fn serialize_str(self, v: &str) -> SerResult<Value> {
+        Ok(Value::from(String::from(v)))
+    }
+
+    /// As an array of numbers.
+    fn serialize_bytes(self, v: &[u8]) -> SerResult<Value> {
+        Ok(Value::Array(
+            v.iter().map(|&byte| Value::from(u32::from(byte))).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> SerResult<Value> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> SerResult<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> SerResult<Value> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> SerResult<Value> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> SerResult<Value> {
+        Ok(Value::from(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> SerResult<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> SerResult<Value> {
+        Ok(tagged(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> SerResult<SeqSerializer> {
+        Ok(SeqSerializer {
+            variant: None,
+            arr: Arr::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> SerResult<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> SerResult<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerResult<SeqSerializer> {
+        Ok(SeqSerializer {
+            variant: Some(variant),
+            arr: Arr::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> SerResult<MapSerializer> {
+        Ok(MapSerializer {
+            variant: None,
+            obj: Obj::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> SerResult<MapSerializer> {
+        self.serialize_map(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> SerResult<MapSerializer> {
+        Ok(MapSerializer {
+            variant: Some(variant),
+            obj: Obj::new(),
+            key: None,
+        })
+    }
+}
+
+pub(super) struct SeqSerializer {
+    /// For a tuple variant.
+    variant: Option<&'static str>,
+    arr: Arr,
+}
+
+impl SeqSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult<()> {
+        self.arr.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> SerResult<Value> {
+        let arr = Value::Array(self.arr);
+        Ok(match self.variant {
+            Some(variant) => tagged(variant, arr),
+            None => arr,
+        })
+    }
+}
+
+macro_rules! impl_seq {
+    ($($trait:ident::$method:ident),+) => {$(
+        impl $trait for SeqSerializer {
+            type Ok = Value;
+            type Error = SerdeError;
+
+            fn $method<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult<()> {
+                self.push(value)
+            }
+
+            fn end(self) -> SerResult<Value> {
+                self.finish()
+            }
+        }
+    )+};
+}
+
+impl_seq!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field
+);
+
+pub(super) struct MapSerializer {
+    /// For a struct variant.
+    variant: Option<&'static str>,
+    obj: Obj,
+    /// Pending, between `serialize_key` and `serialize_value`.
+    key: Option<Str>,
+}
+
+impl MapSerializer {
+    fn finish(self) -> SerResult<Value> {
+        let obj = Value::Object(self.obj);
+        Ok(match self.variant {
+            Some(variant) => tagged(variant, obj),
+            None => obj,
+        })
+    }
+}
+
+impl SerializeMap for MapSerializer {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> SerResult<()> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult<()> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_key before serialize_value");
+        self.obj.insert(key, value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> SerResult<Value> {
+        self.finish()
+    }
+}
+
+impl SerializeStruct for MapSerializer {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> SerResult<()> {
+        self.obj
+            .insert(Str::from(key), value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> SerResult<Value> {
+        self.finish()
+    }
+}
+
+impl SerializeStructVariant for MapSerializer {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> SerResult<()> {
+        SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> SerResult<Value> {
+        self.finish()
+    }
+}
+
+/// Serializer of object keys: strings, or scalars converted to strings.
+struct KeySerializer;
+
+fn key_error() -> SerdeError {
+    ser::Error::custom("object key must be a string (or a number, bool or char)")
+}
+
+macro_rules! serialize_key_to_string {
+    ($($method:ident: $typ:ty),+) => {$(
+        fn $method(self, v: $typ) -> SerResult<Str> {
+            Ok(Str::from(v.to_string()))
+        }
+    )+};
+}
+
+impl Serializer for KeySerializer {
+    type Ok = Str;
+    type Error = SerdeError;
+    type SerializeSeq = ser::Impossible<Str, SerdeError>;
+    type SerializeTuple = ser::Impossible<Str, SerdeError>;
+    type SerializeTupleStruct = ser::Impossible<Str, SerdeError>;
+    type SerializeTupleVariant = ser::Impossible<Str, SerdeError>;
+    type SerializeMap = ser::Impossible<Str, SerdeError>;
+    type SerializeStruct = ser::Impossible<Str, SerdeError>;
+    type SerializeStructVariant = ser::Impossible<Str, SerdeError>;
+
+    serialize_key_to_string! {
+        serialize_bool: bool,
+        serialize_i8: i8, serialize_i16: i16, serialize_i32: i32, serialize_i64: i64, serialize_i128: i128,
+        serialize_u8: u8, serialize_u16: u16, serialize_u32: u32, serialize_u64: u64, serialize_u128: u128,
+        serialize_char: char, serialize_str: &str
+    }
+
+    fn serialize_f32(self, _v: f32) -> SerResult<Str> {
+        Err(key_error())
+    }
+
+    fn serialize_f64(self, _v: f64) -> SerResult<Str> {
+        Err(key_error())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> SerResult<Str> {
+        Err(key_error())
+    }
+
+    fn serialize_none(self) -> SerResult<Str> {
+        Err(key_error())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> SerResult<Str> {
+        Err(key_error())
+    }
+
+    fn serialize_unit(self) -> SerResult<Str> {
+        Err(key_error())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> SerResult<Str> {
+        Err(key_error())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> SerResult<Str> {
+        Ok(Str::from(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> SerResult<Str> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> SerResult<Str> {
+        Err(key_error())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> SerResult<Self::SerializeSeq> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> SerResult<Self::SerializeTuple> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> SerResult<Self::SerializeTupleStruct> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> SerResult<Self::SerializeTupleVariant> {
+        Err(key_error())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> SerResult<Self::SerializeMap> {
+        Err(key_error())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> SerResult<Self::SerializeStruct> {
+        Err(key_error())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> SerResult<Self::SerializeStructVariant> {
+        Err(key_error())
+    }
+}
@@ -0,0 +1,220 @@
+use super::*;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+impl Value {
+    /// Encodes into [Smile] (with its header), with shared key names and shared
+    /// (short) string values enabled, so repeated keys and strings are written
+    /// as back-references. Numbers take the smallest encoding: whole numbers within
+    /// the 64-bit integer range as (variable-length) integers, other numbers as
+    /// 32-bit floats when that preserves them, otherwise as 64-bit floats.
+    ///
+    /// [Smile]: https://github.com/FasterXML/smile-format-specification
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    ///
+    /// assert_eq!(json!([{"id": 1, "tag": "new"}, {"id": 300, "tag": "new"}]).to_smile(), [
+    ///     b':', b')', b'\n', 0x03, // header
+    ///     0xf8, // [
+    ///     0xfa, 0x81, b'i', b'd', 0xc2, 0x82, b't', b'a', b'g', 0x42, b'n', b'e', b'w', 0xfb,
+    ///     0xfa, 0x40, 0x24, 0x09, 0x98, 0x41, 0x01, 0xfb, // back-references
+    ///     0xf9, // ]
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn to_smile(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        helper::encode(self, &mut out);
+        out
+    }
+
+    /// Decodes a single [Smile] value (after its mandatory header), with default options
+    /// (rejecting any value without a JSON equivalent).
+    ///
+    /// Integers, big integers and big decimals are converted to `f64`
+    /// (so possibly rounded). An end-of-content marker (`0xff`) may follow the value.
+    ///
+    /// [Smile]: https://github.com/FasterXML/smile-format-specification
+    ///
+    /// # Errors
+    ///
+    /// If the data is invalid, not completely consumed,
+    /// or contains a value rejected by the options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{Obj, SmileError, SmileErrorKind, Str, Value};
+    ///
+    /// let value = json!({"a": [1, 0.5, "x", (-123456789)], "b": null});
+    /// assert_eq!(Value::from_smile(&value.to_smile()), Ok(value));
+    ///
+    /// // more shared strings than fit in the (1024-entry) back-reference tables
+    /// let value = Value::Array((0..3000).map(|i| {
+    ///     Value::Object(Obj::from([(Str::from(format!("k{}", i % 1500)), Value::from(format!("v{}", i % 1100)))]))
+    /// }).collect());
+    /// assert_eq!(Value::from_smile(&value.to_smile()), Ok(value));
+    ///
+    /// let error = Value::from_smile(b"{}").unwrap_err();
+    /// assert_eq!(error, SmileError { kind: SmileErrorKind::InvalidHeader, offset: 0 });
+    /// // reference to the 2nd shared string, with only one
+    /// let error = Value::from_smile(b":)\n\x03\xf8\x40a\x02\xf9").unwrap_err();
+    /// assert_eq!(error.to_string(), "invalid back-reference at byte 7");
+    /// ```
+    ///
+    /// Values at the boundaries of each encoding survive a round trip
+    /// (and back to JSON text):
+    ///
+    /// ```
+    /// use rustic_json::{Arr, Obj, Str, Value};
+    ///
+    /// let mut values = Arr::new();
+    /// for i in [-17_i64, -16, 15, 16, i32::MIN.into(), i32::MAX.into(), 1 << 31, -(1 << 53), i64::MIN] {
+    ///     values.push(Value::try_from(i as f64).unwrap());
+    /// }
+    /// for f in [0.5, 0.1, -1e-300, f64::from(f32::MAX), 9223372036854775808.0] {
+    ///     values.push(Value::try_from(f).unwrap());
+    /// }
+    /// // string and key lengths (in bytes) around the short/long token limits
+    /// values.extend([Value::from(""), Value::from("a"), Value::Object(Obj::from([(Str::from(""), Value::Null)]))]);
+    /// for len in [2, 32, 33, 34, 57, 58, 64, 65, 66, 100] {
+    ///     for str in ["a".repeat(len), format!("é{}", "a".repeat(len - 2))] {
+    ///         values.push(Value::Object(Obj::from([(Str::from(str.clone()), Value::from(str))])));
+    ///     }
+    /// }
+    ///
+    /// // (repeated, for back-references)
+    /// let all = Value::Array(Arr::from([Value::Array(values.clone()), Value::Array(values.clone())]));
+    /// for value in values.iter().chain([&all]) {
+    ///     let decoded = Value::from_smile(&value.to_smile()).unwrap();
+    ///     assert_eq!(&decoded, value);
+    ///     assert_eq!(&decoded.to_string().parse::<Value>().unwrap(), value);
+    /// }
+    /// ```
+    pub fn from_smile(bytes: &[u8]) -> Result<Self, SmileError> {
+        Self::from_smile_with(bytes, &SmileOptions::default())
+    }
+
+    /// Decodes a single [Smile] value (after its mandatory header), mapping
+    /// values without a JSON equivalent as configured (see [`SmileOptions`]).
+    ///
+    /// [Smile]: https://github.com/FasterXML/smile-format-specification
+    ///
+    /// # Errors
+    ///
+    /// See [`Value::from_smile`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{SmileErrorKind, SmileOptions, Value};
+    ///
+    /// // [raw binary 01 ff, float NaN]
+    /// let bytes = b":)\n\x04\xf8\xfd\x82\x01\xff\x28\x07\x7e\x00\x00\x00\xf9";
+    /// assert_eq!(Value::from_smile_with(bytes, &SmileOptions::lossy()), Ok(json!(["Af8", null])));
+    ///
+    /// let error = Value::from_smile(bytes).unwrap_err();
+    /// assert_eq!((error.kind, error.offset), (SmileErrorKind::Binary, 5));
+    /// ```
+    pub fn from_smile_with(bytes: &[u8], options: &SmileOptions) -> Result<Self, SmileError> {
+        helper::decode(bytes, options)
+    }
+}
+
+/// Options for [`Value::from_smile_with`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SmileOptions {
+    /// Binary values (7-bit encoded or raw): converted to base64url strings (without padding).
+    pub binary: NonJsonPolicy,
+    /// NaN and infinities: converted to `null`.
+    pub non_finite_floats: NonJsonPolicy,
+    /// Maximum nesting of arrays and objects.
+    pub max_depth: usize,
+}
+
+impl SmileOptions {
+    /// Converting every value without a JSON equivalent.
+    #[must_use]
+    pub fn lossy() -> Self {
+        Self {
+            binary: NonJsonPolicy::Convert,
+            non_finite_floats: NonJsonPolicy::Convert,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for SmileOptions {
+    /// Rejecting every value without a JSON equivalent.
+    fn default() -> Self {
+        Self {
+            binary: NonJsonPolicy::Reject,
+            non_finite_floats: NonJsonPolicy::Reject,
+            max_depth: 512,
+        }
+    }
+}
+
+/// Error from [`Value::from_smile`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SmileError {
+    pub kind: SmileErrorKind,
+    /// Of the start of the offending value (or of the end of data).
+    pub offset: usize,
+}
+
+impl Display for SmileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl Error for SmileError {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SmileErrorKind {
+    PrematureEnd,
+    /// Data after the (first) value.
+    TrailingData,
+    /// A missing header, or an unsupported version.
+    InvalidHeader,
+    /// An unknown (or misplaced) token.
+    InvalidToken(u8),
+    InvalidUtf8,
+    /// A shared key name or string value reference beyond those seen so far.
+    InvalidReference,
+    /// A malformed number.
+    InvalidNumber,
+    /// A big integer or big decimal that does not fit into a finite `f64`.
+    TooBigNumber,
+    DuplicateKey(Str),
+    TooDeep,
+    Binary,
+    NonFiniteFloat,
+}
+
+impl Display for SmileErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PrematureEnd => f.write_str("premature end of data"),
+            Self::TrailingData => f.write_str("trailing data"),
+            Self::InvalidHeader => f.write_str("invalid header"),
+            Self::InvalidToken(token) => write!(f, "invalid token 0x{:02x}", token),
+            Self::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            Self::InvalidReference => f.write_str("invalid back-reference"),
+            Self::InvalidNumber => f.write_str("invalid number"),
+            Self::TooBigNumber => f.write_str("too big number"),
+            Self::DuplicateKey(key) => write!(f, "duplicate key {:?}", key),
+            Self::TooDeep => f.write_str("too deep nesting"),
+            Self::Binary => f.write_str("unsupported binary"),
+            Self::NonFiniteFloat => f.write_str("unsupported non-finite float"),
+        }
+    }
+}
+
+mod helper;
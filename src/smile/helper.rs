@@ -0,0 +1,585 @@
+use super::*;
+use std::collections::HashMap;
+
+type DecodeResult<T> = Result<T, SmileError>;
+
+use SmileErrorKind as K;
+
+const HEADER: &[u8; 3] = b":)\n";
+const SHARED_NAMES: u8 = 0x01;
+const SHARED_VALUES: u8 = 0x02;
+
+const EMPTY_STRING: u8 = 0x20;
+const NULL: u8 = 0x21;
+const FALSE: u8 = 0x22;
+const TRUE: u8 = 0x23;
+const INT32: u8 = 0x24;
+const INT64: u8 = 0x25;
+const BIG_INTEGER: u8 = 0x26;
+const FLOAT32: u8 = 0x28;
+const FLOAT64: u8 = 0x29;
+const BIG_DECIMAL: u8 = 0x2a;
+const LONG_ASCII: u8 = 0xe0;
+const LONG_UNICODE: u8 = 0xe4;
+const BINARY_7BIT: u8 = 0xe8;
+const START_ARRAY: u8 = 0xf8;
+const END_ARRAY: u8 = 0xf9;
+const START_OBJECT: u8 = 0xfa;
+const END_OBJECT: u8 = 0xfb;
+const END_STRING: u8 = 0xfc;
+const BINARY_RAW: u8 = 0xfd;
+const END_CONTENT: u8 = 0xff;
+const LONG_KEY: u8 = 0x34;
+
+/// Capacity of each back-reference table (which is cleared when full).
+const MAX_SHARED: usize = 1024;
+
+pub(super) fn encode(value: &Value, out: &mut Vec<u8>) {
+    out.extend_from_slice(HEADER);
+    out.push(SHARED_NAMES | SHARED_VALUES);
+    Encoder {
+        out,
+        names: SharedTable::default(),
+        values: SharedTable::default(),
+    }
+    .encode(value);
+}
+
+/// Back-reference indexes of the strings seen so far, as tracked by decoders.
+#[derive(Default)]
+struct SharedTable<'v> {
+    indexes: HashMap<&'v str, usize>,
+    len: usize,
+}
+
+impl<'v> SharedTable<'v> {
+    fn add(&mut self, str: &'v str) {
+        if self.len == MAX_SHARED {
+            self.indexes.clear();
+            self.len = 0;
+        }
+        self.indexes.insert(str, self.len);
+        self.len += 1;
+    }
+
+    /// Index of the string, if seen and referable
+    /// (long references must not contain the reserved bytes `0xfe` and `0xff`).
+    fn find(&self, str: &str) -> Option<usize> {
+        self.indexes
+            .get(str)
+            .copied()
+            .filter(|&index| index & 0xff < 0xfe)
+    }
+}
+
+struct Encoder<'o, 'v> {
+    out: &'o mut Vec<u8>,
+    names: SharedTable<'v>,
+    values: SharedTable<'v>,
+}
+
+impl<'v> Encoder<'_, 'v> {
+    fn encode(&mut self, value: &'v Value) {
+        match value {
+            Value::Null => self.out.push(NULL),
+            Value::Boolean(b) => self.out.push(if *b { TRUE } else { FALSE }),
+            Value::Number(num) => self.encode_number(*num),
+            Value::String(str) => self.encode_str(str),
+            Value::Array(arr) => {
+                self.out.push(START_ARRAY);
+                for element in arr {
+                    self.encode(element);
+                }
+                self.out.push(END_ARRAY);
+            }
+            Value::Object(obj) => {
+                self.out.push(START_OBJECT);
+                for (key, value) in obj {
+                    self.encode_key(key);
+                    self.encode(value);
+                }
+                self.out.push(END_OBJECT);
+            }
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn encode_number(&mut self, num: Num) {
+        let f = num.get();
+        if let Some(i) = num.as_i64() {
+            if let Ok(i) = i32::try_from(i) {
+                let zigzag = ((i << 1) ^ (i >> 31)) as u32;
+                if zigzag < 32 {
+                    self.out.push(0xc0 | zigzag as u8);
+                } else {
+                    self.out.push(INT32);
+                    write_vint(u64::from(zigzag), self.out);
+                }
+            } else {
+                self.out.push(INT64);
+                write_vint(((i << 1) ^ (i >> 63)) as u64, self.out);
+            }
+        } else if f64::from(f as f32) == f {
+            self.out.push(FLOAT32);
+            write_7bit_groups(u64::from((f as f32).to_bits()), 5, self.out);
+        } else {
+            self.out.push(FLOAT64);
+            write_7bit_groups(f.to_bits(), 10, self.out);
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn encode_str(&mut self, str: &'v str) {
+        let len = str.len();
+        if len == 0 {
+            self.out.push(EMPTY_STRING);
+            return;
+        }
+        if let Some(index) = self.values.find(str) {
+            if index < 31 {
+                self.out.push(index as u8 + 1);
+            } else {
+                self.out
+                    .extend_from_slice(&[0xec | (index >> 8) as u8, index as u8]);
+            }
+            return;
+        }
+        let token = match (str.is_ascii(), len) {
+            (true, 1..=32) => Some(0x40 + (len - 1) as u8),
+            (true, 33..=64) => Some(0x60 + (len - 33) as u8),
+            (false, 2..=33) => Some(0x80 + (len - 2) as u8),
+            (false, 34..=65) => Some(0xa0 + (len - 34) as u8),
+            _ => None,
+        };
+        match token {
+            Some(token) => {
+                self.out.push(token);
+                self.out.extend_from_slice(str.as_bytes());
+                self.values.add(str);
+            }
+            None => {
+                self.out.push(if str.is_ascii() {
+                    LONG_ASCII
+                } else {
+                    LONG_UNICODE
+                });
+                self.out.extend_from_slice(str.as_bytes());
+                self.out.push(END_STRING);
+            }
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn encode_key(&mut self, key: &'v str) {
+        let len = key.len();
+        if len == 0 {
+            self.out.push(EMPTY_STRING);
+            return;
+        }
+        if let Some(index) = self.names.find(key) {
+            if index < 64 {
+                self.out.push(0x40 | index as u8);
+            } else {
+                self.out
+                    .extend_from_slice(&[0x30 | (index >> 8) as u8, index as u8]);
+            }
+            return;
+        }
+        let token = match (key.is_ascii(), len) {
+            (true, 1..=64) => 0x80 + (len - 1) as u8,
+            (false, 2..=57) => 0xc0 + (len - 2) as u8,
+            _ => LONG_KEY,
+        };
+        self.out.push(token);
+        self.out.extend_from_slice(key.as_bytes());
+        if token == LONG_KEY {
+            self.out.push(END_STRING);
+        }
+        self.names.add(key);
+    }
+}
+
+/// Variable-length unsigned integer: 7 bits per byte, most significant first,
+/// the last byte holding 6 bits and flagged with its high bit.
+#[allow(clippy::cast_possible_truncation)]
+fn write_vint(n: u64, out: &mut Vec<u8>) {
+    let mut shift = 6;
+    while shift < 64 && n >> shift != 0 {
+        shift += 7;
+    }
+    while shift > 6 {
+        shift -= 7;
+        out.push((n >> shift) as u8 & 0x7f);
+    }
+    out.push(0x80 | (n as u8 & 0x3f));
+}
+
+/// The given number of 7-bit groups, most significant first.
+#[allow(clippy::cast_possible_truncation)]
+fn write_7bit_groups(n: u64, groups: u32, out: &mut Vec<u8>) {
+    for group in (0..groups).rev() {
+        out.push((n.checked_shr(7 * group).unwrap_or(0)) as u8 & 0x7f);
+    }
+}
+
+pub(super) fn decode(bytes: &[u8], options: &SmileOptions) -> DecodeResult<Value> {
+    let Some((header, rest)) = bytes.split_first_chunk::<4>() else {
+        return Err(SmileError {
+            kind: K::InvalidHeader,
+            offset: 0,
+        });
+    };
+    if header[..3] != *HEADER || header[3] >> 4 != 0 {
+        return Err(SmileError {
+            kind: K::InvalidHeader,
+            offset: if header[..3] == *HEADER { 3 } else { 0 },
+        });
+    }
+    let mut decoder = Decoder {
+        bytes,
+        offset: bytes.len() - rest.len(),
+        options,
+        depth: 0,
+        shared_names: (header[3] & SHARED_NAMES != 0).then(Vec::new),
+        shared_values: (header[3] & SHARED_VALUES != 0).then(Vec::new),
+    };
+    let value = decoder.decode_value()?;
+    if decoder.bytes.get(decoder.offset) == Some(&END_CONTENT) {
+        decoder.offset += 1;
+    }
+    if decoder.offset < bytes.len() {
+        return Err(decoder.error_at(K::TrailingData, decoder.offset));
+    }
+    Ok(value)
+}
+
+struct Decoder<'b, 'o> {
+    bytes: &'b [u8],
+    offset: usize,
+    options: &'o SmileOptions,
+    depth: usize,
+    /// If enabled by the header.
+    shared_names: Option<Vec<Str>>,
+    /// If enabled by the header.
+    shared_values: Option<Vec<Str>>,
+}
+
+impl<'b> Decoder<'b, '_> {
+    fn error_at(&self, kind: SmileErrorKind, offset: usize) -> SmileError {
+        SmileError { kind, offset }
+    }
+
+    /// Fails with the error unless the options convert the value.
+    fn check(
+        &self,
+        policy: NonJsonPolicy,
+        kind: SmileErrorKind,
+        offset: usize,
+    ) -> DecodeResult<()> {
+        match policy {
+            NonJsonPolicy::Reject => Err(self.error_at(kind, offset)),
+            NonJsonPolicy::Convert => Ok(()),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> DecodeResult<&'b [u8]> {
+        match self
+            .bytes
+            .get(self.offset..)
+            .and_then(|rest| rest.get(..len))
+        {
+            Some(taken) => {
+                self.offset += len;
+                Ok(taken)
+            }
+            None => Err(self.error_at(K::PrematureEnd, self.bytes.len())),
+        }
+    }
+
+    fn read_u8(&mut self) -> DecodeResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_vint(&mut self, start: usize) -> DecodeResult<u64> {
+        let mut n: u64 = 0;
+        loop {
+            let byte = self.read_u8()?;
+            let (shift, bits) = if byte & 0x80 == 0 {
+                (7, byte)
+            } else {
+                (6, byte & 0x3f)
+            };
+            if n >> (64 - shift) != 0 {
+                return Err(self.error_at(K::InvalidNumber, start));
+            }
+            n = n << shift | u64::from(bits);
+            if byte & 0x80 != 0 {
+                return Ok(n);
+            }
+        }
+    }
+
+    fn read_len(&mut self, start: usize) -> DecodeResult<usize> {
+        let len = self.read_vint(start)?;
+        usize::try_from(len).map_err(|_| self.error_at(K::InvalidNumber, start))
+    }
+
+    fn read_7bit_groups(&mut self, groups: usize, start: usize) -> DecodeResult<u128> {
+        let mut n = 0;
+        for &byte in self.take(groups)? {
+            if byte >= 0x80 {
+                return Err(self.error_at(K::InvalidNumber, start));
+            }
+            n = n << 7 | u128::from(byte);
+        }
+        Ok(n)
+    }
+
+    /// 7-bit encoded bytes (each 7 bytes in 8, then the remaining bits right-aligned).
+    #[allow(clippy::cast_possible_truncation)]
+    fn read_7bit_bytes(&mut self, len: usize, start: usize) -> DecodeResult<Vec<u8>> {
+        let (chunks, remainder) = (len / 7, len % 7);
+        let encoded_len = chunks
+            .checked_mul(8)
+            .and_then(|encoded_len| encoded_len.checked_add(remainder + usize::from(remainder > 0)))
+            .ok_or_else(|| self.error_at(K::PrematureEnd, self.bytes.len()))?;
+        let encoded = self.take(encoded_len)?;
+        if encoded.iter().any(|&byte| byte >= 0x80) {
+            return Err(self.error_at(K::InvalidToken(BINARY_7BIT), start));
+        }
+        let mut bytes = Vec::with_capacity(len);
+        for chunk in encoded.chunks(8) {
+            let data_len = chunk.len() - 1;
+            let (groups, last) = chunk.split_at(data_len);
+            let n = groups
+                .iter()
+                .fold(0_u64, |n, &byte| n << 7 | u64::from(byte));
+            let (n, bits) = if data_len == 7 {
+                (n << 7 | u64::from(last[0]), 56)
+            } else {
+                (n << data_len | u64::from(last[0]), 8 * data_len)
+            };
+            bytes.extend((0..bits / 8).rev().map(|i| (n >> (8 * i)) as u8));
+        }
+        Ok(bytes)
+    }
+
+    /// Until the end-of-string marker.
+    fn read_terminated(&mut self) -> DecodeResult<&'b [u8]> {
+        let rest = &self.bytes[self.offset..];
+        let Some(len) = rest.iter().position(|&byte| byte == END_STRING) else {
+            return Err(self.error_at(K::PrematureEnd, self.bytes.len()));
+        };
+        self.offset += len + 1;
+        Ok(&rest[..len])
+    }
+
+    fn utf8(&self, bytes: &[u8], start: usize) -> DecodeResult<Str> {
+        match std::str::from_utf8(bytes) {
+            Ok(str) => Ok(Str::from(String::from(str))),
+            Err(_) => Err(self.error_at(K::InvalidUtf8, start)),
+        }
+    }
+
+    fn enter(&mut self, start: usize) -> DecodeResult<()> {
+        if self.depth >= self.options.max_depth {
+            return Err(self.error_at(K::TooDeep, start));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn decode_value(&mut self) -> DecodeResult<Value> {
+        let start = self.offset;
+        let token = self.read_u8()?;
+        let f = match token {
+            NULL => return Ok(Value::Null),
+            FALSE => return Ok(Value::Boolean(false)),
+            TRUE => return Ok(Value::Boolean(true)),
+            EMPTY_STRING => return Ok(Value::from("")),
+            0x01..=0x1f => return self.shared_value(usize::from(token - 1), start),
+            0xec..=0xef => {
+                let index = usize::from(token & 0x03) << 8 | usize::from(self.read_u8()?);
+                return self.shared_value(index, start);
+            }
+            0x40..=0xbf => {
+                let len = usize::from(token & 0x1f)
+                    + match token >> 5 {
+                        2 => 1,
+                        3 => 33,
+                        4 => 2,
+                        _ => 34,
+                    };
+                let str = self.take(len)?;
+                let str = self.utf8(str, start)?;
+                if let Some(shared_values) = &mut self.shared_values {
+                    if shared_values.len() == MAX_SHARED {
+                        shared_values.clear();
+                    }
+                    shared_values.push(str.clone());
+                }
+                return Ok(Value::String(str));
+            }
+            LONG_ASCII | LONG_UNICODE => {
+                let str = self.read_terminated()?;
+                return self.utf8(str, start).map(Value::String);
+            }
+            0xc0..=0xdf => {
+                let zigzag = i32::from(token & 0x1f);
+                f64::from(zigzag >> 1 ^ -(zigzag & 1))
+            }
+            INT32 => {
+                let zigzag = u32::try_from(self.read_vint(start)?)
+                    .map_err(|_| self.error_at(K::InvalidNumber, start))?;
+                f64::from((zigzag >> 1) as i32 ^ -((zigzag & 1) as i32))
+            }
+            INT64 => {
+                let zigzag = self.read_vint(start)?;
+                ((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64)) as f64
+            }
+            FLOAT32 => {
+                let bits = u32::try_from(self.read_7bit_groups(5, start)?)
+                    .map_err(|_| self.error_at(K::InvalidNumber, start))?;
+                f64::from(f32::from_bits(bits))
+            }
+            FLOAT64 => {
+                let bits = u64::try_from(self.read_7bit_groups(10, start)?)
+                    .map_err(|_| self.error_at(K::InvalidNumber, start))?;
+                f64::from_bits(bits)
+            }
+            BIG_INTEGER => {
+                let len = self.read_len(start)?;
+                let bytes = self.read_7bit_bytes(len, start)?;
+                return self.big_number(&bytes, 0, start);
+            }
+            BIG_DECIMAL => {
+                let zigzag = u32::try_from(self.read_vint(start)?)
+                    .map_err(|_| self.error_at(K::InvalidNumber, start))?;
+                let scale = (zigzag >> 1) as i32 ^ -((zigzag & 1) as i32);
+                let len = self.read_len(start)?;
+                let bytes = self.read_7bit_bytes(len, start)?;
+                return self.big_number(&bytes, scale, start);
+            }
+            BINARY_7BIT | BINARY_RAW => {
+                self.check(self.options.binary, K::Binary, start)?;
+                let len = self.read_len(start)?;
+                let bytes = match token {
+                    BINARY_RAW => self.take(len)?.to_vec(),
+                    _ => self.read_7bit_bytes(len, start)?,
+                };
+                return Ok(Value::from(base64::encode(&bytes, base64::URL_SAFE, false)));
+            }
+            START_ARRAY => {
+                self.enter(start)?;
+                let mut arr = Arr::new();
+                while self.bytes.get(self.offset) != Some(&END_ARRAY) {
+                    arr.push(self.decode_value()?);
+                }
+                self.offset += 1;
+                self.depth -= 1;
+                return Ok(Value::Array(arr));
+            }
+            START_OBJECT => {
+                self.enter(start)?;
+                let mut obj = Obj::new();
+                while !self.decode_member(&mut obj)? {}
+                self.depth -= 1;
+                return Ok(Value::Object(obj));
+            }
+            _ => return Err(self.error_at(K::InvalidToken(token), start)),
+        };
+        match Num::new(f) {
+            Some(num) => Ok(Value::Number(num)),
+            None => {
+                self.check(self.options.non_finite_floats, K::NonFiniteFloat, start)?;
+                Ok(Value::Null)
+            }
+        }
+    }
+
+    fn shared_value(&self, index: usize, start: usize) -> DecodeResult<Value> {
+        match self
+            .shared_values
+            .as_ref()
+            .and_then(|values| values.get(index))
+        {
+            Some(str) => Ok(Value::String(str.clone())),
+            None => Err(self.error_at(K::InvalidReference, start)),
+        }
+    }
+
+    /// Two's complement big-endian bytes, times 10<sup>-scale</sup>.
+    fn big_number(&self, bytes: &[u8], scale: i32, start: usize) -> DecodeResult<Value> {
+        let negative = bytes.first().is_some_and(|&byte| byte >= 0x80);
+        let magnitude = if negative {
+            // -n = !n + 1, so |n| = (!n) + 1
+            bytes
+                .iter()
+                .fold(0.0, |acc, &byte| acc * 256.0 + f64::from(!byte))
+                + 1.0
+        } else {
+            bytes
+                .iter()
+                .fold(0.0, |acc, &byte| acc * 256.0 + f64::from(byte))
+        };
+        let f = if negative { -magnitude } else { magnitude };
+        let f = if scale == 0 || f == 0.0 {
+            f
+        } else {
+            format!("{}e{}", f, -i64::from(scale))
+                .parse()
+                .unwrap_or(f64::INFINITY)
+        };
+        Num::new(f)
+            .map(Value::Number)
+            .ok_or_else(|| self.error_at(K::TooBigNumber, start))
+    }
+
+    /// Returns whether the object ended.
+    fn decode_member(&mut self, obj: &mut Obj) -> DecodeResult<bool> {
+        let start = self.offset;
+        let token = self.read_u8()?;
+        let key = match token {
+            END_OBJECT => return Ok(true),
+            EMPTY_STRING => Str::from(""),
+            0x40..=0x7f => self.shared_name(usize::from(token & 0x3f), start)?,
+            0x30..=0x33 => {
+                let index = usize::from(token & 0x03) << 8 | usize::from(self.read_u8()?);
+                self.shared_name(index, start)?
+            }
+            0x80..=0xf7 | LONG_KEY => {
+                let key = match token {
+                    LONG_KEY => self.read_terminated()?,
+                    0x80..=0xbf => self.take(usize::from(token & 0x3f) + 1)?,
+                    _ => self.take(usize::from(token & 0x3f) + 2)?,
+                };
+                let key = self.utf8(key, start)?;
+                if let Some(shared_names) = &mut self.shared_names {
+                    if shared_names.len() == MAX_SHARED {
+                        shared_names.clear();
+                    }
+                    shared_names.push(key.clone());
+                }
+                key
+            }
+            _ => return Err(self.error_at(K::InvalidToken(token), start)),
+        };
+        if obj.contains_key(&key) {
+            return Err(self.error_at(K::DuplicateKey(key), start));
+        }
+        let value = self.decode_value()?;
+        obj.insert(key, value);
+        Ok(false)
+    }
+
+    fn shared_name(&self, index: usize, start: usize) -> DecodeResult<Str> {
+        match self
+            .shared_names
+            .as_ref()
+            .and_then(|names| names.get(index))
+        {
+            Some(name) => Ok(name.clone()),
+            None => Err(self.error_at(K::InvalidReference, start)),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use super::*;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+impl Value {
+    /// Encodes into a compact, crate-specific binary snapshot
+    /// (see [`Value::from_snapshot`]), where each distinct string is stored once.
+    ///
+    /// # Format
+    ///
+    /// - magic `RJSN` and format version (1);
+    /// - string table: count, then each string (keys and string values, each stored once)
+    ///   as byte length and UTF-8 bytes;
+    /// - value, in pre-order, each starting with a tag byte:
+    ///   - `0x00` null, `0x01` false, `0x02` true,
+    ///   - `0x03` whole number within ±2<sup>53</sup> (zigzag-encoded),
+    ///   - `0x04` other number (IEEE 754 double, little-endian),
+    ///   - `0x05` string (index into the string table),
+    ///   - `0x06` array (length, then elements),
+    ///   - `0x07` object (length, then pairs of key index and value, keys in increasing order);
+    /// - CRC-32 (IEEE) of all the preceding bytes, little-endian.
+    ///
+    /// Counts, lengths and indexes are unsigned LEB128 varints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::Value;
+    ///
+    /// let record = json!({"name": "sensor", "unit": "celsius", "values": [21.5, 22, (-3)]});
+    /// let value = Value::Array(vec![record; 100]);
+    /// let snapshot = value.to_snapshot();
+    /// assert!(snapshot.len() < value.to_string().len() / 2);
+    /// assert_eq!(Value::from_snapshot(&snapshot), Ok(value));
+    ///
+    /// assert_eq!(json!({"a": ["a", null]}).to_snapshot(), [
+    ///     b'R', b'J', b'S', b'N', 1, // magic and version
+    ///     1, 1, b'a', // string table: ["a"]
+    ///     0x07, 1, 0, 0x06, 2, 0x05, 0, 0x00, // {#0: [#0, null]}
+    ///     0x7f, 0x43, 0x76, 0x63, // checksum
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        helper::encode(self)
+    }
+
+    /// Decodes a snapshot produced by [`Value::to_snapshot`], in a single
+    /// bounds-checked pass (after verifying its checksum), without recursion.
+    ///
+    /// # Errors
+    ///
+    /// If the snapshot is truncated, corrupted (checksum mismatch),
+    /// of another format version, or otherwise invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{SnapshotError, SnapshotErrorKind, Value};
+    ///
+    /// let mut snapshot = json!({"answer": 42}).to_snapshot();
+    /// snapshot[8] ^= 0x20;
+    /// let error = Value::from_snapshot(&snapshot).unwrap_err();
+    /// assert_eq!(error, SnapshotError { kind: SnapshotErrorKind::ChecksumMismatch, offset: 18 });
+    /// assert_eq!(error.to_string(), "checksum mismatch at byte 18");
+    ///
+    /// let error = Value::from_snapshot(b"{\"answer\": 42}").unwrap_err();
+    /// assert_eq!(error.kind, SnapshotErrorKind::InvalidHeader);
+    ///
+    /// // containers grow beyond what is reserved upfront
+    /// let value = Value::Array((0..3000).map(|i| json!([i, {"k": (i % 7)}])).collect());
+    /// assert_eq!(Value::from_snapshot(&value.to_snapshot()), Ok(value));
+    /// ```
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        helper::decode(bytes)
+    }
+}
+
+/// Error from [`Value::from_snapshot`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SnapshotError {
+    pub kind: SnapshotErrorKind,
+    /// Of the start of the offending item (or of the checksum).
+    pub offset: usize,
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl Error for SnapshotError {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SnapshotErrorKind {
+    PrematureEnd,
+    /// Data between the value and the checksum.
+    TrailingData,
+    /// Missing magic, or another format version.
+    InvalidHeader,
+    ChecksumMismatch,
+    /// A varint longer than 64 bits.
+    InvalidVarint,
+    /// With the unknown tag.
+    InvalidTag(u8),
+    InvalidUtf8,
+    /// A string index beyond the string table.
+    InvalidStringIndex,
+    /// Object keys not in strictly increasing order (including duplicate keys).
+    UnsortedKeys,
+    NonFiniteFloat,
+}
+
+impl Display for SnapshotErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PrematureEnd => f.write_str("premature end of data"),
+            Self::TrailingData => f.write_str("trailing data"),
+            Self::InvalidHeader => f.write_str("invalid header"),
+            Self::ChecksumMismatch => f.write_str("checksum mismatch"),
+            Self::InvalidVarint => f.write_str("invalid varint"),
+            Self::InvalidTag(tag) => write!(f, "invalid tag 0x{:02x}", tag),
+            Self::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            Self::InvalidStringIndex => f.write_str("invalid string index"),
+            Self::UnsortedKeys => f.write_str("unsorted keys"),
+            Self::NonFiniteFloat => f.write_str("non-finite float"),
+        }
+    }
+}
+
+mod helper;
@@ -0,0 +1,340 @@
+use super::*;
+use std::collections::HashMap;
+
+type DecodeResult<T> = Result<T, SnapshotError>;
+
+use SnapshotErrorKind as K;
+
+const MAGIC: &[u8; 4] = b"RJSN";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1;
+const CHECKSUM_LEN: usize = 4;
+
+const NULL: u8 = 0x00;
+const FALSE: u8 = 0x01;
+const TRUE: u8 = 0x02;
+const INTEGER: u8 = 0x03;
+const FLOAT: u8 = 0x04;
+const STRING: u8 = 0x05;
+const ARRAY: u8 = 0x06;
+const OBJECT: u8 = 0x07;
+
+/// 2<sup>53</sup>, the bound of integers stored as such.
+const INTEGER_BOUND: u64 = 1 << 53;
+
+/// The most elements reserved upfront from a count, which is only bounded by the
+/// remaining bytes (while each element takes much more memory once decoded,
+/// and nested containers would each reserve their own).
+const MAX_PREALLOCATED: usize = 1024;
+
+pub(super) fn encode(value: &Value) -> Vec<u8> {
+    let mut encoder = Encoder {
+        strings: Vec::new(),
+        indexes: HashMap::new(),
+        tree: Vec::new(),
+    };
+    encoder.encode(value);
+    let mut out = Vec::with_capacity(HEADER_LEN + encoder.tree.len() + CHECKSUM_LEN);
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    write_varint(encoder.strings.len() as u64, &mut out);
+    for str in &encoder.strings {
+        write_varint(str.len() as u64, &mut out);
+        out.extend_from_slice(str.as_bytes());
+    }
+    out.extend_from_slice(&encoder.tree);
+    let checksum = crc32(&out);
+    out.extend_from_slice(&checksum.to_le_bytes());
+    out
+}
+
+struct Encoder<'v> {
+    /// In order of first appearance.
+    strings: Vec<&'v str>,
+    indexes: HashMap<&'v str, usize>,
+    tree: Vec<u8>,
+}
+
+impl<'v> Encoder<'v> {
+    fn write_string(&mut self, str: &'v str) {
+        let index = *self.indexes.entry(str).or_insert_with(|| {
+            self.strings.push(str);
+            self.strings.len() - 1
+        });
+        write_varint(index as u64, &mut self.tree);
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn encode(&mut self, value: &'v Value) {
+        match value {
+            Value::Null => self.tree.push(NULL),
+            Value::Boolean(b) => self.tree.push(if *b { TRUE } else { FALSE }),
+            Value::Number(num) => {
+                let integer = num.as_i64().filter(|i| i.unsigned_abs() <= INTEGER_BOUND);
+                if let Some(i) = integer {
+                    self.tree.push(INTEGER);
+                    write_varint(((i << 1) ^ (i >> 63)) as u64, &mut self.tree);
+                } else {
+                    self.tree.push(FLOAT);
+                    self.tree.extend_from_slice(&num.get().to_le_bytes());
+                }
+            }
+            Value::String(str) => {
+                self.tree.push(STRING);
+                self.write_string(str);
+            }
+            Value::Array(arr) => {
+                self.tree.push(ARRAY);
+                write_varint(arr.len() as u64, &mut self.tree);
+                for element in arr {
+                    self.encode(element);
+                }
+            }
+            Value::Object(obj) => {
+                self.tree.push(OBJECT);
+                write_varint(obj.len() as u64, &mut self.tree);
+                for (key, value) in obj {
+                    self.write_string(key);
+                    self.encode(value);
+                }
+            }
+        }
+    }
+}
+
+/// Unsigned LEB128.
+#[allow(clippy::cast_possible_truncation)]
+fn write_varint(mut n: u64, out: &mut Vec<u8>) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+/// CRC-32 (IEEE 802.3, reflected polynomial `0xedb88320`).
+fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 == 0 {
+                    crc >> 1
+                } else {
+                    crc >> 1 ^ 0xedb8_8320
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+    !bytes.iter().fold(!0, |crc, &byte| {
+        TABLE[usize::from(crc as u8 ^ byte)] ^ crc >> 8
+    })
+}
+
+pub(super) fn decode(bytes: &[u8]) -> DecodeResult<Value> {
+    if bytes.len() < HEADER_LEN + CHECKSUM_LEN || bytes[..MAGIC.len()] != *MAGIC {
+        return Err(SnapshotError {
+            kind: K::InvalidHeader,
+            offset: 0,
+        });
+    }
+    if bytes[MAGIC.len()] != VERSION {
+        return Err(SnapshotError {
+            kind: K::InvalidHeader,
+            offset: MAGIC.len(),
+        });
+    }
+    let (content, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+    if crc32(content).to_le_bytes() != checksum {
+        return Err(SnapshotError {
+            kind: K::ChecksumMismatch,
+            offset: content.len(),
+        });
+    }
+    let mut decoder = Decoder {
+        bytes: content,
+        offset: HEADER_LEN,
+    };
+    let strings = decoder.read_strings()?;
+    let value = decoder.decode_value(&strings)?;
+    if decoder.offset < content.len() {
+        return Err(decoder.error_at(K::TrailingData, decoder.offset));
+    }
+    Ok(value)
+}
+
+struct Decoder<'b> {
+    /// Without the checksum.
+    bytes: &'b [u8],
+    offset: usize,
+}
+
+/// A container being decoded, with its number of remaining elements.
+enum Frame {
+    Array(Arr, usize),
+    /// With the key of the next value, once read.
+    Object(Vec<(Str, Value)>, usize, Option<Str>),
+}
+
+impl<'b> Decoder<'b> {
+    fn error_at(&self, kind: SnapshotErrorKind, offset: usize) -> SnapshotError {
+        SnapshotError { kind, offset }
+    }
+
+    fn take(&mut self, len: usize) -> DecodeResult<&'b [u8]> {
+        match self
+            .bytes
+            .get(self.offset..)
+            .and_then(|rest| rest.get(..len))
+        {
+            Some(taken) => {
+                self.offset += len;
+                Ok(taken)
+            }
+            None => Err(self.error_at(K::PrematureEnd, self.bytes.len())),
+        }
+    }
+
+    fn read_u8(&mut self) -> DecodeResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_varint(&mut self) -> DecodeResult<u64> {
+        let start = self.offset;
+        let mut n = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            let bits = u64::from(byte & 0x7f);
+            if bits << shift >> shift != bits {
+                break;
+            }
+            n |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(self.error_at(K::InvalidVarint, start))
+    }
+
+    /// A count of items taking at least one byte each, so bounded by the remaining data.
+    fn read_count(&mut self) -> DecodeResult<usize> {
+        let count = self.read_varint()?;
+        usize::try_from(count)
+            .ok()
+            .filter(|&count| count <= self.bytes.len() - self.offset)
+            .ok_or_else(|| self.error_at(K::PrematureEnd, self.bytes.len()))
+    }
+
+    fn read_strings(&mut self) -> DecodeResult<Vec<Str>> {
+        let count = self.read_count()?;
+        let mut strings = Vec::with_capacity(count.min(MAX_PREALLOCATED));
+        for _ in 0..count {
+            let start = self.offset;
+            let len = self.read_count()?;
+            let str = std::str::from_utf8(self.take(len)?)
+                .map_err(|_| self.error_at(K::InvalidUtf8, start))?;
+            strings.push(Str::from(String::from(str)));
+        }
+        Ok(strings)
+    }
+
+    fn read_string(&mut self, strings: &[Str]) -> DecodeResult<Str> {
+        let start = self.offset;
+        let index = self.read_varint()?;
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| strings.get(index))
+            .cloned()
+            .ok_or_else(|| self.error_at(K::InvalidStringIndex, start))
+    }
+
+    /// Iteratively, with an explicit stack of the containers being decoded.
+    #[allow(clippy::cast_possible_wrap, clippy::cast_precision_loss)]
+    fn decode_value(&mut self, strings: &[Str]) -> DecodeResult<Value> {
+        let mut stack: Vec<Frame> = Vec::new();
+        loop {
+            // key of the next object member
+            if let Some(Frame::Object(members, remaining, key @ None)) = stack.last_mut() {
+                if *remaining > 0 {
+                    let start = self.offset;
+                    let next = self.read_string(strings)?;
+                    if members
+                        .last()
+                        .is_some_and(|(previous, _)| *previous >= next)
+                    {
+                        return Err(self.error_at(K::UnsortedKeys, start));
+                    }
+                    *key = Some(next);
+                }
+            }
+            let value = match stack.last() {
+                Some(Frame::Array(_, 0) | Frame::Object(_, 0, _)) => match stack.pop() {
+                    Some(Frame::Array(arr, _)) => Value::Array(arr),
+                    Some(Frame::Object(members, _, _)) => {
+                        Value::Object(members.into_iter().collect())
+                    }
+                    None => unreachable!("non-empty stack"),
+                },
+                _ => {
+                    let start = self.offset;
+                    match self.read_u8()? {
+                        NULL => Value::Null,
+                        FALSE => Value::Boolean(false),
+                        TRUE => Value::Boolean(true),
+                        INTEGER => {
+                            let zigzag = self.read_varint()?;
+                            let i = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+                            Value::Number(Num::new(i as f64).expect("finite number"))
+                        }
+                        FLOAT => {
+                            let bytes = self.take(8)?.try_into().expect("8 bytes");
+                            match Num::new(f64::from_le_bytes(bytes)) {
+                                Some(num) => Value::Number(num),
+                                None => return Err(self.error_at(K::NonFiniteFloat, start)),
+                            }
+                        }
+                        STRING => Value::String(self.read_string(strings)?),
+                        ARRAY => {
+                            let len = self.read_count()?;
+                            stack.push(Frame::Array(
+                                Arr::with_capacity(len.min(MAX_PREALLOCATED)),
+                                len,
+                            ));
+                            continue;
+                        }
+                        OBJECT => {
+                            let len = self.read_count()?;
+                            stack.push(Frame::Object(
+                                Vec::with_capacity(len.min(MAX_PREALLOCATED)),
+                                len,
+                                None,
+                            ));
+                            continue;
+                        }
+                        tag => return Err(self.error_at(K::InvalidTag(tag), start)),
+                    }
+                }
+            };
+            // attach to the parent container, if any
+            match stack.last_mut() {
+                None => return Ok(value),
+                Some(Frame::Array(arr, remaining)) => {
+                    arr.push(value);
+                    *remaining -= 1;
+                }
+                Some(Frame::Object(members, remaining, key)) => {
+                    let key = key.take().expect("key read before value");
+                    members.push((key, value));
+                    *remaining -= 1;
+                }
+            }
+        }
+    }
+}
@@ -53,99 +53,441 @@ impl Display for Value {
     /// ```
     #[doc(alias("stringify", "encode", "serialize"))]
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result {
-        helper::write_value(fmt, self, 0)
+        let options = StringifyOptions {
+            pretty: fmt.alternate(),
+            ..StringifyOptions::default()
+        };
+        helper::write_value(fmt, self, &options, 0)
     }
 }
 
+impl Value {
+    /// Formats into JSON with the given options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{NumberStyle, StringifyOptions};
+    ///
+    /// let value = json!([1e21, 1.5e-7, 0.000001, 123456789012345680000.0, (-0.0)]);
+    /// assert_eq!(value.to_string(), "[1e21,1.5e-7,1e-6,1.2345678901234568e20,-0]");
+    ///
+    /// let options = StringifyOptions { number_style: NumberStyle::ECMAScript, ..StringifyOptions::default() };
+    /// assert_eq!(value.to_string_with(&options), "[1e+21,1.5e-7,0.000001,123456789012345680000,0]");
+    ///
+    /// let options = StringifyOptions { pretty: true, ..options };
+    /// assert_eq!(json!({"a": [0.1]}).to_string_with(&options), "{\n    \"a\": [\n        0.1\n    ]\n}");
+    /// ```
+    ///
+    /// Numbers are written with the fewest digits that parse back to the same `f64`
+    /// (the closest such decimal if there are several, and the even one if two are equally close):
+    ///
+    /// ```
+    /// use rustic_json::json;
+    ///
+    /// assert_eq!(json!([0.1, 5e-324, 1.7976931348623157e308]).to_string(), "[0.1,5e-324,1.7976931348623157e308]");
+    /// // exactly between ...675.62 and ...675.63
+    /// assert_eq!(json!(101313107266675.625).to_string(), "101313107266675.62");
+    /// ```
+    ///
+    /// Only `"`, `\` and control characters are escaped by default, but more can be:
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::StringifyOptions;
+    ///
+    /// let value = json!({"é": "</script> & 🧡\u{2028}\u{1f}"});
+    /// assert_eq!(value.to_string(), "{\"é\":\"</script> & 🧡\u{2028}\\u001f\"}");
+    ///
+    /// let options = StringifyOptions { escape_non_ascii: true, ..StringifyOptions::default() };
+    /// assert_eq!(value.to_string_with(&options), r#"{"\u00e9":"</script> & \ud83e\udde1\u2028\u001f"}"#);
+    ///
+    /// let options = StringifyOptions { uppercase_hex: true, ..options };
+    /// assert_eq!(value.to_string_with(&options), r#"{"\u00E9":"</script> & \uD83E\uDDE1\u2028\u001F"}"#);
+    ///
+    /// // e.g. for an inline <script>
+    /// let options = StringifyOptions {
+    ///     escape_slash: true,
+    ///     escape_html: true,
+    ///     escape_line_separators: true,
+    ///     ..StringifyOptions::default()
+    /// };
+    /// let text = value.to_string_with(&options);
+    /// assert_eq!(text, r#"{"é":"\u003c\/script\u003e \u0026 🧡\u2028\u001f"}"#);
+    /// assert_eq!(text.parse(), Ok(value));
+    /// ```
+    ///
+    /// Random numbers round-trip:
+    ///
+    /// ```
+    /// use rustic_json::{NumberStyle, StringifyOptions, Value};
+    ///
+    /// let options = StringifyOptions { number_style: NumberStyle::ECMAScript, ..StringifyOptions::default() };
+    /// let mut seed = 0x5eed_u64;
+    /// for _ in 0..100_000 {
+    ///     seed ^= seed << 13;
+    ///     seed ^= seed >> 7;
+    ///     seed ^= seed << 17;
+    ///     let Ok(value) = Value::try_from(f64::from_bits(seed)) else { continue };
+    ///     for text in [value.to_string(), value.to_string_with(&options)] {
+    ///         assert_eq!(text.parse::<Value>(), Ok(value.clone()));
+    ///         // as many digits as Rust's (also shortest) formatting
+    ///         let digits = |s: &str| s.split('e').next().unwrap().replace(['-', '.'], "").trim_matches('0').len();
+    ///         assert_eq!(digits(&text), digits(&format!("{:e}", f64::from_bits(seed))));
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn to_string_with(&self, options: &StringifyOptions) -> String {
+        let mut out = String::new();
+        helper::write_value(&mut out, self, options, 0).expect("writing to a String");
+        out
+    }
+
+    /// Formats into the canonical JSON of [RFC 8785] (JSON Canonicalization Scheme),
+    /// e.g. to hash or sign it: compact, with object keys sorted by their UTF-16 code units,
+    /// numbers formatted as in ECMAScript ([`NumberStyle::ECMAScript`]),
+    /// and only the mandatory characters escaped in strings.
+    ///
+    /// [RFC 8785]: https://www.rfc-editor.org/rfc/rfc8785
+    ///
+    /// # Examples
+    ///
+    /// From the RFC (section 3.2.2):
+    ///
+    /// ```
+    /// use rustic_json::Value;
+    ///
+    /// let value: Value = r#"{
+    ///     "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+    ///     "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+    ///     "literals": [null, true, false]
+    /// }"#.parse().unwrap();
+    /// assert_eq!(
+    ///     value.to_canonical_string(),
+    ///     r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+    /// );
+    /// ```
+    ///
+    /// Keys are sorted by UTF-16 code units, unlike [`Obj`] (section 3.2.3):
+    ///
+    /// ```
+    /// use rustic_json::json;
+    ///
+    /// let value = json!({
+    ///     "\u{20ac}": "Euro Sign",
+    ///     "\r": "Carriage Return",
+    ///     "\u{fb33}": "Hebrew Letter Dalet With Dagesh",
+    ///     "1": "One",
+    ///     "\u{1f600}": "Emoji: Grinning Face",
+    ///     "\u{80}": "Control",
+    ///     "\u{f6}": "Latin Small Letter O With Diaeresis"
+    /// });
+    /// let values: Vec<_> = value.to_canonical_string().split(r#"":""#).skip(1)
+    ///     .map(|rest| rest.split('"').next().unwrap().to_owned())
+    ///     .collect();
+    /// assert_eq!(values, [
+    ///     "Carriage Return",
+    ///     "One",
+    ///     "Control",
+    ///     "Latin Small Letter O With Diaeresis",
+    ///     "Euro Sign",
+    ///     "Emoji: Grinning Face",
+    ///     "Hebrew Letter Dalet With Dagesh",
+    /// ]);
+    /// ```
+    ///
+    /// Numbers (appendix B):
+    ///
+    /// ```
+    /// use rustic_json::Value;
+    ///
+    /// for (bits, expected) in [
+    ///     (0x0000000000000000_u64, "0"),
+    ///     (0x8000000000000000, "0"),
+    ///     (0x0000000000000001, "5e-324"),
+    ///     (0x8000000000000001, "-5e-324"),
+    ///     (0x7fefffffffffffff, "1.7976931348623157e+308"),
+    ///     (0xffefffffffffffff, "-1.7976931348623157e+308"),
+    ///     (0x4340000000000000, "9007199254740992"),
+    ///     (0xc340000000000000, "-9007199254740992"),
+    ///     (0x4430000000000000, "295147905179352830000"),
+    ///     (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+    ///     (0x44b52d02c7e14af6, "1e+23"),
+    ///     (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+    ///     (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+    ///     (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+    ///     (0x444b1ae4d6e2ef50, "1e+21"),
+    ///     (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+    ///     (0x3eb0c6f7a0b5ed8d, "0.000001"),
+    ///     (0x41b3de4355555553, "333333333.3333332"),
+    ///     (0x41b3de4355555554, "333333333.33333325"),
+    ///     (0x41b3de4355555555, "333333333.3333333"),
+    ///     (0x41b3de4355555556, "333333333.3333334"),
+    ///     (0x41b3de4355555557, "333333333.33333343"),
+    ///     (0xbecbf647612f3696, "-0.0000033333333333333333"),
+    ///     (0x43143ff3c1cb0959, "1424953923781206.2"),
+    /// ] {
+    ///     let value = Value::try_from(f64::from_bits(bits)).unwrap();
+    ///     assert_eq!(value.to_canonical_string(), expected);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn to_canonical_string(&self) -> String {
+        let mut out = String::new();
+        helper::write_canonical(&mut out, self).expect("writing to a String");
+        out
+    }
+}
+
+/// Options for [`Value::to_string_with`].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct StringifyOptions {
+    /// Pretty-printing (as the "alternate" form of [`Display`]), otherwise compact.
+    pub pretty: bool,
+    pub number_style: NumberStyle,
+    /// Escaping all non-ASCII characters as `\uXXXX` (as surrogate pairs beyond U+FFFF),
+    /// e.g. for ASCII-only channels.
+    pub escape_non_ascii: bool,
+    /// Escaping `/` as `\/`, e.g. against `</script>` when embedding into HTML.
+    pub escape_slash: bool,
+    /// Escaping `<`, `>` and `&` as `\uXXXX`, e.g. against `<!--` or `]]>`
+    /// when embedding into HTML or XML.
+    pub escape_html: bool,
+    /// Escaping U+2028 and U+2029 (line terminators in JavaScript before ES2019) as `\uXXXX`.
+    pub escape_line_separators: bool,
+    /// Upper-case hexadecimal digits in `\uXXXX` escapes, otherwise lower-case.
+    pub uppercase_hex: bool,
+}
+
+/// How to format numbers, always with the fewest digits that parse back to the same `f64`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum NumberStyle {
+    /// Like Rust's [`Debug`](std::fmt::Debug) for `f64` (without any `.0` suffix):
+    /// exponential notation outside of [10<sup>-4</sup>, 10<sup>16</sup>), e.g. `1e-5` or `1e16`,
+    /// and keeping the sign of `-0`.
+    ///
+    /// Unlike Rust though, when two shortest decimals are equally close to the number,
+    /// the even one is written, so the last digit may differ from Rust's (and from
+    /// the `Display` output of earlier versions of this crate), still parsing back
+    /// to the same `f64`:
+    ///
+    /// ```
+    /// use rustic_json::json;
+    ///
+    /// let f = 101313107266675.625;
+    /// assert_eq!(format!("{:?}", f), "101313107266675.63");
+    /// assert_eq!(json!(f).to_string(), "101313107266675.62");
+    /// assert_eq!(json!(f).to_string().parse::<f64>(), Ok(f));
+    /// ```
+    #[default]
+    Rust,
+    /// Like ECMAScript's `Number.prototype.toString()` (e.g. in `JSON.stringify()`):
+    /// exponential notation outside of [10<sup>-6</sup>, 10<sup>21</sup>), e.g. `1e-7` or `1e+21`,
+    /// and `0` for `-0`.
+    ECMAScript,
+}
+
 mod helper {
     use super::*;
+    use ryu::Decimal;
 
-    pub(super) fn write_value(fmt: &mut Formatter, value: &Value, depth: usize) -> Result {
+    pub(super) fn write_value(
+        out: &mut impl Write,
+        value: &Value,
+        options: &StringifyOptions,
+        depth: usize,
+    ) -> Result {
         match value {
-            Value::Null => fmt.write_str("null"),
-            Value::Boolean(b) => write!(fmt, "{}", *b),
-            Value::Number(num) => write_number(fmt, *num),
-            Value::String(str) => write_string(fmt, str),
-            Value::Array(arr) => write_array(fmt, arr, depth),
-            Value::Object(obj) => write_object(fmt, obj, depth),
+            Value::Null => out.write_str("null"),
+            Value::Boolean(b) => write!(out, "{}", *b),
+            Value::Number(num) => write_number(out, *num, options.number_style),
+            Value::String(str) => write_string(out, str, options),
+            Value::Array(arr) => write_array(out, arr, options, depth),
+            Value::Object(obj) => write_object(out, obj, options, depth),
         }
     }
 
-    fn write_number(fmt: &mut Formatter<'_>, num: Num) -> Result {
-        let debug = format!("{:?}", num.get());
-        fmt.write_str(debug.strip_suffix(".0").unwrap_or(&debug))
+    /// As [`write_value`] (compact, ECMAScript numbers), but with object keys
+    /// in UTF-16 order rather than UTF-8 (which differ from U+E000).
+    pub(super) fn write_canonical(out: &mut impl Write, value: &Value) -> Result {
+        let options = StringifyOptions {
+            number_style: NumberStyle::ECMAScript,
+            ..StringifyOptions::default()
+        };
+        match value {
+            Value::Array(arr) => {
+                out.write_char('[')?;
+                for (i, element) in arr.iter().enumerate() {
+                    if i != 0 {
+                        out.write_char(',')?;
+                    }
+                    write_canonical(out, element)?;
+                }
+                out.write_char(']')
+            }
+            Value::Object(obj) => {
+                let mut entries: Vec<_> = obj.iter().collect();
+                // (stable and adaptive, so cheap for the usual already sorted keys)
+                entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+                out.write_char('{')?;
+                for (i, (key, value)) in entries.into_iter().enumerate() {
+                    if i != 0 {
+                        out.write_char(',')?;
+                    }
+                    write_string(out, key, &options)?;
+                    out.write_char(':')?;
+                    write_canonical(out, value)?;
+                }
+                out.write_char('}')
+            }
+            _ => write_value(out, value, &options, 0),
+        }
     }
 
-    fn write_string(fmt: &mut Formatter<'_>, str: &Str) -> Result {
-        fmt.write_char('"')?;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    fn write_number(out: &mut impl Write, num: Num, style: NumberStyle) -> Result {
+        const ZEROS: &str = "00000000000000000000";
+        let f = num.get();
+        if f == 0.0 {
+            let is_negative = f.is_sign_negative() && style == NumberStyle::Rust;
+            return out.write_str(if is_negative { "-0" } else { "0" });
+        }
+        if f < 0.0 {
+            out.write_char('-')?;
+        }
+        let decimal = Decimal::new(f.abs());
+        let mut buf = [0; 17];
+        let len = decimal.write_digits(&mut buf);
+        let digits = std::str::from_utf8(&buf[..len]).expect("ASCII digits");
+        // as 0.digits × 10^point
+        let point = decimal.exponent + len as i32;
+        let decimal_range = match style {
+            NumberStyle::Rust => -3..=16,
+            NumberStyle::ECMAScript => -5..=21,
+        };
+        if !decimal_range.contains(&point) {
+            out.write_str(&digits[..1])?;
+            if len > 1 {
+                out.write_char('.')?;
+                out.write_str(&digits[1..])?;
+            }
+            out.write_char('e')?;
+            if style == NumberStyle::ECMAScript && point > 0 {
+                out.write_char('+')?;
+            }
+            write!(out, "{}", point - 1)
+        } else if point <= 0 {
+            out.write_str("0.")?;
+            out.write_str(&ZEROS[..point.unsigned_abs() as usize])?;
+            out.write_str(digits)
+        } else if (point as usize) < len {
+            out.write_str(&digits[..point as usize])?;
+            out.write_char('.')?;
+            out.write_str(&digits[point as usize..])
+        } else {
+            out.write_str(digits)?;
+            out.write_str(&ZEROS[..point as usize - len])
+        }
+    }
+
+    fn write_string(out: &mut impl Write, str: &Str, options: &StringifyOptions) -> Result {
+        out.write_char('"')?;
         for c in str.chars() {
-            if let Ok(byte) = u8::try_from(c) {
-                if let Some(escape) = STRINGIFY_ESCAPE[usize::from(byte)] {
-                    write!(fmt, "\\{}", char::from(escape))?;
-                    continue;
-                }
-                if byte < MIN_VALID_STRING_CHAR {
-                    write!(fmt, "\\u{:04x}", byte)?;
-                    continue;
+            let escape = u8::try_from(c)
+                .ok()
+                .and_then(|byte| STRINGIFY_ESCAPE[usize::from(byte)]);
+            if let Some(escape) = escape {
+                write!(out, "\\{}", char::from(escape))?;
+            } else if c == '/' && options.escape_slash {
+                out.write_str("\\/")?;
+            } else if needs_unicode_escape(c, options) {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    if options.uppercase_hex {
+                        write!(out, "\\u{:04X}", unit)?;
+                    } else {
+                        write!(out, "\\u{:04x}", unit)?;
+                    }
                 }
+            } else {
+                out.write_char(c)?;
             }
-            fmt.write_char(c)?;
         }
-        fmt.write_char('"')
+        out.write_char('"')
     }
 
-    fn write_array(fmt: &mut Formatter<'_>, arr: &Arr, depth: usize) -> Result {
-        fmt.write_char('[')?;
+    fn needs_unicode_escape(c: char, options: &StringifyOptions) -> bool {
+        c < char::from(MIN_VALID_STRING_CHAR)
+            || (options.escape_non_ascii && !c.is_ascii())
+            || (options.escape_html && matches!(c, '<' | '>' | '&'))
+            || (options.escape_line_separators && matches!(c, '\u{2028}' | '\u{2029}'))
+    }
+
+    fn write_array(
+        out: &mut impl Write,
+        arr: &Arr,
+        options: &StringifyOptions,
+        depth: usize,
+    ) -> Result {
+        out.write_char('[')?;
         if !arr.is_empty() {
             {
                 let depth = depth + 1;
                 for (i, element) in arr.iter().enumerate() {
                     if i != 0 {
-                        fmt.write_char(',')?;
+                        out.write_char(',')?;
                     }
-                    pretty_writeln_indent(fmt, depth)?;
-                    write_value(fmt, element, depth)?;
+                    pretty_writeln_indent(out, options, depth)?;
+                    write_value(out, element, options, depth)?;
                 }
             }
-            pretty_writeln_indent(fmt, depth)?;
+            pretty_writeln_indent(out, options, depth)?;
         }
-        fmt.write_char(']')
+        out.write_char(']')
     }
 
-    fn write_object(fmt: &mut Formatter<'_>, obj: &Obj, depth: usize) -> Result {
-        fmt.write_char('{')?;
+    fn write_object(
+        out: &mut impl Write,
+        obj: &Obj,
+        options: &StringifyOptions,
+        depth: usize,
+    ) -> Result {
+        out.write_char('{')?;
         if !obj.is_empty() {
             {
                 let depth = depth + 1;
                 for (i, (key, value)) in obj.iter().enumerate() {
                     if i != 0 {
-                        fmt.write_char(',')?;
+                        out.write_char(',')?;
                     }
-                    pretty_writeln_indent(fmt, depth)?;
-                    write_string(fmt, key)?;
-                    fmt.write_char(':')?;
-                    if is_pretty(fmt) {
-                        fmt.write_char(' ')?;
+                    pretty_writeln_indent(out, options, depth)?;
+                    write_string(out, key, options)?;
+                    out.write_char(':')?;
+                    if options.pretty {
+                        out.write_char(' ')?;
                     }
-                    write_value(fmt, value, depth)?;
+                    write_value(out, value, options, depth)?;
                 }
             }
-            pretty_writeln_indent(fmt, depth)?;
+            pretty_writeln_indent(out, options, depth)?;
         }
-        fmt.write_char('}')
+        out.write_char('}')
     }
 
-    fn pretty_writeln_indent(fmt: &mut Formatter, depth: usize) -> Result {
-        if is_pretty(fmt) {
-            writeln!(fmt)?;
+    fn pretty_writeln_indent(
+        out: &mut impl Write,
+        options: &StringifyOptions,
+        depth: usize,
+    ) -> Result {
+        if options.pretty {
+            writeln!(out)?;
             for _ in 0..depth {
-                fmt.write_str("    ")?;
+                out.write_str("    ")?;
             }
         }
         Ok(())
     }
-
-    fn is_pretty(fmt: &Formatter) -> bool {
-        fmt.alternate()
-    }
 }
+
+mod ryu;
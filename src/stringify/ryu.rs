@@ -0,0 +1,303 @@
+//! Shortest round-trip decimal representation of an `f64`, after the Ryū algorithm
+//! (Ulf Adams, "Ryū: Fast Float-to-String Conversion", PLDI 2018).
+
+/// A positive finite `f64` as `digits × 10^exponent`, with the fewest digits that
+/// parse back to the same `f64` (the closest such decimal if there are several,
+/// and the even one if two are equally close), without trailing zeros.
+pub(crate) struct Decimal {
+    pub(crate) digits: u64,
+    pub(crate) exponent: i32,
+}
+
+const MANTISSA_BITS: u32 = 52;
+const EXPONENT_BITS: u32 = 11;
+const EXPONENT_BIAS: i32 = 1023;
+
+/// Significant bits kept in the tables (so that products fit in 64 + 125 bits).
+const POW5_BITCOUNT: i32 = 125;
+const POW5_INV_BITCOUNT: i32 = 125;
+
+/// `5^i`, to its 125 most significant bits.
+static POW5_SPLIT: [u128; 326] = pow5_split();
+/// `2^(pow5bits(i) - 1 + 125) / 5^i + 1`, i.e. `1 / 5^i` to (a bit more than) 125 significant bits.
+static POW5_INV_SPLIT: [u128; 342] = pow5_inv_split();
+
+/// Enough 64-bit limbs for the (exact) big integers used to compute the tables.
+const LIMBS: usize = 16;
+type Big = [u64; LIMBS];
+
+/// The 128 bits of `n` from bit `shift` (inclusive).
+const fn bits_from(n: &Big, shift: usize) -> u128 {
+    let (limb, offset) = (shift / 64, shift % 64);
+    let mut result = 0;
+    let mut k = 0;
+    while k < 3 && limb + k < LIMBS {
+        let bits = n[limb + k] as u128;
+        if k == 0 {
+            result |= bits >> offset;
+        } else if k * 64 - offset < 128 {
+            result |= bits << (k * 64 - offset);
+        }
+        k += 1;
+    }
+    result
+}
+
+#[allow(clippy::cast_sign_loss)]
+const fn pow5_split() -> [u128; 326] {
+    let mut table = [0; 326];
+    let mut pow: Big = [0; LIMBS];
+    pow[0] = 1;
+    let mut i = 0;
+    while i < table.len() {
+        let bits = pow5bits(i as i32);
+        table[i] = if bits < POW5_BITCOUNT {
+            bits_from(&pow, 0) << (POW5_BITCOUNT - bits)
+        } else {
+            bits_from(&pow, (bits - POW5_BITCOUNT) as usize)
+        };
+        // pow *= 5
+        let mut carry = 0;
+        let mut k = 0;
+        while k < LIMBS {
+            let product = pow[k] as u128 * 5 + carry;
+            pow[k] = product as u64;
+            carry = product >> 64;
+            k += 1;
+        }
+        i += 1;
+    }
+    table
+}
+
+#[allow(clippy::cast_sign_loss)]
+const fn pow5_inv_split() -> [u128; 342] {
+    const TOP: usize = LIMBS * 64 - 1;
+    let mut table = [0; 342];
+    // floor(2^TOP / 5^i), so that floor(2^j / 5^i) = floor(quotient / 2^(TOP - j))
+    let mut quotient: Big = [0; LIMBS];
+    quotient[LIMBS - 1] = 1 << 63;
+    let mut i = 0;
+    while i < table.len() {
+        let j = (pow5bits(i as i32) - 1 + POW5_INV_BITCOUNT) as usize;
+        table[i] = bits_from(&quotient, TOP - j) + 1;
+        // quotient /= 5
+        let mut remainder = 0;
+        let mut k = LIMBS;
+        while k > 0 {
+            k -= 1;
+            let dividend = remainder << 64 | quotient[k] as u128;
+            quotient[k] = (dividend / 5) as u64;
+            remainder = dividend % 5;
+        }
+        i += 1;
+    }
+    table
+}
+
+/// `ceil(log2(5^e))` (or 1 for `e = 0`), for `0 <= e <= 3528`.
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_wrap)]
+const fn pow5bits(e: i32) -> i32 {
+    ((e as u32 * 1_217_359) >> 19) as i32 + 1
+}
+
+/// `floor(log10(2^e))`, for `0 <= e <= 1650`.
+#[allow(clippy::cast_sign_loss)]
+fn log10_pow2(e: i32) -> u32 {
+    (e as u32 * 78_913) >> 18
+}
+
+/// `floor(log10(5^e))`, for `0 <= e <= 2620`.
+#[allow(clippy::cast_sign_loss)]
+fn log10_pow5(e: i32) -> u32 {
+    (e as u32 * 732_923) >> 20
+}
+
+fn is_multiple_of_pow5(mut v: u64, p: u32) -> bool {
+    let mut count = 0;
+    while v.is_multiple_of(5) {
+        v /= 5;
+        count += 1;
+    }
+    count >= p
+}
+
+fn is_multiple_of_pow2(v: u64, p: u32) -> bool {
+    v & ((1 << p) - 1) == 0
+}
+
+/// `(m × mul) >> j`, for `j >= 64`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn mul_shift(m: u64, mul: u128, j: i32) -> u64 {
+    let low = u128::from(m) * (mul as u64 as u128);
+    let high = u128::from(m) * (mul >> 64);
+    (((low >> 64) + high) >> (j - 64)) as u64
+}
+
+impl Decimal {
+    /// For a positive (or zero) finite `f64`.
+    pub(crate) fn new(f: f64) -> Self {
+        debug_assert!(f.is_finite() && f.is_sign_positive());
+        let mut decimal = Self::small_integer(f).unwrap_or_else(|| Self::shortest(f));
+        while decimal.digits.is_multiple_of(10) && decimal.digits != 0 {
+            decimal.digits /= 10;
+            decimal.exponent += 1;
+        }
+        decimal
+    }
+
+    /// Number of digits.
+    pub(crate) fn len(&self) -> usize {
+        let mut len = 1;
+        let mut rest = self.digits / 10;
+        while rest != 0 {
+            len += 1;
+            rest /= 10;
+        }
+        len
+    }
+
+    /// Writes the digits (as ASCII) at the start of the buffer, returning their number.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn write_digits(&self, buf: &mut [u8; 17]) -> usize {
+        let len = self.len();
+        let mut rest = self.digits;
+        for byte in buf[..len].iter_mut().rev() {
+            *byte = b'0' + (rest % 10) as u8;
+            rest /= 10;
+        }
+        len
+    }
+
+    /// Fast path: whole numbers below 2<sup>53</sup> are their own shortest representation.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn small_integer(f: f64) -> Option<Self> {
+        (f.fract() == 0.0 && f < 9_007_199_254_740_992.0).then_some(Self {
+            digits: f as u64,
+            exponent: 0,
+        })
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss
+    )]
+    fn shortest(f: f64) -> Self {
+        let bits = f.to_bits();
+        let ieee_mantissa = bits & ((1 << MANTISSA_BITS) - 1);
+        let ieee_exponent = (bits >> MANTISSA_BITS) as u32 & ((1 << EXPONENT_BITS) - 1);
+
+        // as m2 × 2^e2 (with 2 extra bits, for the bounds below)
+        let (e2, m2) = if ieee_exponent == 0 {
+            (1 - EXPONENT_BIAS - MANTISSA_BITS as i32 - 2, ieee_mantissa)
+        } else {
+            (
+                ieee_exponent as i32 - EXPONENT_BIAS - MANTISSA_BITS as i32 - 2,
+                1 << MANTISSA_BITS | ieee_mantissa,
+            )
+        };
+        // (round-half-even parsing accepts the bounds of an even mantissa)
+        let accept_bounds = m2 % 2 == 0;
+
+        // the interval of decimals parsing back to f: (mm, mp) × 2^e2, around mv × 2^e2;
+        // its lower half is narrower at the boundaries between binary exponents
+        let mv = 4 * m2;
+        let mm_shift = u64::from(ieee_mantissa != 0 || ieee_exponent <= 1);
+
+        // converted to (vm, vp) × 10^e10, around vr × 10^e10
+        let (mut vr, mut vp, mut vm);
+        let e10;
+        let mut vm_is_trailing_zeros = false;
+        let mut vr_is_trailing_zeros = false;
+        if e2 >= 0 {
+            let q = log10_pow2(e2) - u32::from(e2 > 3);
+            e10 = q as i32;
+            let k = POW5_INV_BITCOUNT + pow5bits(q as i32) - 1;
+            let i = -e2 + q as i32 + k;
+            let mul = POW5_INV_SPLIT[q as usize];
+            vr = mul_shift(4 * m2, mul, i);
+            vp = mul_shift(4 * m2 + 2, mul, i);
+            vm = mul_shift(4 * m2 - 1 - mm_shift, mul, i);
+            if q <= 21 {
+                // only one of mp, mv and mm can be a multiple of 5, if any
+                if mv % 5 == 0 {
+                    vr_is_trailing_zeros = is_multiple_of_pow5(mv, q);
+                } else if accept_bounds {
+                    vm_is_trailing_zeros = is_multiple_of_pow5(mv - 1 - mm_shift, q);
+                } else {
+                    vp -= u64::from(is_multiple_of_pow5(mv + 2, q));
+                }
+            }
+        } else {
+            let q = log10_pow5(-e2) - u32::from(-e2 > 1);
+            e10 = q as i32 + e2;
+            let i = -e2 - q as i32;
+            let k = pow5bits(i) - POW5_BITCOUNT;
+            let j = q as i32 - k;
+            let mul = POW5_SPLIT[i as usize];
+            vr = mul_shift(4 * m2, mul, j);
+            vp = mul_shift(4 * m2 + 2, mul, j);
+            vm = mul_shift(4 * m2 - 1 - mm_shift, mul, j);
+            if q <= 1 {
+                // mv = 4 × m2 has at least 2 trailing zero bits
+                vr_is_trailing_zeros = true;
+                if accept_bounds {
+                    // mm = mv - 1 - mm_shift has 1 trailing zero bit iff mm_shift is 1
+                    vm_is_trailing_zeros = mm_shift == 1;
+                } else {
+                    // mp = mv + 2 has at least 1 trailing zero bit
+                    vp -= 1;
+                }
+            } else if q < 63 {
+                vr_is_trailing_zeros = is_multiple_of_pow2(mv, q);
+            }
+        }
+
+        // remove digits while the interval still contains a decimal
+        let mut removed = 0;
+        let mut last_removed_digit = 0;
+        let digits = if vm_is_trailing_zeros || vr_is_trailing_zeros {
+            // general case (rare)
+            while vp / 10 > vm / 10 {
+                vm_is_trailing_zeros &= vm % 10 == 0;
+                vr_is_trailing_zeros &= last_removed_digit == 0;
+                last_removed_digit = vr % 10;
+                vr /= 10;
+                vp /= 10;
+                vm /= 10;
+                removed += 1;
+            }
+            if vm_is_trailing_zeros {
+                while vm % 10 == 0 {
+                    vr_is_trailing_zeros &= last_removed_digit == 0;
+                    last_removed_digit = vr % 10;
+                    vr /= 10;
+                    vm /= 10;
+                    removed += 1;
+                }
+            }
+            if vr_is_trailing_zeros && last_removed_digit == 5 && vr % 2 == 0 {
+                // exactly halfway: round to even
+                last_removed_digit = 4;
+            }
+            let is_out_of_bounds = vr == vm && (!accept_bounds || !vm_is_trailing_zeros);
+            vr + u64::from(is_out_of_bounds || last_removed_digit >= 5)
+        } else {
+            // common case
+            let mut round_up = false;
+            while vp / 10 > vm / 10 {
+                round_up = vr % 10 >= 5;
+                vr /= 10;
+                vp /= 10;
+                vm /= 10;
+                removed += 1;
+            }
+            vr + u64::from(vr == vm || round_up)
+        };
+        Self {
+            digits,
+            exponent: e10 + removed,
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use super::*;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+impl Value {
+    /// Encodes into [UBJSON] (Draft 12), choosing the smallest encoding for each number:
+    /// whole numbers within the 64-bit integer range as (the smallest) integers,
+    /// other numbers as `float32` when that preserves them, otherwise as `float64`.
+    ///
+    /// Arrays and objects of 5 or more elements that all have the same type marker
+    /// (e.g. small integers, or strings) are written as optimized containers
+    /// (with a type and a count, so without per-element markers).
+    ///
+    /// [UBJSON]: https://ubjson.org/
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    ///
+    /// assert_eq!(json!({"a": [1, 1.5, "x"], "b": null}).to_ubjson(), [
+    ///     b'{', b'i', 1, b'a', b'[', b'i', 1, b'd', 0x3f, 0xc0, 0x00, 0x00, b'S', b'i', 1, b'x', b']',
+    ///     b'i', 1, b'b', b'Z', b'}',
+    /// ]);
+    /// // `i` and `U` markers: not optimized
+    /// assert_eq!(json!([1, 2, 3, 4, 200]).to_ubjson(), [
+    ///     b'[', b'i', 1, b'i', 2, b'i', 3, b'i', 4, b'U', 200, b']',
+    /// ]);
+    /// // optimized: `[$i#` followed by the count and the payloads
+    /// assert_eq!(json!([10, 20, 30, 40, 50]).to_ubjson(), [
+    ///     b'[', b'$', b'i', b'#', b'i', 5, 10, 20, 30, 40, 50,
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn to_ubjson(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        helper::encode(self, &mut out);
+        out
+    }
+
+    /// Decodes a single [UBJSON] (Draft 12) value, with default options
+    /// (rejecting non-finite floats).
+    ///
+    /// Optimized containers (with a type and/or a count) and no-op markers
+    /// (within containers without a count) are accepted, except containers
+    /// typed with a marker without payload (`Z`, `N`, `T` or `F`), since nothing
+    /// would bound their count. Integers are converted to `f64` (so possibly
+    /// rounded beyond ±2<sup>53</sup>), as are high-precision numbers (`H`),
+    /// which must have valid JSON syntax; characters (`C`) become strings.
+    ///
+    /// [UBJSON]: https://ubjson.org/
+    ///
+    /// # Errors
+    ///
+    /// If the data is invalid, not completely consumed,
+    /// or contains a value rejected by the options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{UbjsonError, UbjsonErrorKind, Value};
+    ///
+    /// // `{#i2` with `a` → typed array `[$U#i3` of bytes, and `b` → character 'x'
+    /// let bytes = [
+    ///     b'{', b'#', b'i', 2,
+    ///     b'i', 1, b'a', b'[', b'$', b'U', b'#', b'i', 3, 1, 2, 255,
+    ///     b'i', 1, b'b', b'C', b'x',
+    /// ];
+    /// assert_eq!(Value::from_ubjson(&bytes), Ok(json!({"a": [1, 2, 255], "b": "x"})));
+    /// assert_eq!(Value::from_ubjson(b"[NHi\x0512e-3N]"), Ok(json!([0.012])));
+    ///
+    /// let error = Value::from_ubjson(b"[$Z#i\x03").unwrap_err();
+    /// assert_eq!(error, UbjsonError { kind: UbjsonErrorKind::InvalidMarker(b'Z'), offset: 2 });
+    /// assert_eq!(error.to_string(), "invalid marker 'Z' at byte 2");
+    /// ```
+    ///
+    /// Values at the boundaries of each encoding survive a round trip
+    /// (and back to JSON text):
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{Arr, Value};
+    ///
+    /// let mut values = Arr::new();
+    /// for i in [-129_i64, -128, 127, 128, 255, 256, -32769, 32767, 32768, -(1 << 31) - 1, 1 << 31, i64::MIN] {
+    ///     values.push(Value::try_from(i as f64).unwrap());
+    /// }
+    /// for f in [0.5, 0.1, -1e-300, f64::from(f32::MAX), 1e300] {
+    ///     values.push(Value::try_from(f).unwrap());
+    /// }
+    /// for str in [String::new(), "é".into(), "a".repeat(255), "a".repeat(256)] {
+    ///     values.push(Value::from(str));
+    /// }
+    /// // containers are optimized only from 5 elements of the same (payload) type
+    /// values.extend([json!([1, 2, 3, 4]), json!([1, 2, 3, 4, 5]), json!([1, 2, 3, 4, 300]), json!(["a", "b", "c", "d", "e"])]);
+    /// values.extend([json!([[], [], [], [], []]), json!({"a": 1, "b": 2, "c": 3, "d": 4, "e": 5}), json!({"": {}})]);
+    ///
+    /// for value in values.iter().chain([&Value::Array(values.clone())]) {
+    ///     let decoded = Value::from_ubjson(&value.to_ubjson()).unwrap();
+    ///     assert_eq!(&decoded, value);
+    ///     assert_eq!(&decoded.to_string().parse::<Value>().unwrap(), value);
+    /// }
+    /// ```
+    pub fn from_ubjson(bytes: &[u8]) -> Result<Self, UbjsonError> {
+        Self::from_ubjson_with(bytes, &UbjsonOptions::default())
+    }
+
+    /// Decodes a single [UBJSON] (Draft 12) value, mapping non-finite floats
+    /// as configured (see [`UbjsonOptions`]).
+    ///
+    /// [UBJSON]: https://ubjson.org/
+    ///
+    /// # Errors
+    ///
+    /// See [`Value::from_ubjson`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    /// use rustic_json::{UbjsonErrorKind, UbjsonOptions, Value};
+    ///
+    /// let bytes = [b'[', b'd', 0x7f, 0x80, 0x00, 0x00, b'i', 1, b']'];
+    /// assert_eq!(Value::from_ubjson_with(&bytes, &UbjsonOptions::lossy()), Ok(json!([null, 1])));
+    ///
+    /// let options = UbjsonOptions { max_depth: 1, ..UbjsonOptions::default() };
+    /// let error = Value::from_ubjson_with(b"[[]]", &options).unwrap_err();
+    /// assert_eq!((error.kind, error.offset), (UbjsonErrorKind::TooDeep, 1));
+    /// ```
+    pub fn from_ubjson_with(bytes: &[u8], options: &UbjsonOptions) -> Result<Self, UbjsonError> {
+        helper::decode(bytes, options)
+    }
+}
+
+/// Options for [`Value::from_ubjson_with`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UbjsonOptions {
+    /// NaN and infinities: converted to `null`.
+    pub non_finite_floats: NonJsonPolicy,
+    /// Maximum nesting of arrays and objects.
+    pub max_depth: usize,
+}
+
+impl UbjsonOptions {
+    /// Converting every value without a JSON equivalent.
+    #[must_use]
+    pub fn lossy() -> Self {
+        Self {
+            non_finite_floats: NonJsonPolicy::Convert,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for UbjsonOptions {
+    /// Rejecting every value without a JSON equivalent.
+    fn default() -> Self {
+        Self {
+            non_finite_floats: NonJsonPolicy::Reject,
+            max_depth: 512,
+        }
+    }
+}
+
+/// Error from [`Value::from_ubjson`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UbjsonError {
+    pub kind: UbjsonErrorKind,
+    /// Of the start of the offending value (or of the end of data).
+    pub offset: usize,
+}
+
+impl Display for UbjsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl Error for UbjsonError {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum UbjsonErrorKind {
+    PrematureEnd,
+    /// Data after the (first) value.
+    TrailingData,
+    /// An unknown (or misplaced) marker.
+    InvalidMarker(u8),
+    /// A negative length or count, or a count exceeding the remaining data.
+    InvalidLength,
+    InvalidUtf8,
+    /// A high-precision number without valid JSON syntax (or out of the `f64` range),
+    /// or a character beyond ASCII.
+    InvalidValue,
+    DuplicateKey(Str),
+    TooDeep,
+    NonFiniteFloat,
+}
+
+impl Display for UbjsonErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PrematureEnd => f.write_str("premature end of data"),
+            Self::TrailingData => f.write_str("trailing data"),
+            Self::InvalidMarker(marker) => write!(f, "invalid marker {:?}", char::from(*marker)),
+            Self::InvalidLength => f.write_str("invalid length"),
+            Self::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            Self::InvalidValue => f.write_str("invalid value"),
+            Self::DuplicateKey(key) => write!(f, "duplicate key {:?}", key),
+            Self::TooDeep => f.write_str("too deep nesting"),
+            Self::NonFiniteFloat => f.write_str("unsupported non-finite float"),
+        }
+    }
+}
+
+mod helper;
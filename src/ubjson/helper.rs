@@ -0,0 +1,362 @@
+use super::*;
+
+type DecodeResult<T> = Result<T, UbjsonError>;
+
+use UbjsonErrorKind as K;
+
+/// The smallest number of elements for which an optimized container is shorter
+/// (its header `$`, type, `#` and count takes at least 4 more bytes than the end marker).
+const MIN_OPTIMIZED_LEN: usize = 5;
+
+pub(super) fn encode(value: &Value, out: &mut Vec<u8>) {
+    let marker = marker(value);
+    out.push(marker);
+    encode_payload(value, marker, out);
+}
+
+fn marker(value: &Value) -> u8 {
+    match value {
+        Value::Null => b'Z',
+        Value::Boolean(true) => b'T',
+        Value::Boolean(false) => b'F',
+        Value::Number(num) => number_marker(*num),
+        Value::String(_) => b'S',
+        Value::Array(_) => b'[',
+        Value::Object(_) => b'{',
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn number_marker(num: Num) -> u8 {
+    let f = num.get();
+    if let Some(i) = num.as_i64() {
+        int_marker(i)
+    } else if f64::from(f as f32) == f {
+        b'd'
+    } else {
+        b'D'
+    }
+}
+
+fn int_marker(i: i64) -> u8 {
+    if i8::try_from(i).is_ok() {
+        b'i'
+    } else if u8::try_from(i).is_ok() {
+        b'U'
+    } else if i16::try_from(i).is_ok() {
+        b'I'
+    } else if i32::try_from(i).is_ok() {
+        b'l'
+    } else {
+        b'L'
+    }
+}
+
+/// For the given marker (as returned by [`marker`]).
+#[allow(clippy::cast_possible_truncation)]
+fn encode_payload(value: &Value, marker: u8, out: &mut Vec<u8>) {
+    match value {
+        Value::Null | Value::Boolean(_) => {}
+        Value::Number(num) => match marker {
+            b'd' => out.extend_from_slice(&(num.get() as f32).to_be_bytes()),
+            b'D' => out.extend_from_slice(&num.get().to_be_bytes()),
+            _ => write_int_payload(num.get() as i64, marker, out),
+        },
+        Value::String(str) => {
+            write_int(str.len(), out);
+            out.extend_from_slice(str.as_bytes());
+        }
+        Value::Array(arr) => {
+            let typ = write_container_header(arr.iter(), arr.len(), out);
+            for element in arr {
+                encode_element(element, typ, out);
+            }
+            if typ.is_none() {
+                out.push(b']');
+            }
+        }
+        Value::Object(obj) => {
+            let typ = write_container_header(obj.values(), obj.len(), out);
+            for (key, value) in obj {
+                write_int(key.len(), out);
+                out.extend_from_slice(key.as_bytes());
+                encode_element(value, typ, out);
+            }
+            if typ.is_none() {
+                out.push(b'}');
+            }
+        }
+    }
+}
+
+/// Writes the type and count of an optimized container if worth it, returning the type.
+fn write_container_header<'v>(
+    mut elements: impl Iterator<Item = &'v Value>,
+    len: usize,
+    out: &mut Vec<u8>,
+) -> Option<u8> {
+    if len < MIN_OPTIMIZED_LEN {
+        return None;
+    }
+    let typ = marker(elements.next()?);
+    if matches!(typ, b'Z' | b'T' | b'F' | b'[' | b'{') || elements.any(|value| marker(value) != typ)
+    {
+        return None;
+    }
+    out.extend_from_slice(&[b'$', typ, b'#']);
+    write_int(len, out);
+    Some(typ)
+}
+
+fn encode_element(value: &Value, typ: Option<u8>, out: &mut Vec<u8>) {
+    match typ {
+        Some(typ) => encode_payload(value, typ, out),
+        None => encode(value, out),
+    }
+}
+
+fn write_int(len: usize, out: &mut Vec<u8>) {
+    let i = i64::try_from(len).expect("length within 63 bits");
+    let marker = int_marker(i);
+    out.push(marker);
+    write_int_payload(i, marker, out);
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn write_int_payload(i: i64, marker: u8, out: &mut Vec<u8>) {
+    match marker {
+        b'i' => out.push(i as i8 as u8),
+        b'U' => out.push(i as u8),
+        b'I' => out.extend_from_slice(&(i as i16).to_be_bytes()),
+        b'l' => out.extend_from_slice(&(i as i32).to_be_bytes()),
+        _ => out.extend_from_slice(&i.to_be_bytes()),
+    }
+}
+
+pub(super) fn decode(bytes: &[u8], options: &UbjsonOptions) -> DecodeResult<Value> {
+    let mut decoder = Decoder {
+        bytes,
+        offset: 0,
+        options,
+        depth: 0,
+    };
+    let value = decoder.decode_value()?;
+    if decoder.offset < bytes.len() {
+        return Err(decoder.error_at(K::TrailingData, decoder.offset));
+    }
+    Ok(value)
+}
+
+struct Decoder<'b, 'o> {
+    bytes: &'b [u8],
+    offset: usize,
+    options: &'o UbjsonOptions,
+    depth: usize,
+}
+
+impl<'b> Decoder<'b, '_> {
+    fn error_at(&self, kind: UbjsonErrorKind, offset: usize) -> UbjsonError {
+        UbjsonError { kind, offset }
+    }
+
+    fn take(&mut self, len: usize) -> DecodeResult<&'b [u8]> {
+        match self
+            .bytes
+            .get(self.offset..)
+            .and_then(|rest| rest.get(..len))
+        {
+            Some(taken) => {
+                self.offset += len;
+                Ok(taken)
+            }
+            None => Err(self.error_at(K::PrematureEnd, self.bytes.len())),
+        }
+    }
+
+    fn array<const N: usize>(&mut self) -> DecodeResult<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("N bytes"))
+    }
+
+    fn read_u8(&mut self) -> DecodeResult<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn peek(&self) -> DecodeResult<u8> {
+        self.bytes
+            .get(self.offset)
+            .copied()
+            .ok_or_else(|| self.error_at(K::PrematureEnd, self.bytes.len()))
+    }
+
+    /// A length or count: a non-negative integer (with its marker).
+    fn read_len(&mut self) -> DecodeResult<usize> {
+        let start = self.offset;
+        let len = match self.read_u8()? {
+            b'i' => i64::from(i8::from_be_bytes(self.array()?)),
+            b'U' => i64::from(self.read_u8()?),
+            b'I' => i64::from(i16::from_be_bytes(self.array()?)),
+            b'l' => i64::from(i32::from_be_bytes(self.array()?)),
+            b'L' => i64::from_be_bytes(self.array()?),
+            marker => return Err(self.error_at(K::InvalidMarker(marker), start)),
+        };
+        usize::try_from(len).map_err(|_| self.error_at(K::InvalidLength, start))
+    }
+
+    fn read_string(&mut self) -> DecodeResult<&'b str> {
+        let start = self.offset;
+        let len = self.read_len()?;
+        let bytes = self.take(len)?;
+        std::str::from_utf8(bytes).map_err(|_| self.error_at(K::InvalidUtf8, start))
+    }
+
+    fn enter(&mut self, start: usize) -> DecodeResult<()> {
+        if self.depth >= self.options.max_depth {
+            return Err(self.error_at(K::TooDeep, start));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn decode_value(&mut self) -> DecodeResult<Value> {
+        let start = self.offset;
+        let marker = self.read_u8()?;
+        self.decode_marked(marker, start)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn decode_marked(&mut self, marker: u8, start: usize) -> DecodeResult<Value> {
+        let f = match marker {
+            b'Z' => return Ok(Value::Null),
+            b'T' => return Ok(Value::Boolean(true)),
+            b'F' => return Ok(Value::Boolean(false)),
+            b'i' => f64::from(i8::from_be_bytes(self.array()?)),
+            b'U' => f64::from(self.read_u8()?),
+            b'I' => f64::from(i16::from_be_bytes(self.array()?)),
+            b'l' => f64::from(i32::from_be_bytes(self.array()?)),
+            b'L' => i64::from_be_bytes(self.array()?) as f64,
+            b'd' => f64::from(f32::from_be_bytes(self.array()?)),
+            b'D' => f64::from_be_bytes(self.array()?),
+            b'H' => {
+                return match self.read_string()?.parse() {
+                    Ok(Value::Number(num)) => Ok(Value::Number(num)),
+                    _ => Err(self.error_at(K::InvalidValue, start)),
+                };
+            }
+            b'C' => {
+                let char = self.read_u8()?;
+                if !char.is_ascii() {
+                    return Err(self.error_at(K::InvalidValue, start));
+                }
+                return Ok(Value::from(String::from(char::from(char))));
+            }
+            b'S' => return Ok(Value::from(String::from(self.read_string()?))),
+            b'[' => return self.decode_array(start),
+            b'{' => return self.decode_object(start),
+            _ => return Err(self.error_at(K::InvalidMarker(marker), start)),
+        };
+        match Num::new(f) {
+            Some(num) => Ok(Value::Number(num)),
+            None => match self.options.non_finite_floats {
+                NonJsonPolicy::Reject => Err(self.error_at(K::NonFiniteFloat, start)),
+                NonJsonPolicy::Convert => Ok(Value::Null),
+            },
+        }
+    }
+
+    /// The type and count of an optimized container, if any.
+    fn read_container_header(&mut self) -> DecodeResult<(Option<u8>, Option<usize>)> {
+        let mut typ = None;
+        if self.peek()? == b'$' {
+            self.offset += 1;
+            let start = self.offset;
+            match self.read_u8()? {
+                marker @ (b'Z' | b'N' | b'T' | b'F') => {
+                    return Err(self.error_at(K::InvalidMarker(marker), start));
+                }
+                marker => typ = Some(marker),
+            }
+            if self.peek()? != b'#' {
+                return Err(self.error_at(K::InvalidMarker(self.peek()?), self.offset));
+            }
+        }
+        if self.peek()? != b'#' {
+            return Ok((None, None));
+        }
+        self.offset += 1;
+        let start = self.offset;
+        let count = self.read_len()?;
+        // every element takes at least one byte
+        if count > self.bytes.len() - self.offset {
+            return Err(self.error_at(K::InvalidLength, start));
+        }
+        Ok((typ, Some(count)))
+    }
+
+    fn decode_element(&mut self, typ: Option<u8>) -> DecodeResult<Value> {
+        match typ {
+            Some(typ) => self.decode_marked(typ, self.offset),
+            None => self.decode_value(),
+        }
+    }
+
+    /// Whether the end marker follows (after any no-op markers).
+    fn at_end(&mut self, end: u8) -> DecodeResult<bool> {
+        while self.peek()? == b'N' {
+            self.offset += 1;
+        }
+        if self.peek()? == end {
+            self.offset += 1;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn decode_array(&mut self, start: usize) -> DecodeResult<Value> {
+        self.enter(start)?;
+        let mut arr = Arr::new();
+        match self.read_container_header()? {
+            (typ, Some(count)) => {
+                for _ in 0..count {
+                    arr.push(self.decode_element(typ)?);
+                }
+            }
+            (_, None) => {
+                while !self.at_end(b']')? {
+                    arr.push(self.decode_value()?);
+                }
+            }
+        }
+        self.depth -= 1;
+        Ok(Value::Array(arr))
+    }
+
+    fn decode_object(&mut self, start: usize) -> DecodeResult<Value> {
+        self.enter(start)?;
+        let mut obj = Obj::new();
+        match self.read_container_header()? {
+            (typ, Some(count)) => {
+                for _ in 0..count {
+                    self.decode_member(&mut obj, typ)?;
+                }
+            }
+            (_, None) => {
+                while !self.at_end(b'}')? {
+                    self.decode_member(&mut obj, None)?;
+                }
+            }
+        }
+        self.depth -= 1;
+        Ok(Value::Object(obj))
+    }
+
+    fn decode_member(&mut self, obj: &mut Obj, typ: Option<u8>) -> DecodeResult<()> {
+        let start = self.offset;
+        let key = Str::from(String::from(self.read_string()?));
+        if obj.contains_key(&key) {
+            return Err(self.error_at(K::DuplicateKey(key), start));
+        }
+        let value = self.decode_element(typ)?;
+        obj.insert(key, value);
+        Ok(())
+    }
+}
@@ -0,0 +1,389 @@
+use super::*;
+use helper::{Children, ChildrenMut};
+
+/// Read-only traversal of a [`Value`], with [`Value::visit`].
+///
+/// Values are visited in pre-order, each with its location. By default,
+/// [`visit_value`](Visit::visit_value) dispatches to the method for its variant,
+/// which does nothing (and visits the contents of arrays and objects).
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::{JsonPointer, Obj, Str, Visit};
+///
+/// /// Locations of all the strings, except in "private" objects.
+/// struct Strings(Vec<String>);
+///
+/// impl Visit for Strings {
+///     fn visit_string(&mut self, path: &JsonPointer, _: &Str) {
+///         self.0.push(path.to_string());
+///     }
+///
+///     fn visit_object(&mut self, _: &JsonPointer, obj: &Obj) -> bool {
+///         obj.get("private") != Some(&json!(true))
+///     }
+/// }
+///
+/// let value = json!({"a": ["x", 1, {"b": "y"}], "c": {"private": true, "d": "z"}, "e": "w"});
+/// let mut strings = Strings(Vec::new());
+/// value.visit(&mut strings);
+/// assert_eq!(strings.0, ["/a/0", "/a/2/b", "/e"]);
+/// ```
+///
+/// Without recursion, so deeply nested values are fine:
+///
+/// ```
+/// use rustic_json::{JsonPointer, Value, Visit};
+///
+/// struct Depth(usize);
+///
+/// impl Visit for Depth {
+///     fn visit_value(&mut self, path: &JsonPointer, _: &Value) -> bool {
+///         self.0 = self.0.max(path.as_str().len() / 2); // "/0" per level
+///         true
+///     }
+/// }
+///
+/// let value = (0..100_000).fold(Value::Null, |value, _| Value::Array(vec![value]));
+/// let mut depth = Depth(0);
+/// value.visit(&mut depth);
+/// assert_eq!(depth.0, 100_000);
+/// # // (unlike dropping)
+/// # let mut value = value;
+/// # while let Value::Array(mut arr) = value {
+/// #     value = arr.pop().unwrap();
+/// # }
+/// ```
+pub trait Visit {
+    /// Called for each value, returning whether to visit its contents (if any).
+    fn visit_value(&mut self, path: &JsonPointer, value: &Value) -> bool {
+        match value {
+            Value::Null => self.visit_null(path),
+            Value::Boolean(b) => self.visit_boolean(path, *b),
+            Value::Number(num) => self.visit_number(path, *num),
+            Value::String(str) => self.visit_string(path, str),
+            Value::Array(arr) => return self.visit_array(path, arr),
+            Value::Object(obj) => return self.visit_object(path, obj),
+        }
+        true
+    }
+
+    fn visit_null(&mut self, _path: &JsonPointer) {}
+
+    fn visit_boolean(&mut self, _path: &JsonPointer, _b: bool) {}
+
+    fn visit_number(&mut self, _path: &JsonPointer, _num: Num) {}
+
+    fn visit_string(&mut self, _path: &JsonPointer, _str: &Str) {}
+
+    /// Called before the elements, returning whether to visit them.
+    fn visit_array(&mut self, _path: &JsonPointer, _arr: &Arr) -> bool {
+        true
+    }
+
+    /// Called before the members, returning whether to visit them.
+    fn visit_object(&mut self, _path: &JsonPointer, _obj: &Obj) -> bool {
+        true
+    }
+}
+
+/// Mutating traversal of a [`Value`], with [`Value::visit_mut`].
+///
+/// As [`Visit`], but arrays and objects are visited after any changes made
+/// when visiting them (e.g. a value replaced by an object has its new members visited).
+///
+/// # Examples
+///
+/// ```
+/// use rustic_json::json;
+/// use rustic_json::{JsonPointer, Obj, Str, Value, VisitMut};
+///
+/// /// Redacts secrets and trims strings.
+/// struct Cleanup;
+///
+/// impl VisitMut for Cleanup {
+///     fn visit_string_mut(&mut self, _: &JsonPointer, str: &mut Str) {
+///         *str = Str::from(str.trim().to_owned());
+///     }
+///
+///     fn visit_object_mut(&mut self, _: &JsonPointer, obj: &mut Obj) -> bool {
+///         if let Some(password) = obj.get_mut("password") {
+///             *password = json!("***");
+///         }
+///         true
+///     }
+/// }
+///
+/// let mut value = json!([{"user": " alice ", "password": " secret "}, null]);
+/// value.visit_mut(&mut Cleanup);
+/// assert_eq!(value, json!([{"user": "alice", "password": "***"}, null]));
+/// ```
+pub trait VisitMut {
+    /// Called for each value, returning whether to visit its contents (if any).
+    fn visit_value_mut(&mut self, path: &JsonPointer, value: &mut Value) -> bool {
+        match value {
+            Value::Null => self.visit_null_mut(path, value),
+            Value::Boolean(b) => self.visit_boolean_mut(path, b),
+            Value::Number(num) => self.visit_number_mut(path, num),
+            Value::String(str) => self.visit_string_mut(path, str),
+            Value::Array(arr) => return self.visit_array_mut(path, arr),
+            Value::Object(obj) => return self.visit_object_mut(path, obj),
+        }
+        true
+    }
+
+    /// With the value itself, e.g. to replace it.
+    fn visit_null_mut(&mut self, _path: &JsonPointer, _value: &mut Value) {}
+
+    fn visit_boolean_mut(&mut self, _path: &JsonPointer, _b: &mut bool) {}
+
+    fn visit_number_mut(&mut self, _path: &JsonPointer, _num: &mut Num) {}
+
+    fn visit_string_mut(&mut self, _path: &JsonPointer, _str: &mut Str) {}
+
+    /// Called before the elements, returning whether to visit them.
+    fn visit_array_mut(&mut self, _path: &JsonPointer, _arr: &mut Arr) -> bool {
+        true
+    }
+
+    /// Called before the members, returning whether to visit them.
+    fn visit_object_mut(&mut self, _path: &JsonPointer, _obj: &mut Obj) -> bool {
+        true
+    }
+}
+
+impl Value {
+    /// Traverses this value (see [`Visit`]), without recursion.
+    pub fn visit(&self, visitor: &mut impl Visit) {
+        let mut path = JsonPointer::root();
+        let mut stack = Vec::new();
+        if visitor.visit_value(&path, self) {
+            stack.extend(Children::of(self));
+        }
+        while let Some(children) = stack.last_mut() {
+            let Some((token, child)) = children.next() else {
+                stack.pop();
+                path.pop();
+                continue;
+            };
+            token.push_to(&mut path);
+            match Children::of(child) {
+                Some(grandchildren) if visitor.visit_value(&path, child) => {
+                    stack.push(grandchildren);
+                }
+                Some(_) => {
+                    path.pop();
+                }
+                None => {
+                    visitor.visit_value(&path, child);
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    /// Traverses this value (see [`VisitMut`]), without recursion.
+    pub fn visit_mut(&mut self, visitor: &mut impl VisitMut) {
+        let mut path = JsonPointer::root();
+        let mut stack = Vec::new();
+        if visitor.visit_value_mut(&path, self) {
+            stack.extend(ChildrenMut::of(self));
+        }
+        while let Some(children) = stack.last_mut() {
+            let Some((token, child)) = children.next() else {
+                stack.pop();
+                path.pop();
+                continue;
+            };
+            token.push_to(&mut path);
+            if visitor.visit_value_mut(&path, child) {
+                if let Some(grandchildren) = ChildrenMut::of(child) {
+                    stack.push(grandchildren);
+                    continue;
+                }
+            }
+            path.pop();
+        }
+    }
+
+    /// Iterates over this value and all the values it contains, with their locations,
+    /// in pre-order (each array or object before its contents), without recursion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    ///
+    /// let value = json!({"a": [1, {"b": null}], "c": true});
+    /// let paths: Vec<_> = value.walk().map(|(path, _)| path.to_string()).collect();
+    /// assert_eq!(paths, ["", "/a", "/a/0", "/a/1", "/a/1/b", "/c"]);
+    ///
+    /// let (path, found) = value.walk().find(|(_, value)| value.is_null()).unwrap();
+    /// assert_eq!((path.as_str(), found), ("/a/1/b", &json!(null)));
+    /// ```
+    #[must_use]
+    pub fn walk(&self) -> Walk<'_> {
+        Walk::new(self, false)
+    }
+
+    /// As [`Value::walk`], but in post-order (each array or object after its contents).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustic_json::json;
+    ///
+    /// let value = json!({"a": [1, {"b": null}], "c": true});
+    /// let paths: Vec<_> = value.walk_post_order().map(|(path, _)| path.to_string()).collect();
+    /// assert_eq!(paths, ["/a/0", "/a/1/b", "/a/1", "/a", "/c", ""]);
+    /// ```
+    #[must_use]
+    pub fn walk_post_order(&self) -> Walk<'_> {
+        Walk::new(self, true)
+    }
+}
+
+/// Iterator from [`Value::walk`] or [`Value::walk_post_order`].
+pub struct Walk<'v> {
+    post_order: bool,
+    /// Not yet visited.
+    root: Option<&'v Value>,
+    /// The arrays and objects being iterated over, with their contents left.
+    stack: Vec<(&'v Value, Children<'v>)>,
+    /// Of the array or object at the top of the stack.
+    path: JsonPointer,
+}
+
+impl<'v> Walk<'v> {
+    fn new(root: &'v Value, post_order: bool) -> Self {
+        Self {
+            post_order,
+            root: Some(root),
+            stack: Vec::new(),
+            path: JsonPointer::root(),
+        }
+    }
+
+    /// Starts iterating over the contents of the value (if any),
+    /// returning whether to yield it now.
+    fn enter(&mut self, value: &'v Value) -> bool {
+        match Children::of(value) {
+            Some(children) => {
+                self.stack.push((value, children));
+                !self.post_order
+            }
+            None => true,
+        }
+    }
+}
+
+impl<'v> Iterator for Walk<'v> {
+    type Item = (JsonPointer, &'v Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            if self.enter(root) {
+                return Some((JsonPointer::root(), root));
+            }
+        }
+        loop {
+            let (value, children) = self.stack.last_mut()?;
+            let Some((token, child)) = children.next() else {
+                let value = *value;
+                self.stack.pop();
+                let path = self.path.clone();
+                self.path.pop();
+                if self.post_order {
+                    return Some((path, value));
+                }
+                continue;
+            };
+            token.push_to(&mut self.path);
+            let path = self.path.clone();
+            if Children::of(child).is_none() {
+                self.path.pop();
+            }
+            if self.enter(child) {
+                return Some((path, child));
+            }
+        }
+    }
+}
+
+pub(crate) mod helper {
+    use super::*;
+    use std::collections::btree_map;
+    use std::iter::Enumerate;
+    use std::slice;
+
+    /// Reference token of a child (not allocated).
+    pub(crate) enum Token<'v> {
+        Index(usize),
+        Key(&'v str),
+    }
+
+    impl Token<'_> {
+        pub(super) fn push_to(&self, path: &mut JsonPointer) {
+            match self {
+                Self::Index(index) => path.push_index(*index),
+                Self::Key(key) => path.push_key(key),
+            }
+        }
+    }
+
+    pub(crate) enum Children<'v> {
+        Array(Enumerate<slice::Iter<'v, Value>>),
+        Object(btree_map::Iter<'v, Str, Value>),
+    }
+
+    impl<'v> Children<'v> {
+        /// For arrays and objects.
+        pub(crate) fn of(value: &'v Value) -> Option<Self> {
+            match value {
+                Value::Array(arr) => Some(Self::Array(arr.iter().enumerate())),
+                Value::Object(obj) => Some(Self::Object(obj.iter())),
+                _ => None,
+            }
+        }
+    }
+
+    impl<'v> Iterator for Children<'v> {
+        type Item = (Token<'v>, &'v Value);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            match self {
+                Self::Array(elements) => elements.next().map(|(i, v)| (Token::Index(i), v)),
+                Self::Object(members) => members.next().map(|(k, v)| (Token::Key(k), v)),
+            }
+        }
+    }
+
+    pub(super) enum ChildrenMut<'v> {
+        Array(Enumerate<slice::IterMut<'v, Value>>),
+        Object(btree_map::IterMut<'v, Str, Value>),
+    }
+
+    impl<'v> ChildrenMut<'v> {
+        /// For arrays and objects.
+        pub(super) fn of(value: &'v mut Value) -> Option<Self> {
+            match value {
+                Value::Array(arr) => Some(Self::Array(arr.iter_mut().enumerate())),
+                Value::Object(obj) => Some(Self::Object(obj.iter_mut())),
+                _ => None,
+            }
+        }
+    }
+
+    impl<'v> Iterator for ChildrenMut<'v> {
+        type Item = (Token<'v>, &'v mut Value);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            match self {
+                Self::Array(elements) => elements.next().map(|(i, v)| (Token::Index(i), v)),
+                Self::Object(members) => members.next().map(|(k, v)| (Token::Key(k), v)),
+            }
+        }
+    }
+}